@@ -113,7 +113,7 @@ use std::{
     mem,
     num::NonZeroU32,
     ops::{ControlFlow, Deref, DerefMut, Range, RangeInclusive},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -426,6 +426,9 @@ pub struct Editor {
     highlighted_rows: HashMap<TypeId, Vec<(usize, Range<Anchor>, Hsla)>>,
     background_highlights: TreeMap<TypeId, BackgroundHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
+    /// The matches marked in the scrollbar, along with the index of the one to emphasize,
+    /// see [`Editor::set_scrollbar_match_markers`].
+    scrollbar_match_markers: Option<(Arc<[Range<Anchor>]>, Option<usize>)>,
     nav_history: Option<ItemNavHistory>,
     context_menu: RwLock<Option<ContextMenu>>,
     mouse_context_menu: Option<MouseContextMenu>,
@@ -476,6 +479,8 @@ pub struct Editor {
                 + Fn(&mut Self, DisplayPoint, &mut ViewContext<Self>) -> Option<View<ui::ContextMenu>>,
         >,
     >,
+    excerpt_header_path:
+        Option<Box<dyn 'static + Fn(&dyn language::File, &AppContext) -> Option<PathBuf>>>,
     last_bounds: Option<Bounds<Pixels>>,
     expect_bounds_change: Option<Bounds<Pixels>>,
 }
@@ -1460,6 +1465,7 @@ impl Editor {
             highlighted_rows: HashMap::default(),
             background_highlights: Default::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
+            scrollbar_match_markers: None,
             nav_history: None,
             context_menu: RwLock::new(None),
             mouse_context_menu: None,
@@ -1502,6 +1508,7 @@ impl Editor {
             vim_replace_map: Default::default(),
             show_inline_completions: mode == EditorMode::Full,
             custom_context_menu: None,
+            excerpt_header_path: None,
             show_git_blame_gutter: false,
             show_git_blame_inline: false,
             show_git_blame_inline_delay_task: None,
@@ -1708,6 +1715,28 @@ impl Editor {
         self.custom_context_menu = Some(Box::new(f))
     }
 
+    /// Overrides the path shown in the headers of the excerpts of every file, which falls back
+    /// to the default one when `f` returns `None`.
+    pub fn set_excerpt_header_path(
+        &mut self,
+        f: impl 'static + Fn(&dyn language::File, &AppContext) -> Option<PathBuf>,
+    ) {
+        self.excerpt_header_path = Some(Box::new(f))
+    }
+
+    pub(crate) fn excerpt_header_path(
+        &self,
+        buffer: &language::BufferSnapshot,
+        include_root: bool,
+        cx: &AppContext,
+    ) -> Option<PathBuf> {
+        self.excerpt_header_path
+            .as_ref()
+            .zip(buffer.file())
+            .and_then(|(header_path, file)| header_path(file.as_ref(), cx))
+            .or_else(|| buffer.resolve_file_path(cx, include_root))
+    }
+
     pub fn set_completion_provider(&mut self, hub: Box<dyn CompletionProvider>) {
         self.completion_provider = Some(hub);
     }
@@ -9233,6 +9262,34 @@ impl Editor {
         Some(text_highlights)
     }
 
+    /// Marks the rows of `ranges` in the scrollbar, emphasizing the one at `active_index`.
+    /// Unlike the markers of the search highlights, these are shown in multi-buffers too, and
+    /// clicking one of them selects its range.
+    pub fn set_scrollbar_match_markers(
+        &mut self,
+        ranges: Arc<[Range<Anchor>]>,
+        active_index: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.scrollbar_match_markers = Some((ranges, active_index));
+        self.scrollbar_marker_state.dirty = true;
+        cx.notify();
+    }
+
+    pub fn clear_scrollbar_match_markers(&mut self, cx: &mut ViewContext<Self>) {
+        if self.scrollbar_match_markers.take().is_some() {
+            self.scrollbar_marker_state.dirty = true;
+            cx.notify();
+        }
+    }
+
+    #[cfg(feature = "test-support")]
+    pub fn scrollbar_match_markers(&self) -> Option<(&[Range<Anchor>], Option<usize>)> {
+        self.scrollbar_match_markers
+            .as_ref()
+            .map(|(ranges, active_index)| (ranges.as_ref(), *active_index))
+    }
+
     #[cfg(feature = "test-support")]
     pub fn all_text_background_highlights(
         &mut self,
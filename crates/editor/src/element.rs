@@ -13,7 +13,7 @@ use crate::{
     },
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MouseContextMenu},
-    scroll::scroll_amount::ScrollAmount,
+    scroll::{scroll_amount::ScrollAmount, Autoscroll},
     CursorShape, DisplayPoint, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, GutterDimensions, HalfPageDown, HalfPageUp,
     HoveredCursor, LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point, SelectPhase, Selection,
@@ -1003,6 +1003,9 @@ impl EditorElement {
                     // Diagnostics
                     (is_singleton && scrollbar_settings.diagnostics && snapshot.buffer_snapshot.has_diagnostics())
                     ||
+                    // Match markers
+                    editor.scrollbar_match_markers.is_some()
+                    ||
                     // Scrollmanager
                     editor.scroll_manager.scrollbars_visible()
             }
@@ -1609,7 +1612,10 @@ impl EditorElement {
                     });
 
                     let element = if *starts_new_buffer {
-                        let path = buffer.resolve_file_path(cx, include_root);
+                        let path =
+                            self.editor
+                                .read(cx)
+                                .excerpt_header_path(buffer, include_root, cx);
                         let mut filename = None;
                         let mut parent_path = None;
                         // Can't use .and_then() because `.file_name()` and `.parent()` return references :(
@@ -2567,12 +2573,22 @@ impl EditorElement {
             cx.on_mouse_event({
                 let editor = self.editor.clone();
                 let hitbox = scrollbar_layout.hitbox.clone();
+                let scrollbar_layout = scrollbar_layout.clone();
                 move |event: &MouseDownEvent, phase, cx| {
                     if phase == DispatchPhase::Capture || !hitbox.is_hovered(cx) {
                         return;
                     }
 
                     editor.update(cx, |editor, cx| {
+                        let y = event.position.y - hitbox.top();
+                        if let Some(range) = scrollbar_layout.match_marker_at(y, editor, cx) {
+                            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                s.select_ranges([range])
+                            });
+                            cx.stop_propagation();
+                            return;
+                        }
+
                         editor.scroll_manager.set_is_dragging_scrollbar(true, cx);
 
                         let y = event.position.y;
@@ -2602,7 +2618,8 @@ impl EditorElement {
         cx: &mut ElementContext,
     ) {
         self.editor.update(cx, |editor, cx| {
-            if !editor.is_singleton(cx)
+            let is_singleton = editor.is_singleton(cx);
+            if (!is_singleton && editor.scrollbar_match_markers.is_none())
                 || !editor
                     .scrollbar_marker_state
                     .should_refresh(scrollbar_layout.hitbox.size)
@@ -2611,6 +2628,7 @@ impl EditorElement {
             }
 
             let scrollbar_layout = scrollbar_layout.clone();
+            let match_markers = editor.scrollbar_match_markers.clone();
             let background_highlights = editor.background_highlights.clone();
             let snapshot = layout.position_map.snapshot.clone();
             let theme = cx.theme().clone();
@@ -2626,7 +2644,7 @@ impl EditorElement {
                         .spawn(async move {
                             let mut marker_quads = Vec::new();
 
-                            if scrollbar_settings.git_diff {
+                            if is_singleton && scrollbar_settings.git_diff {
                                 let marker_row_ranges = snapshot
                                     .buffer_snapshot
                                     .git_diff_hunks_in_range(0..max_row)
@@ -2660,7 +2678,7 @@ impl EditorElement {
                             }
 
                             for (background_highlight_id, (_, background_ranges)) in
-                                background_highlights.iter()
+                                background_highlights.iter().filter(|_| is_singleton)
                             {
                                 let is_search_highlights = *background_highlight_id
                                     == TypeId::of::<BufferSearchHighlights>();
@@ -2692,7 +2710,38 @@ impl EditorElement {
                                 }
                             }
 
-                            if scrollbar_settings.diagnostics {
+                            if let Some((ranges, active_index)) = match_markers {
+                                let row_range = |range: &Range<Anchor>, color| ColoredRange {
+                                    start: range
+                                        .start
+                                        .to_display_point(&snapshot.display_snapshot)
+                                        .row(),
+                                    end: range
+                                        .end
+                                        .to_display_point(&snapshot.display_snapshot)
+                                        .row(),
+                                    color,
+                                };
+                                let marker_row_ranges = ranges
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(ix, _)| Some(*ix) != active_index)
+                                    .map(|(_, range)| {
+                                        row_range(range, theme.colors().search_match_background)
+                                    });
+                                marker_quads.extend(
+                                    scrollbar_layout.marker_quads_for_ranges(marker_row_ranges, 1),
+                                );
+                                // The active match is painted last, over the ones around it.
+                                let active_row_range = active_index
+                                    .and_then(|ix| ranges.get(ix))
+                                    .map(|range| row_range(range, theme.colors().text_accent));
+                                marker_quads.extend(
+                                    scrollbar_layout.marker_quads_for_ranges(active_row_range, 1),
+                                );
+                            }
+
+                            if is_singleton && scrollbar_settings.diagnostics {
                                 let max_point =
                                     snapshot.display_snapshot.buffer_snapshot.max_point();
 
@@ -4108,6 +4157,27 @@ impl ScrollbarLayout {
         self.hitbox.top() + self.first_row_y_offset + row * self.row_height
     }
 
+    /// Returns the range of the match marker under `y`, relative to the top of the scrollbar.
+    fn match_marker_at(
+        &self,
+        y: Pixels,
+        editor: &mut Editor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<Range<Anchor>> {
+        let (ranges, _) = editor.scrollbar_match_markers.clone()?;
+        let snapshot = editor.snapshot(cx);
+        ranges
+            .iter()
+            .find(|range| {
+                let start_row = range.start.to_display_point(&snapshot).row();
+                let end_row = range.end.to_display_point(&snapshot).row();
+                let start_y = self.first_row_y_offset + start_row as f32 * self.row_height;
+                let end_y = self.first_row_y_offset + (end_row + 1) as f32 * self.row_height;
+                start_y <= y && y <= end_y.max(start_y + Self::MIN_MARKER_HEIGHT)
+            })
+            .cloned()
+    }
+
     fn marker_quads_for_ranges(
         &self,
         row_ranges: impl IntoIterator<Item = ColoredRange<u32>>,
@@ -4553,9 +4623,10 @@ mod tests {
     use crate::{
         display_map::{BlockDisposition, BlockProperties},
         editor_tests::{init_test, update_test_language_settings},
+        test::editor_test_context::EditorTestContext,
         Editor, MultiBuffer,
     };
-    use gpui::TestAppContext;
+    use gpui::{Modifiers, TestAppContext};
     use language::language_settings;
     use log::info;
     use std::num::NonZeroU32;
@@ -4828,6 +4899,63 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_clicking_scrollbar_match_marker(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = format!("«{}»", sample_text(30, 6, 'a'));
+        let mut cx = EditorTestContext::new_multibuffer(cx, [text.as_str()]);
+        let editor = cx.editor.clone();
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let ranges = [
+                Point::new(10, 1)..Point::new(10, 4),
+                Point::new(25, 2)..Point::new(25, 5),
+            ]
+            .into_iter()
+            .map(|range| snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end))
+            .collect();
+            editor.set_scrollbar_match_markers(ranges, Some(0), cx);
+        });
+        cx.run_until_parked();
+
+        let viewport_size = cx.update(|cx| cx.viewport_size());
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        let mut element = EditorElement::new(&editor, style);
+        let state = cx.update(|cx| {
+            cx.with_element_context(|cx| {
+                element.after_layout(
+                    Bounds {
+                        origin: point(px(0.), px(0.)),
+                        size: viewport_size,
+                    },
+                    &mut (),
+                    cx,
+                )
+            })
+        });
+        let scrollbar_layout = state.scrollbar_layout.unwrap();
+        assert!(scrollbar_layout.visible);
+
+        // The marker rows are offset by the excerpt header.
+        let marker_row = cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            Point::new(25, 2).to_display_point(&snapshot).row()
+        });
+        let position = point(
+            scrollbar_layout.hitbox.center().x,
+            scrollbar_layout.y_for_row(marker_row as f32) + scrollbar_layout.row_height / 2.,
+        );
+        cx.simulate_click(position, Modifiers::none());
+        cx.update_editor(|editor, cx| {
+            assert_eq!(
+                editor.selections.ranges::<Point>(cx),
+                [Point::new(25, 2)..Point::new(25, 5)]
+            );
+            assert!(!editor.scroll_manager.is_dragging_scrollbar());
+        });
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -13,7 +13,7 @@ use std::{
         Arc,
     },
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use util::TryFutureExt;
 use waker_fn::waker_fn;
@@ -291,6 +291,12 @@ impl BackgroundExecutor {
         }
     }
 
+    /// Returns the current time, as measured by the same clock as [`BackgroundExecutor::timer`].
+    /// In tests, it only moves forward when the clock is advanced.
+    pub fn now(&self) -> Instant {
+        self.dispatcher.now()
+    }
+
     /// Returns a task that will complete after the given duration.
     /// Depending on other concurrent tasks the elapsed duration may be longer
     /// than requested.
@@ -39,7 +39,7 @@ use seahash::SeaHasher;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{
     any::Any,
     fmt::{self, Debug},
@@ -251,6 +251,10 @@ pub trait PlatformDispatcher: Send + Sync {
     fn park(&self);
     fn unparker(&self) -> Unparker;
 
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_test(&self) -> Option<&TestDispatcher> {
         None
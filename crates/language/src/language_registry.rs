@@ -360,6 +360,25 @@ impl LanguageRegistry {
         result
     }
 
+    /// Returns the path suffixes files of the given language are detected by,
+    /// such as file extensions.
+    pub fn path_suffixes_for_language(&self, name: &str) -> Vec<String> {
+        let state = self.state.read();
+        state
+            .languages
+            .iter()
+            .find(|language| language.config.name.as_ref() == name)
+            .map(|language| language.config.matcher.path_suffixes.clone())
+            .or_else(|| {
+                state
+                    .available_languages
+                    .iter()
+                    .find(|language| language.name.as_ref() == name)
+                    .map(|language| language.matcher.path_suffixes.clone())
+            })
+            .unwrap_or_default()
+    }
+
     pub fn grammar_names(&self) -> Vec<Arc<str>> {
         let state = self.state.read();
         let mut result = state.grammars.keys().cloned().collect::<Vec<_>>();
@@ -917,7 +917,7 @@ impl MultiBuffer {
         &mut self,
         buffer: Model<Buffer>,
         ranges: Vec<Range<text::Anchor>>,
-        context_line_count: u32,
+        (context_lines_before, context_lines_after): (u32, u32),
         cx: &mut ModelContext<Self>,
     ) -> mpsc::Receiver<Range<Anchor>> {
         let (buffer_id, buffer_snapshot) =
@@ -930,8 +930,12 @@ impl MultiBuffer {
             cx.background_executor()
                 .scoped(|scope| {
                     scope.spawn(async {
-                        let (ranges, counts) =
-                            build_excerpt_ranges(&buffer_snapshot, &ranges, context_line_count);
+                        let (ranges, counts) = build_excerpt_ranges(
+                            &buffer_snapshot,
+                            &ranges,
+                            context_lines_before,
+                            context_lines_after,
+                        );
                         excerpt_ranges = ranges;
                         range_counts = counts;
                     });
@@ -997,8 +1001,12 @@ impl MultiBuffer {
     {
         let buffer_id = buffer.read(cx).remote_id();
         let buffer_snapshot = buffer.read(cx).snapshot();
-        let (excerpt_ranges, range_counts) =
-            build_excerpt_ranges(&buffer_snapshot, &ranges, context_line_count);
+        let (excerpt_ranges, range_counts) = build_excerpt_ranges(
+            &buffer_snapshot,
+            &ranges,
+            context_line_count,
+            context_line_count,
+        );
 
         let excerpt_ids = self.push_excerpts(buffer, excerpt_ranges, cx);
 
@@ -4272,7 +4280,8 @@ impl ToPointUtf16 for PointUtf16 {
 fn build_excerpt_ranges<T>(
     buffer: &BufferSnapshot,
     ranges: &[Range<T>],
-    context_line_count: u32,
+    context_lines_before: u32,
+    context_lines_after: u32,
 ) -> (Vec<ExcerptRange<Point>>, Vec<usize>)
 where
     T: text::ToPoint,
@@ -4285,15 +4294,17 @@ where
         .map(|range| range.start.to_point(buffer)..range.end.to_point(buffer))
         .peekable();
     while let Some(range) = range_iter.next() {
-        let excerpt_start = Point::new(range.start.row.saturating_sub(context_line_count), 0);
-        let mut excerpt_end = Point::new(range.end.row + context_line_count, 0).min(max_point);
+        let excerpt_start = Point::new(range.start.row.saturating_sub(context_lines_before), 0);
+        let mut excerpt_end = Point::new(range.end.row + context_lines_after, 0).min(max_point);
 
         let mut ranges_in_excerpt = 1;
 
         while let Some(next_range) = range_iter.peek() {
-            if next_range.start.row <= excerpt_end.row + context_line_count {
+            // Merge the excerpts that would overlap or touch, so that nearby ranges are shown
+            // in a single continuous excerpt.
+            if next_range.start.row.saturating_sub(context_lines_before) <= excerpt_end.row + 1 {
                 excerpt_end =
-                    Point::new(next_range.end.row + 1 + context_line_count, 0).min(max_point);
+                    Point::new(next_range.end.row + 1 + context_lines_after, 0).min(max_point);
                 ranges_in_excerpt += 1;
                 range_iter.next();
             } else {
@@ -4757,6 +4768,37 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_push_excerpts_with_context_lines_merges_adjacent_excerpts(cx: &mut AppContext) {
+        let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let anchor_ranges = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(
+                buffer.clone(),
+                vec![
+                    Point::new(2, 0)..Point::new(2, 3),
+                    Point::new(5, 0)..Point::new(5, 3),
+                ],
+                1,
+                cx,
+            )
+        });
+
+        assert_eq!(multibuffer.read(cx).excerpt_ids().len(), 1);
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        assert_eq!(snapshot.text(), "bbb\nccc\nddd\neee\nfff\nggg\n");
+        assert_eq!(
+            anchor_ranges
+                .iter()
+                .map(|range| range.to_point(&snapshot))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(1, 0)..Point::new(1, 3),
+                Point::new(4, 0)..Point::new(4, 3),
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_stream_excerpts_with_context_lines(cx: &mut TestAppContext) {
         let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
@@ -4769,7 +4811,7 @@ mod tests {
                 snapshot.anchor_before(Point::new(15, 0))
                     ..snapshot.anchor_before(Point::new(15, 0)),
             ];
-            multibuffer.stream_excerpts_with_context_lines(buffer.clone(), ranges, 2, cx)
+            multibuffer.stream_excerpts_with_context_lines(buffer.clone(), ranges, (2, 2), cx)
         });
 
         let anchor_ranges = anchor_ranges.collect::<Vec<_>>().await;
@@ -4793,6 +4835,32 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_stream_excerpts_with_asymmetric_context_lines(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let anchor_ranges = multibuffer.update(cx, |multibuffer, cx| {
+            let snapshot = buffer.read(cx);
+            let ranges = vec![
+                snapshot.anchor_before(Point::new(10, 0))
+                    ..snapshot.anchor_before(Point::new(10, 3)),
+            ];
+            multibuffer.stream_excerpts_with_context_lines(buffer.clone(), ranges, (1, 3), cx)
+        });
+
+        let anchor_ranges = anchor_ranges.collect::<Vec<_>>().await;
+
+        let snapshot = multibuffer.update(cx, |multibuffer, cx| multibuffer.snapshot(cx));
+        assert_eq!(snapshot.text(), "jjj\nkkk\nlll\nmmm\n");
+        assert_eq!(
+            anchor_ranges
+                .iter()
+                .map(|range| range.to_point(&snapshot))
+                .collect::<Vec<_>>(),
+            vec![Point::new(1, 0)..Point::new(1, 3)]
+        );
+    }
+
     #[gpui::test]
     fn test_empty_multibuffer(cx: &mut AppContext) {
         let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
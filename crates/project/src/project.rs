@@ -6499,14 +6499,20 @@ impl Project {
         // This isn't as straightforward as running an insertion sort sadly, and would also mean that it would have to care about maintaining match index
         // in face of constantly updating list of sorted matches.
         // Meanwhile, this implementation offers index stability, since the matches are already reported in a sorted order.
-        let snapshots = self
-            .visible_worktrees(cx)
+        let mut snapshots = self
+            .worktrees()
+            .filter(|tree| query.include_invisible_worktrees() || tree.read(cx).is_visible())
             .filter_map(|tree| {
                 let tree = tree.read(cx).as_local()?;
                 Some(tree.snapshot())
             })
             .collect::<Vec<_>>();
+        // Paths keep their root names when searching a single worktree of several,
+        // so that the globs written for the whole project still apply to them.
         let include_root = snapshots.len() > 1;
+        if let Some(worktree_id) = query.worktree_id() {
+            snapshots.retain(|snapshot| snapshot.id() == worktree_id);
+        }
 
         let background = cx.background_executor().clone();
         let path_count: usize = snapshots
@@ -6524,23 +6530,29 @@ impl Project {
             return rx;
         }
         let workers = background.num_cpus().min(path_count);
-        let (matching_paths_tx, matching_paths_rx) = smol::channel::bounded(1024);
+        let (matching_paths_tx, mut matching_paths_rx) = smol::channel::bounded(1024);
         let mut unnamed_files = vec![];
         let opened_buffers = self
             .opened_buffers
             .iter()
             .filter_map(|(_, b)| {
                 let buffer = b.upgrade()?;
-                let (is_ignored, snapshot) = buffer.update(cx, |buffer, cx| {
-                    let is_ignored = buffer
-                        .project_path(cx)
-                        .and_then(|path| self.entry_for_path(&path, cx))
+                let (project_path, is_ignored, snapshot) = buffer.update(cx, |buffer, cx| {
+                    let project_path = buffer.project_path(cx);
+                    let is_ignored = project_path
+                        .as_ref()
+                        .and_then(|path| self.entry_for_path(path, cx))
                         .map_or(false, |entry| entry.is_ignored);
-                    (is_ignored, buffer.snapshot())
+                    (project_path, is_ignored, buffer.snapshot())
                 });
                 if is_ignored && !query.include_ignored() {
                     return None;
-                } else if let Some(file) = snapshot.file() {
+                } else if let Some(worktree_id) = query.worktree_id() {
+                    if project_path.map_or(true, |path| path.worktree_id != worktree_id) {
+                        return None;
+                    }
+                }
+                if let Some(file) = snapshot.file() {
                     let matched_path = if include_root {
                         query.file_matches(Some(&file.full_path(cx)))
                     } else {
@@ -6579,16 +6591,19 @@ impl Project {
             const MAX_SEARCH_RESULT_FILES: usize = 5_000;
             const MAX_SEARCH_RESULT_RANGES: usize = 10_000;
 
-            let mut matching_paths = matching_paths_rx
-                .take(MAX_SEARCH_RESULT_FILES + 1)
-                .collect::<Vec<_>>()
-                .await;
-            let mut limit_reached = if matching_paths.len() > MAX_SEARCH_RESULT_FILES {
-                matching_paths.pop();
-                true
-            } else {
-                false
-            };
+            let mut matching_paths = Vec::new();
+            let mut limit_reached = false;
+            while let Some(matching_path) = matching_paths_rx.next().await {
+                // Dropping the candidates receiver stops the worktree walk once the results are no longer awaited.
+                if result_tx.is_closed() {
+                    return Ok(());
+                }
+                if matching_paths.len() == MAX_SEARCH_RESULT_FILES {
+                    limit_reached = true;
+                    break;
+                }
+                matching_paths.push(matching_path);
+            }
             matching_paths.sort_by_key(|candidate| (candidate.is_ignored(), candidate.path()));
 
             let mut range_count = 0;
@@ -4921,6 +4921,129 @@ async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_with_crlf_line_endings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "lf.txt": "one\ntwo\nthree\n",
+            "crlf.txt": "one\r\ntwo\r\nthree\r\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    for query in [
+        SearchQuery::regex("two$", false, true, false, Vec::new(), Vec::new()).unwrap(),
+        SearchQuery::regex("^two$\\n^three", false, true, false, Vec::new(), Vec::new()).unwrap(),
+        SearchQuery::text("two\nthree", false, true, false, Vec::new(), Vec::new()).unwrap(),
+    ] {
+        let expected_range = if query.as_str().contains("three") {
+            4..13
+        } else {
+            4..7
+        };
+        assert_eq!(
+            search(&project, query.clone(), cx).await.unwrap(),
+            HashMap::from_iter([
+                ("dir/lf.txt".to_string(), vec![expected_range.clone()]),
+                ("dir/crlf.txt".to_string(), vec![expected_range]),
+            ]),
+            "CRLF files should match {:?} the same way as LF files",
+            query.as_str()
+        );
+    }
+}
+
+#[gpui::test]
+async fn test_search_in_single_worktree(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/a",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+        }),
+    )
+    .await;
+    fs.insert_tree(
+        "/b",
+        json!({
+            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/a".as_ref(), "/b".as_ref()], cx).await;
+    let (worktree_a, worktree_b) = project.read_with(cx, |project, cx| {
+        let mut worktree_ids = project.worktrees().map(|worktree| worktree.read(cx).id());
+        (worktree_ids.next().unwrap(), worktree_ids.next().unwrap())
+    });
+    // Opened buffers are searched in memory, and have to be left out of other worktrees too.
+    project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/b/three.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(
+        search(&project, query.clone().with_worktree(Some(worktree_a)), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            ("a/one.rs".to_string(), vec![6..9]),
+            ("a/two.rs".to_string(), vec![24..27, 35..38]),
+        ]),
+        "Only the files of the first worktree should be searched"
+    );
+    assert_eq!(
+        search(&project, query.clone().with_worktree(Some(worktree_b)), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            ("b/four.rs".to_string(), vec![25..28]),
+            ("b/three.rs".to_string(), vec![26..29]),
+        ]),
+        "Only the files of the second worktree should be searched"
+    );
+    assert_eq!(
+        search(&project, query.with_worktree(None), cx)
+            .await
+            .unwrap()
+            .len(),
+        4,
+        "Without a worktree, all worktrees should be searched"
+    );
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::text(
+                "ONE",
+                false,
+                true,
+                false,
+                vec![PathMatcher::new("b/four.rs").unwrap()],
+                Vec::new()
+            )
+            .unwrap()
+            .with_worktree(Some(worktree_b)),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("b/four.rs".to_string(), vec![25..28])]),
+        "Globs should keep matching the paths with their worktree root names"
+    );
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
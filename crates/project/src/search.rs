@@ -2,7 +2,7 @@ use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::{Context, Result};
 use client::proto;
 use itertools::Itertools;
-use language::{char_kind, BufferSnapshot};
+use language::{char_kind, BufferSnapshot, LineEnding};
 use regex::{Captures, Regex, RegexBuilder};
 use smol::future::yield_now;
 use std::{
@@ -13,6 +13,7 @@ use std::{
     sync::{Arc, OnceLock},
 };
 use util::paths::PathMatcher;
+use worktree::WorktreeId;
 
 static TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -21,6 +22,9 @@ pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: Vec<PathMatcher>,
     files_to_exclude: Vec<PathMatcher>,
+    include_invisible_worktrees: bool,
+    worktree_id: Option<WorktreeId>,
+    inverted: bool,
 }
 
 impl SearchInputs {
@@ -34,6 +38,23 @@ impl SearchInputs {
         &self.files_to_exclude
     }
 }
+/// Whether the regex `pattern` starts with a group of inline flags enabling `s`,
+/// which lets `.` match newlines.
+fn enables_dot_matches_new_line(pattern: &str) -> bool {
+    pattern
+        .strip_prefix("(?")
+        .and_then(|pattern| pattern.split_once(')'))
+        .is_some_and(|(flags, _)| {
+            flags
+                .chars()
+                .all(|flag| flag.is_ascii_alphabetic() || flag == '-')
+                && flags
+                    .split('-')
+                    .next()
+                    .is_some_and(|enabled| enabled.contains('s'))
+        })
+}
+
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
     Text {
@@ -73,6 +94,9 @@ impl SearchQuery {
             query: query.into(),
             files_to_exclude,
             files_to_include,
+            include_invisible_worktrees: false,
+            worktree_id: None,
+            inverted: false,
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -94,6 +118,9 @@ impl SearchQuery {
     ) -> Result<Self> {
         let mut query = query.to_string();
         let initial_query = Arc::from(query.as_str());
+        // Matches can only span lines when the whole buffer is searched at once.
+        let multiline =
+            query.contains('\n') || query.contains("\\n") || enables_dot_matches_new_line(&query);
         if whole_word {
             let mut word_query = String::new();
             word_query.push_str("\\b");
@@ -102,7 +129,6 @@ impl SearchQuery {
             query = word_query
         }
 
-        let multiline = query.contains('\n') || query.contains("\\n");
         let regex = RegexBuilder::new(&query)
             .case_insensitive(!case_sensitive)
             .multi_line(multiline)
@@ -111,6 +137,9 @@ impl SearchQuery {
             query: initial_query,
             files_to_exclude,
             files_to_include,
+            include_invisible_worktrees: false,
+            worktree_id: None,
+            inverted: false,
         };
         Ok(Self::Regex {
             regex,
@@ -124,7 +153,7 @@ impl SearchQuery {
     }
 
     pub fn from_proto(message: proto::SearchProject) -> Result<Self> {
-        if message.regex {
+        let query = if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -132,7 +161,7 @@ impl SearchQuery {
                 message.include_ignored,
                 deserialize_path_matches(&message.files_to_include)?,
                 deserialize_path_matches(&message.files_to_exclude)?,
-            )
+            )?
         } else {
             Self::text(
                 message.query,
@@ -141,8 +170,11 @@ impl SearchQuery {
                 message.include_ignored,
                 deserialize_path_matches(&message.files_to_include)?,
                 deserialize_path_matches(&message.files_to_exclude)?,
-            )
-        }
+            )?
+        };
+        Ok(query
+            .with_worktree(message.worktree_id.map(WorktreeId::from_proto))
+            .with_inverted_matches(message.inverted))
     }
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
         match self {
@@ -159,6 +191,39 @@ impl SearchQuery {
             }
         }
     }
+
+    /// Makes local searches also go through the worktrees that are not visible in the project panel,
+    /// such as the ones opened for files outside of the project.
+    pub fn with_invisible_worktrees(mut self, include_invisible_worktrees: bool) -> Self {
+        match &mut self {
+            Self::Text { inner, .. } | Self::Regex { inner, .. } => {
+                inner.include_invisible_worktrees = include_invisible_worktrees;
+            }
+        }
+        self
+    }
+
+    /// Limits the search to the files of the worktree with the given id, if any.
+    pub fn with_worktree(mut self, worktree_id: Option<WorktreeId>) -> Self {
+        match &mut self {
+            Self::Text { inner, .. } | Self::Regex { inner, .. } => {
+                inner.worktree_id = worktree_id;
+            }
+        }
+        self
+    }
+
+    /// Makes the query match the non-empty lines that contain no match of its text instead,
+    /// each of them as a whole.
+    pub fn with_inverted_matches(mut self, inverted: bool) -> Self {
+        match &mut self {
+            Self::Text { inner, .. } | Self::Regex { inner, .. } => {
+                inner.inverted = inverted;
+            }
+        }
+        self
+    }
+
     pub fn to_proto(&self, project_id: u64) -> proto::SearchProject {
         proto::SearchProject {
             project_id,
@@ -177,15 +242,64 @@ impl SearchQuery {
                 .iter()
                 .map(|matcher| matcher.to_string())
                 .join(","),
+            inverted: self.inverted(),
+            worktree_id: self.worktree_id().map(|id| id.to_proto()),
         }
     }
 
+    /// Checks whether the on-disk file contents in `stream` contain a match.
+    ///
+    /// Line endings are normalized to `\n` before matching anything that can span lines,
+    /// the same way they are in the buffers that [`SearchQuery::search`] runs on.
     pub fn detect<T: Read>(&self, stream: T) -> Result<bool> {
         if self.as_str().is_empty() {
             return Ok(false);
         }
 
+        if self.inverted() {
+            let mut text = String::new();
+            BufReader::new(stream).read_to_string(&mut text)?;
+            LineEnding::normalize(&mut text);
+            let has_line_without_match = match self {
+                // Whole words are only told apart in buffers, so any file may have such a line.
+                Self::Text {
+                    whole_word: true, ..
+                } => true,
+                Self::Text { search, .. } if self.as_str().contains('\n') => {
+                    let matches = search
+                        .find_iter(&text)
+                        .map(|mat| mat.start()..mat.end())
+                        .collect::<Vec<_>>();
+                    !lines_without_matches(&text, &matches).is_empty()
+                }
+                Self::Text { search, .. } => text
+                    .split('\n')
+                    .any(|line| !line.is_empty() && search.find(line).is_none()),
+                Self::Regex {
+                    regex,
+                    multiline: true,
+                    ..
+                } => {
+                    let matches = regex
+                        .find_iter(&text)
+                        .map(|mat| mat.start()..mat.end())
+                        .collect::<Vec<_>>();
+                    !lines_without_matches(&text, &matches).is_empty()
+                }
+                Self::Regex { regex, .. } => text
+                    .split('\n')
+                    .any(|line| !line.is_empty() && regex.find(line).is_none()),
+            };
+            return Ok(has_line_without_match);
+        }
+
         match self {
+            Self::Text { search, .. } if self.as_str().contains('\n') => {
+                let mut text = String::new();
+                BufReader::new(stream).read_to_string(&mut text)?;
+                LineEnding::normalize(&mut text);
+                Ok(search.find(&text).is_some())
+            }
             Self::Text { search, .. } => {
                 let mat = search.stream_find_iter(stream).next();
                 match mat {
@@ -203,6 +317,7 @@ impl SearchQuery {
                     if let Err(err) = reader.read_to_string(&mut text) {
                         Err(err.into())
                     } else {
+                        LineEnding::normalize(&mut text);
                         Ok(regex.find(&text).is_some())
                     }
                 } else {
@@ -226,6 +341,9 @@ impl SearchQuery {
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    ///
+    /// Regex replacements can refer to the capture groups of the match by index, as `$1` or `${1}`,
+    /// and by name, as `$name` or `${name}`; `$$` stands for a literal `$`.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
@@ -336,6 +454,10 @@ impl SearchQuery {
             }
         }
 
+        if self.inverted() {
+            matches = lines_without_matches(&rope.to_string(), &matches);
+        }
+
         matches
     }
 
@@ -376,6 +498,13 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn as_regex(&self) -> Option<&Regex> {
+        match self {
+            Self::Text { .. } => None,
+            Self::Regex { regex, .. } => Some(regex),
+        }
+    }
+
     pub fn files_to_include(&self) -> &[PathMatcher] {
         self.as_inner().files_to_include()
     }
@@ -384,30 +513,52 @@ impl SearchQuery {
         self.as_inner().files_to_exclude()
     }
 
+    pub fn include_invisible_worktrees(&self) -> bool {
+        self.as_inner().include_invisible_worktrees
+    }
+
+    pub fn worktree_id(&self) -> Option<WorktreeId> {
+        self.as_inner().worktree_id
+    }
+
+    pub fn inverted(&self) -> bool {
+        self.as_inner().inverted
+    }
+
+    /// Checks whether the file at `file_path` is searched, or any of its ancestors is.
+    ///
+    /// Within the included and within the excluded globs, the last glob matching a path wins,
+    /// so that a negated `!` glob takes a path back out of the globs before it, and a glob after
+    /// it puts it back. Included globs made of negations only start from every file.
     pub fn file_matches(&self, file_path: Option<&Path>) -> bool {
+        let include_all = self
+            .files_to_include()
+            .iter()
+            .all(|include_glob| include_glob.is_negated());
         match file_path {
             Some(file_path) => {
                 let mut path = file_path.to_path_buf();
+                let mut exclusion_negated = false;
                 loop {
-                    if self
-                        .files_to_exclude()
-                        .iter()
-                        .any(|exclude_glob| exclude_glob.is_match(&path))
-                    {
-                        return false;
-                    } else if self.files_to_include().is_empty()
-                        || self
-                            .files_to_include()
-                            .iter()
-                            .any(|include_glob| include_glob.is_match(&path))
-                    {
-                        return true;
-                    } else if !path.pop() {
-                        return false;
+                    if !exclusion_negated {
+                        match last_glob_match(self.files_to_exclude(), &path) {
+                            Some(true) => return false,
+                            Some(false) => exclusion_negated = true,
+                            None => {}
+                        }
+                    }
+                    match last_glob_match(self.files_to_include(), &path) {
+                        Some(included) => return included,
+                        None if include_all => return true,
+                        None => {
+                            if !path.pop() {
+                                return false;
+                            }
+                        }
                     }
                 }
             }
-            None => self.files_to_include().is_empty(),
+            None => include_all,
         }
     }
     pub fn as_inner(&self) -> &SearchInputs {
@@ -417,13 +568,45 @@ impl SearchQuery {
     }
 }
 
+/// Returns whether the last of `globs` matching `path` is not negated, if any matches.
+fn last_glob_match(globs: &[PathMatcher], path: &Path) -> Option<bool> {
+    globs
+        .iter()
+        .rev()
+        .find(|glob| glob.is_match(path))
+        .map(|glob| !glob.is_negated())
+}
+
+/// Returns the ranges of the non-empty lines of `text` that none of the sorted,
+/// non-overlapping `matches` touch.
+fn lines_without_matches(text: &str, matches: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut matches = matches.iter().peekable();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let line_end = line_start + line.len();
+        while matches
+            .peek()
+            .is_some_and(|mat| mat.end <= line_start && mat.start < line_start)
+        {
+            matches.next();
+        }
+        let has_match = matches.peek().is_some_and(|mat| mat.start <= line_end);
+        if !has_match && !line.is_empty() {
+            lines.push(line_start..line_end);
+        }
+        line_start = line_end + 1;
+    }
+    lines
+}
+
 fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>> {
     glob_set
         .split(',')
         .map(str::trim)
         .filter(|glob_str| !glob_str.is_empty())
         .map(|glob_str| {
-            PathMatcher::new(glob_str)
+            PathMatcher::negatable(glob_str)
                 .with_context(|| format!("deserializing path match glob {glob_str}"))
         })
         .collect()
@@ -433,6 +616,112 @@ fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>>
 mod tests {
     use super::*;
 
+    #[test]
+    fn lines_without_matches_skip_matched_and_empty_lines() {
+        let text = "one\ntwo\n\nthree one\nfour";
+        let matches = [0..3, 15..18];
+        assert_eq!(lines_without_matches(text, &matches), [4..7, 19..23]);
+
+        let text = "one\ntwo\nthree";
+        let matches = [2..5];
+        assert_eq!(lines_without_matches(text, &matches), [8..13]);
+    }
+
+    #[test]
+    fn regex_replacement_with_named_groups() {
+        let replace = |pattern: &str, replacement: &str, text: &str| {
+            SearchQuery::regex(pattern, false, true, false, vec![], vec![])
+                .unwrap()
+                .with_replacement(replacement.to_string())
+                .replacement_for(text)
+                .unwrap()
+                .into_owned()
+        };
+
+        assert_eq!(
+            replace(r"(?P<fn>\w+)\(", "call_${fn}(", "foo("),
+            "call_foo("
+        );
+        assert_eq!(replace(r"(?P<fn>\w+)\(", "call_$fn", "foo("), "call_foo");
+        assert_eq!(replace(r"(?P<fn>\w+)\(", "${1}_${0}", "foo("), "foo_foo(");
+        assert_eq!(replace(r"(?P<fn>\w+)\(", "$$${fn}", "foo("), "$foo");
+        assert_eq!(replace(r"(?P<fn>\w+)\(", "$$fn", "foo("), "$fn");
+        assert_eq!(
+            replace(r"(?P<fn>\w+)\(", "${missing}_$2_${fn}", "foo("),
+            "_foo",
+            "References to groups missing from the pattern should expand to nothing"
+        );
+        assert_eq!(replace(r"(?P<fn>\w+)\(", "$ and ${", "foo("), "$ and ${");
+    }
+
+    #[test]
+    fn regex_inline_flags_enable_multiline_search() {
+        assert!(enables_dot_matches_new_line("(?s)a.b"));
+        assert!(enables_dot_matches_new_line("(?ms)a.b"));
+        assert!(!enables_dot_matches_new_line("(?m)a.b"));
+        assert!(!enables_dot_matches_new_line("(?-s)a.b"));
+        assert!(!enables_dot_matches_new_line("(?:s)a.b"));
+        assert!(!enables_dot_matches_new_line("a(?s).b"));
+
+        let query = SearchQuery::regex("(?s)a.b", false, false, false, vec![], vec![]).unwrap();
+        assert!(matches!(
+            query,
+            SearchQuery::Regex {
+                multiline: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn file_matches_with_negated_globs() {
+        let globs = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| PathMatcher::negatable(glob).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let query = SearchQuery::text(
+            "query",
+            false,
+            false,
+            false,
+            globs(&[
+                "src/**",
+                "!src/generated/**",
+                "src/generated/api/**",
+                "!src/generated/api/internal.rs",
+            ]),
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(query.file_matches(Some(Path::new("src/main.rs"))));
+        assert!(!query.file_matches(Some(Path::new("src/generated/schema.rs"))));
+        assert!(query.file_matches(Some(Path::new("src/generated/api/client.rs"))));
+        assert!(!query.file_matches(Some(Path::new("src/generated/api/internal.rs"))));
+        assert!(!query.file_matches(Some(Path::new("tests/main.rs"))));
+
+        let query = SearchQuery::text(
+            "query",
+            false,
+            false,
+            false,
+            globs(&["!src/generated/**"]),
+            globs(&["target/**", "!target/doc/**", "target/doc/*.js"]),
+        )
+        .unwrap();
+        assert!(query.file_matches(Some(Path::new("src/main.rs"))));
+        assert!(!query.file_matches(Some(Path::new("src/generated/schema.rs"))));
+        assert!(!query.file_matches(Some(Path::new("target/debug/build.rs"))));
+        assert!(query.file_matches(Some(Path::new("target/doc/index.html"))));
+        assert!(!query.file_matches(Some(Path::new("target/doc/search.js"))));
+
+        let query = SearchQuery::from_proto(query.to_proto(0)).unwrap();
+        assert_eq!(query.files_to_include(), globs(&["!src/generated/**"]));
+        assert!(query.file_matches(Some(Path::new("target/doc/index.html"))));
+    }
+
     #[test]
     fn path_matcher_creation_for_valid_paths() {
         for valid_path in [
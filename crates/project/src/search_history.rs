@@ -49,6 +49,11 @@ impl SearchHistory {
             }
         }
 
+        if self.history.last() == Some(&search_string) {
+            cursor.selection = Some(self.history.len() - 1);
+            return;
+        }
+
         if self.insertion_behavior == QueryInsertionBehavior::ReplacePreviousIfContains {
             if let Some(previously_searched) = self.history.last_mut() {
                 if search_string.contains(previously_searched.as_str()) {
@@ -84,6 +89,11 @@ impl SearchHistory {
         Some(&self.history[next_index])
     }
 
+    /// Returns the queries in the history, from the oldest to the most recent one.
+    pub fn queries(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
     pub fn current(&self, cursor: &SearchHistoryCursor) -> Option<&str> {
         cursor
             .selection
@@ -202,6 +212,25 @@ mod tests {
         assert_eq!(search_history.current(&cursor), Some("TypeScript"));
     }
 
+    #[test]
+    fn test_add_consecutive_duplicates() {
+        let mut search_history = SearchHistory::new(None, QueryInsertionBehavior::AlwaysInsert);
+        let mut cursor = SearchHistoryCursor::default();
+
+        search_history.add(&mut cursor, "Rust".to_string());
+        search_history.add(&mut cursor, "JavaScript".to_string());
+        cursor.reset();
+        search_history.add(&mut cursor, "JavaScript".to_string());
+        search_history.add(&mut cursor, "Rust".to_string());
+
+        assert_eq!(
+            search_history.queries().collect::<Vec<_>>(),
+            ["Rust", "JavaScript", "Rust"],
+            "Only consecutive duplicates should be skipped"
+        );
+        assert_eq!(search_history.current(&cursor), Some("Rust"));
+    }
+
     #[test]
     fn test_reset_selection() {
         let mut search_history = SearchHistory::new(None, QueryInsertionBehavior::AlwaysInsert);
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use project::Fs;
+use serde::Deserialize;
+use smol::stream::StreamExt;
+use std::path::{Path, PathBuf};
+use util::{paths, ResultExt};
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+pub(crate) fn cargo_home() -> PathBuf {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| paths::HOME.join(".cargo"))
+}
+
+/// Returns the directories under `cargo_home` holding the sources of the registry packages
+/// locked by the `Cargo.lock` files at the given roots.
+///
+/// Packages that were not downloaded yet, as well as git and path dependencies, are skipped.
+pub(crate) async fn cargo_dependency_dirs(
+    fs: &dyn Fs,
+    cargo_home: &Path,
+    roots: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let mut registries = Vec::new();
+    let mut entries = fs
+        .read_dir(&cargo_home.join("registry").join("src"))
+        .await?;
+    while let Some(entry) = entries.next().await {
+        if let Some(entry) = entry.log_err() {
+            if fs.is_dir(&entry).await {
+                registries.push(entry);
+            }
+        }
+    }
+
+    let mut dirs = Vec::new();
+    for root in roots {
+        let Ok(lockfile) = fs.load(&root.join("Cargo.lock")).await else {
+            continue;
+        };
+        let lockfile: CargoLock = toml::from_str(&lockfile)?;
+        for package in lockfile.package {
+            let from_registry = package.source.as_deref().is_some_and(|source| {
+                source.starts_with("registry+") || source.starts_with("sparse+")
+            });
+            if !from_registry {
+                continue;
+            }
+            let dir_name = format!("{}-{}", package.name, package.version);
+            for registry in &registries {
+                let dir = registry.join(&dir_name);
+                if fs.is_dir(&dir).await {
+                    if !dirs.contains(&dir) {
+                        dirs.push(dir);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_cargo_dependency_dirs(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/project",
+            json!({
+                "Cargo.lock": r#"
+                    version = 3
+
+                    [[package]]
+                    name = "project"
+                    version = "0.1.0"
+
+                    [[package]]
+                    name = "anyhow"
+                    version = "1.0.80"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "serde"
+                    version = "1.0.197"
+                    source = "sparse+https://index.crates.io/"
+
+                    [[package]]
+                    name = "not-downloaded"
+                    version = "0.2.0"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "forked"
+                    version = "0.3.0"
+                    source = "git+https://github.com/example/forked?rev=abc#abc"
+                "#,
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/cargo/registry/src",
+            json!({
+                "index.crates.io-6f17d22bba15001f": {
+                    "anyhow-1.0.80": { "src": { "lib.rs": "" } },
+                    "anyhow-1.0.79": { "src": { "lib.rs": "" } },
+                    "serde-1.0.197": { "src": { "lib.rs": "" } },
+                    "forked-0.3.0": { "src": { "lib.rs": "" } },
+                },
+            }),
+        )
+        .await;
+
+        let dirs = cargo_dependency_dirs(
+            fs.as_ref(),
+            Path::new("/cargo"),
+            &[PathBuf::from("/project")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            dirs,
+            [
+                PathBuf::from("/cargo/registry/src/index.crates.io-6f17d22bba15001f/anyhow-1.0.80"),
+                PathBuf::from("/cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.197"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::ProjectSearchView;
+use editor::Editor;
+use gpui::{
+    div, prelude::*, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    Subscription, View, ViewContext, WeakView,
+};
+use ui::{h_flex, prelude::*, v_flex, Label};
+use workspace::ModalView;
+
+/// Prompts for the number of the project search match to select, like going to a line.
+pub(crate) struct GoToMatchModal {
+    number_editor: View<Editor>,
+    search_view: WeakView<ProjectSearchView>,
+    /// The number of the match that is selected when the modal opens, counting from 1.
+    active_match_number: Option<usize>,
+    match_count: usize,
+    _subscription: Subscription,
+}
+
+impl ModalView for GoToMatchModal {}
+
+impl FocusableView for GoToMatchModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.number_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for GoToMatchModal {}
+
+impl GoToMatchModal {
+    pub(crate) fn new(
+        search_view: WeakView<ProjectSearchView>,
+        active_match_number: Option<usize>,
+        match_count: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let number_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            if let Some(number) = active_match_number {
+                editor.set_placeholder_text(number.to_string(), cx);
+            }
+            editor
+        });
+        let subscription = cx.subscribe(&number_editor, Self::on_number_editor_event);
+        Self {
+            number_editor,
+            search_view,
+            active_match_number,
+            match_count,
+            _subscription: subscription,
+        }
+    }
+
+    fn on_number_editor_event(
+        &mut self,
+        _: View<Editor>,
+        event: &editor::EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            editor::EditorEvent::Blurred => cx.emit(DismissEvent),
+            editor::EditorEvent::BufferEdited { .. } => cx.notify(),
+            _ => {}
+        }
+    }
+
+    fn number_from_query(&self, cx: &AppContext) -> Option<usize> {
+        self.number_editor.read(cx).text(cx).trim().parse().ok()
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(number) = self.number_from_query(cx) {
+            self.search_view
+                .update(cx, |search_view, cx| {
+                    search_view.go_to_match(number, cx);
+                    search_view.focus_results_editor(cx);
+                })
+                .ok();
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for GoToMatchModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let match_count = self.match_count;
+        let help_text = match (self.number_from_query(cx), self.active_match_number) {
+            (Some(number), _) => format!(
+                "Go to match {} of {match_count}",
+                number.clamp(1, match_count)
+            ),
+            (None, Some(number)) => format!("Match {number} of {match_count}"),
+            (None, None) => format!("{match_count} matches"),
+        };
+
+        div()
+            .elevation_2(cx)
+            .key_context("GoToMatch")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.number_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(help_text).color(Color::Muted)),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,116 @@
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref PROJECT_SEARCH_DB: ProjectSearchDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE project_searches (
+                workspace_id INTEGER,
+                item_id INTEGER,
+                query TEXT,
+                regex INTEGER,
+                whole_word INTEGER,
+                case_sensitive INTEGER,
+                include_ignored INTEGER,
+                files_to_include TEXT,
+                files_to_exclude TEXT,
+                active_match_index INTEGER,
+                scroll_x REAL,
+                scroll_y REAL,
+                regex_flags TEXT,
+                PRIMARY KEY(workspace_id, item_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl ProjectSearchDb {
+    query! {
+       pub async fn update_workspace_id(
+            new_id: WorkspaceId,
+            old_id: WorkspaceId,
+            item_id: ItemId
+        ) -> Result<()> {
+            UPDATE project_searches
+            SET workspace_id = ?
+            WHERE workspace_id = ? AND item_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_query(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            query: String,
+            regex: bool,
+            whole_word: bool,
+            case_sensitive: bool,
+            include_ignored: bool,
+            files_to_include: String,
+            files_to_exclude: String,
+            regex_flags: String
+        ) -> Result<()> {
+            INSERT INTO project_searches
+                (item_id, workspace_id, query, regex, whole_word, case_sensitive, include_ignored, files_to_include, files_to_exclude, regex_flags)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ON CONFLICT DO UPDATE SET
+                query = ?3,
+                regex = ?4,
+                whole_word = ?5,
+                case_sensitive = ?6,
+                include_ignored = ?7,
+                files_to_include = ?8,
+                files_to_exclude = ?9,
+                regex_flags = ?10
+        }
+    }
+
+    query! {
+        pub fn get_query(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(String, bool, bool, bool, bool, String, String, Option<String>)>> {
+            SELECT query, regex, whole_word, case_sensitive, include_ignored, files_to_include, files_to_exclude, regex_flags
+            FROM project_searches
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    // Searches closed by the user are dropped from the workspace's items when it is serialized,
+    // so every stored search that is no longer among them will never be restored.
+    query! {
+        pub async fn delete_closed_searches(workspace_id: WorkspaceId, kind: String) -> Result<()> {
+            DELETE FROM project_searches
+            WHERE workspace_id = ?1 AND item_id NOT IN (
+                SELECT item_id FROM items
+                WHERE workspace_id = ?1 AND kind = ?2
+            )
+        }
+    }
+
+    query! {
+        pub async fn save_review_position(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            active_match_index: u32,
+            scroll_x: f32,
+            scroll_y: f32
+        ) -> Result<()> {
+            INSERT INTO project_searches
+                (item_id, workspace_id, active_match_index, scroll_x, scroll_y)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT DO UPDATE SET
+                active_match_index = ?3,
+                scroll_x = ?4,
+                scroll_y = ?5
+        }
+    }
+
+    query! {
+        pub fn get_review_position(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<u32>, Option<f32>, Option<f32>)>> {
+            SELECT active_match_index, scroll_x, scroll_y
+            FROM project_searches
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+}
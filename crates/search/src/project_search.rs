@@ -1,61 +1,225 @@
 use crate::{
-    FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
-    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
-    ToggleReplace, ToggleWholeWord,
+    dependency_sources::{cargo_dependency_dirs, cargo_home},
+    go_to_match::GoToMatchModal,
+    persistence::PROJECT_SEARCH_DB,
+    typed_query, FocusSearch, NextHistoryQuery, PathDisplay, PreviousHistoryQuery, ReplaceAll,
+    ReplaceNext, SearchOptions, SearchSettings, SelectNextMatch, SelectPrevMatch,
+    ToggleCaseSensitive, ToggleDotMatchesNewLine, ToggleIncludeIgnored, ToggleMultiLineAnchors,
+    ToggleRegex, ToggleReplace, ToggleWholeWord,
 };
 use anyhow::Context as _;
-use collections::{HashMap, HashSet};
+use collections::{BTreeMap, HashMap, HashSet};
 use editor::{
     actions::SelectAll,
+    display_map::{
+        BlockDisposition, BlockId, BlockProperties, BlockStyle, RenderBlock, ToDisplayPoint,
+    },
     items::active_match_index,
     scroll::{Autoscroll, Axis},
-    Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer, MAX_TAB_TITLE_LEN,
+    Anchor, AnchorRangeExt as _, Bias, DisplayPoint, Editor, EditorElement, EditorEvent,
+    EditorStyle, ExcerptId, MultiBuffer, MultiBufferSnapshot, ToPoint, MAX_TAB_TITLE_LEN,
 };
+use futures::{channel::mpsc, Stream};
+use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, AppContext, Context as _, Element, EntityId,
-    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, Global, Hsla,
-    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Point, Render,
-    SharedString, Styled, Subscription, Task, TextStyle, View, ViewContext, VisualContext,
-    WeakModel, WeakView, WhiteSpace, WindowContext,
+    actions, div, impl_actions, percentage, point, uniform_list, Action, Animation, AnimationExt,
+    AnyElement, AnyView, AppContext, ClickEvent, ClipboardItem, Context as _, Element, EntityId,
+    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, Global, HighlightStyle, Hsla,
+    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Point, PromptLevel,
+    Render, SharedString, Styled, Subscription, Task, TextStyle, Transformation,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WeakModel, WeakView, WhiteSpace,
+    WindowContext,
+};
+use language::{
+    Buffer, BufferId, Capability, OffsetRangeExt as _, ToOffset as _, ToPoint as _, TransactionId,
 };
 use menu::Confirm;
-use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project};
+use project::{
+    search::SearchQuery, search_history::SearchHistoryCursor, Item as _, Project, ProjectPath,
+    Worktree, WorktreeId,
+};
+use semantic_index::{SemanticIndex, Status};
+use serde::{Deserialize, Serialize};
 use settings::Settings;
-use smol::stream::StreamExt;
+use smol::{channel::Receiver, stream::StreamExt};
 use std::{
     any::{Any, TypeId},
-    mem,
+    cmp::Reverse,
+    io, mem,
     ops::{Not, Range},
     path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+    time::Duration,
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
-    Selectable, Tooltip,
+    h_flex, popover_menu, prelude::*, v_flex, ContextMenu, HighlightedLabel, Icon, IconButton,
+    IconName, Indicator, Label, LabelCommon, LabelSize, ListItem, Selectable, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, truncate_and_trailoff, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
+    notifications::NotificationId,
+    register_deserializable_item,
     searchable::{Direction, SearchableItem, SearchableItemHandle},
-    DeploySearch, ItemNavHistory, NewSearch, Pane, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace, WorkspaceId,
+    DeploySearch, ItemId, ItemNavHistory, NewSearch, Pane, SplitDirection, Toast, ToolbarItemEvent,
+    ToolbarItemLocation, ToolbarItemView, Workspace, WorkspaceId,
 };
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+const SEMANTIC_SEARCH_LIMIT: usize = 16;
+const MAX_RECENT_QUERIES: usize = 10;
+const MAX_CACHED_SEARCHES: usize = 8;
+const MULTI_PATTERN_DELIMITER: char = '|';
+const MAX_MATCH_HISTORY_LEN: usize = 100;
+const MAX_CONTEXT_LINES: u32 = 10;
+const REGEX_PREVIEW_DEBOUNCE: Duration = Duration::from_millis(150);
+const MAX_REGEX_PREVIEW_LEN: usize = 60;
+const MAX_FILES_TO_OPEN_WITHOUT_CONFIRMATION: usize = 20;
+const OPTION_TOGGLE_DEBOUNCE: Duration = Duration::from_millis(100);
+const MAX_MULTILINE_QUERY_ROWS: usize = 8;
+const FILENAME_SEARCH_LIMIT: usize = 100;
+const STREAMING_NOTIFY_INTERVAL: Duration = Duration::from_millis(33);
+/// The most match ranges inserted into the results at once while they are streamed.
+const MATCH_RANGES_BATCH_SIZE: usize = 256;
+const MAX_RESULTS_LIST_LINE_LEN: usize = 120;
+const LIVE_SEARCH_DEBOUNCE: Duration = Duration::from_millis(200);
+/// Semantic searches embed the query and rank the whole index, so they wait longer for typing to stop.
+const SEMANTIC_SEARCH_DEBOUNCE: Duration = Duration::from_millis(600);
+const SERIALIZED_ITEM_KIND: &str = "ProjectSearch";
+/// How long the selection and scroll position must stay put before being written to the database.
+const REVIEW_POSITION_SERIALIZATION_DEBOUNCE: Duration = Duration::from_millis(500);
+/// Files larger than this are skipped by hex searches.
+const HEX_SEARCH_MAX_FILE_SIZE: usize = 8 * 1024 * 1024;
+/// How many bytes of a file hex searches read at a time.
+const HEX_SEARCH_CHUNK_SIZE: usize = 64 * 1024;
+/// The number of bytes shown on each side of a hex search match.
+const HEX_CONTEXT_BYTES: usize = 8;
+/// How long the match index stays highlighted after reaching an end of the results.
+const END_OF_RESULTS_FLASH: Duration = Duration::from_millis(400);
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        CycleScope,
+        CycleSymbolKindFilter,
+        CycleSymbolScope,
+        ToggleIncludeDependencies,
+        ToggleSortOrder,
+        ToggleSemanticSearch,
+        ToggleFilenameSearch,
+        ToggleHexSearch,
+        ToggleTreatQueryAsPath,
+        ToggleLiveSearch,
+        ToggleDefaultExcludes,
+        IncreaseContext,
+        DecreaseContext,
+        IncreaseContextBefore,
+        DecreaseContextBefore,
+        IncreaseContextAfter,
+        DecreaseContextAfter,
+        OpenAllMatches,
+        ToggleMultiline,
+        StopSearch,
+        ToggleCasePreservingReplace,
+        ToggleInvertMatch,
+        ToggleMultiPattern,
+        PinResults,
+        CopyMatches,
+        CopyActiveMatch,
+        ExportResults,
+        PeekNextMatch,
+        PeekPrevMatch,
+        FindAndSelect,
+        SearchTodos,
+        CollapseAllGroups,
+        ExpandAllGroups,
+        ExcludeCurrentFile,
+        RevealActiveMatchInProjectPanel,
+        SwapIncludeExclude,
+        ToggleSearchInSelection,
+        SearchWithinResults,
+        ToggleResultsListView,
+        ToggleGroupByDirectory,
+        OpenResultsInSplit,
+        PreviewReplace,
+        ToggleMatchExcluded,
+        SearchNavBack,
+        SearchNavForward,
+        BookmarkMatch,
+        NextBookmark,
+        PrevBookmark,
+        SwapWithPreviousQuery,
+        ResetSearchOptions,
+        ReplaceAllInFile,
+        RepeatLastProjectSearch,
+        ImportRipgrepCommand
+    ]
 );
 
+/// Selects the match with the given number, counting from 1, or prompts for it when omitted.
+#[derive(PartialEq, Clone, Default, Deserialize)]
+pub struct GoToMatch {
+    #[serde(default)]
+    pub number: Option<usize>,
+}
+
+impl_actions!(project_search, [GoToMatch]);
+
+/// Symbol kinds that a search can be restricted to, in the order they are cycled through.
+const SYMBOL_KIND_FILTERS: [(lsp::SymbolKind, &str); 6] = [
+    (lsp::SymbolKind::STRUCT, "struct"),
+    (lsp::SymbolKind::ENUM, "enum"),
+    (lsp::SymbolKind::CLASS, "class"),
+    (lsp::SymbolKind::INTERFACE, "interface"),
+    (lsp::SymbolKind::FUNCTION, "function"),
+    (lsp::SymbolKind::MODULE, "module"),
+];
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
 impl Global for ActiveSettings {}
 
+/// The include and exclude globs of the last search run in every project, prefilled into the
+/// project's new searches.
+#[derive(Default)]
+struct RecentPathFilters(HashMap<WeakModel<Project>, PathFilters>);
+
+impl Global for RecentPathFilters {}
+
+#[derive(Clone, Debug, Default)]
+struct PathFilters {
+    files_to_include: String,
+    files_to_exclude: String,
+}
+
+impl PathFilters {
+    /// The filters `query` was built from, the way they are typed in the filter inputs.
+    fn from_query(query: &SearchQuery) -> Self {
+        let join = |matchers: &[PathMatcher]| {
+            matchers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Self {
+            files_to_include: join(query.files_to_include()),
+            files_to_exclude: join(query.files_to_exclude()),
+        }
+    }
+}
+
 pub fn init(cx: &mut AppContext) {
     cx.set_global(ActiveSettings::default());
+    cx.set_global(RecentPathFilters::default());
+    register_deserializable_item::<ProjectSearchView>(cx);
     cx.observe_new_views(|workspace: &mut Workspace, _cx| {
         register_workspace_action(workspace, move |search_bar, _: &FocusSearch, cx| {
             search_bar.focus_search(cx);
@@ -63,18 +227,228 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &CollapseAllGroups, cx| {
+            search_bar.collapse_all_groups(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ExpandAllGroups, cx| {
+            search_bar.expand_all_groups(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &CycleScope, cx| {
+            search_bar.cycle_scope(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSearchInSelection, cx| {
+                search_bar.toggle_search_in_selection(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &SearchWithinResults, cx| {
+            search_bar.search_within_results(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &CycleSymbolKindFilter, cx| {
+                search_bar.cycle_symbol_kind_filter(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &CycleSymbolScope, cx| {
+            search_bar.cycle_symbol_scope(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleIncludeDependencies, cx| {
+                search_bar.toggle_include_dependencies(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleSortOrder, cx| {
+            search_bar.toggle_sort_order(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSemanticSearch, cx| {
+                search_bar.toggle_semantic_search(cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleFilenameSearch, cx| {
+                search_bar.toggle_filename_search(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleHexSearch, cx| {
+            search_bar.toggle_hex_search(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleTreatQueryAsPath, cx| {
+                search_bar.toggle_treat_query_as_path(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleLiveSearch, cx| {
+            search_bar.toggle_live_search(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleDefaultExcludes, cx| {
+                search_bar.toggle_default_excludes(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleMultiline, cx| {
+            search_bar.toggle_multiline(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &StopSearch, cx| {
+            search_bar.stop_search(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ExcludeCurrentFile, cx| {
+            search_bar.exclude_current_file(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &RevealActiveMatchInProjectPanel, cx| {
+                search_bar.reveal_active_match_in_project_panel(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &SwapIncludeExclude, cx| {
+            search_bar.swap_include_exclude(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleMultiPattern, cx| {
+            search_bar.toggle_multi_pattern(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleInvertMatch, cx| {
+            search_bar.toggle_invert_match(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ResetSearchOptions, cx| {
+            search_bar.reset_search_options(cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleCasePreservingReplace, cx| {
+                search_bar.toggle_case_preserving_replace(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &PinResults, cx| {
+            search_bar.toggle_pin_results(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &CopyMatches, cx| {
+            search_bar.copy_matches(false, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &CopyActiveMatch, cx| {
+            search_bar.copy_matches(true, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ExportResults, cx| {
+            search_bar.export_results(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, action: &GoToMatch, cx| {
+            search_bar.go_to_match(action, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleResultsListView, cx| {
+                search_bar.toggle_results_list_view(cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleGroupByDirectory, cx| {
+                search_bar.toggle_group_by_directory(cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &OpenResultsInSplit, cx| {
+            search_bar.open_results_in_split(cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &IncreaseContext, cx| {
+            search_bar.change_context_lines(1, 1, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &DecreaseContext, cx| {
+            search_bar.change_context_lines(-1, -1, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &IncreaseContextBefore, cx| {
+                search_bar.change_context_lines(1, 0, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &DecreaseContextBefore, cx| {
+                search_bar.change_context_lines(-1, 0, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &IncreaseContextAfter, cx| {
+                search_bar.change_context_lines(0, 1, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &DecreaseContextAfter, cx| {
+                search_bar.change_context_lines(0, -1, cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ToggleCaseSensitive, cx| {
             search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
         });
         register_workspace_action(workspace, move |search_bar, _: &ToggleWholeWord, cx| {
             search_bar.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleIncludeIgnored, cx| {
+                search_bar.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleDotMatchesNewLine, cx| {
+                search_bar.toggle_search_option(SearchOptions::DOT_MATCHES_NEW_LINE, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleMultiLineAnchors, cx| {
+                search_bar.toggle_search_option(SearchOptions::MULTI_LINE_ANCHORS, cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
+        register_workspace_action(workspace, move |search_bar, _: &PreviewReplace, cx| {
+            search_bar.toggle_replace_preview(cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleMatchExcluded, cx| {
+            search_bar.toggle_match_excluded(cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SearchNavBack, cx| {
+            search_bar.navigate_match_history(Direction::Prev, cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &SearchNavForward, cx| {
+            search_bar.navigate_match_history(Direction::Next, cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &BookmarkMatch, cx| {
+            search_bar.toggle_match_bookmarked(cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &NextBookmark, cx| {
+            search_bar.navigate_bookmarks(Direction::Next, cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &PrevBookmark, cx| {
+            search_bar.navigate_bookmarks(Direction::Prev, cx)
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &SwapWithPreviousQuery, cx| {
+                search_bar.swap_with_previous_query(cx)
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &ReplaceAllInFile, cx| {
+                search_bar.replace_all_in_file(action, cx)
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &SelectPrevMatch, cx| {
@@ -87,11 +461,20 @@ pub fn init(cx: &mut AppContext) {
                 search_bar.select_next_match(action, cx)
             },
         );
+        register_workspace_action(workspace, move |search_bar, _: &PeekNextMatch, cx| {
+            search_bar.peek_match(Direction::Next, cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &PeekPrevMatch, cx| {
+            search_bar.peek_match(Direction::Prev, cx)
+        });
 
         // Only handle search_in_new if there is a search present
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
             ProjectSearchView::search_in_new(workspace, action, cx)
         });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::open_all_matches(workspace, action, cx)
+        });
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, cx| {
@@ -110,6 +493,37 @@ pub fn init(cx: &mut AppContext) {
             ProjectSearchView::new_search(workspace, action, cx);
             cx.notify();
         });
+        workspace.register_action(move |workspace, action: &SearchTodos, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::search_todos(workspace, action, cx);
+            cx.notify();
+        });
+        workspace.register_action(move |workspace, action: &RepeatLastProjectSearch, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::repeat_last_search(workspace, action, cx);
+            cx.notify();
+        });
+        workspace.register_action(move |workspace, action: &ImportRipgrepCommand, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::import_ripgrep_command(workspace, action, cx);
+            cx.notify();
+        });
+        workspace.register_action(move |workspace, action: &FindAndSelect, cx| {
+            if workspace.has_active_modal(cx) {
+                cx.propagate();
+                return;
+            }
+            ProjectSearchView::find_and_select(workspace, action, cx);
+        });
     })
     .detach();
 }
@@ -118,13 +532,378 @@ struct ProjectSearch {
     project: Model<Project>,
     excerpts: Model<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
+    /// Streams the matches of the pending search from the project. Dropping it stops the project
+    /// search, the symbol requests and the parse waits along with it.
+    match_stream: Option<Task<Option<()>>>,
+    /// Whether the pending task only rebuilds the excerpts of finished search, in which case
+    /// it can be restarted.
+    rebuilding_excerpts: bool,
+    /// Set while notifications about the streamed results are held back, see
+    /// [`ProjectSearch::notify_throttled`].
+    notify_throttle: Option<Task<()>>,
+    /// Whether a notification was held back since the last one went out.
+    notify_skipped: bool,
     match_ranges: Vec<Range<Anchor>>,
+    /// The number of matches found so far, counted as soon as the matches of a file are known,
+    /// ahead of their excerpts streaming into `match_ranges`.
+    total_match_count: usize,
+    /// The matches of every file in the results, kept to rebuild the excerpts when
+    /// the number of context lines or the results filter changes.
+    buffer_matches: Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>,
+    /// Keeps the matches of the text search results up to date when their files are reloaded.
+    buffer_subscriptions: HashMap<BufferId, Subscription>,
+    context_before: u32,
+    context_after: u32,
+    sort_order: SortOrder,
+    /// Only the matches on lines containing this text are shown.
+    results_filter: String,
     active_query: Option<SearchQuery>,
     last_search_query_text: Option<String>,
     search_id: usize,
     no_results: Option<bool>,
+    /// Whether `excerpts` still holds the results of the previous search, kept visible while
+    /// the active one has not found any match, see `search.keep_previous_results_on_empty`.
+    showing_previous_results: bool,
     limit_reached: bool,
+    /// Whether matches were dropped to stay within the `max_matches` setting.
+    truncated: bool,
+    /// Whether the last search was stopped before going through the whole project.
+    stopped: bool,
+    /// How long the last text search took to find all of its matches, once it completed.
+    search_duration: Option<Duration>,
     search_history_cursor: SearchHistoryCursor,
+    scope: SearchScope,
+    symbol_kind: Option<lsp::SymbolKind>,
+    symbol_scope: SymbolScope,
+    include_dependencies: bool,
+    search_mode: SearchMode,
+    /// The project files whose paths fuzzy-match the query of a filename search, best first.
+    filename_matches: Vec<(ProjectPath, StringMatch)>,
+    /// The occurrences of the bytes of a hex search in the project files, by path.
+    hex_matches: Vec<HexMatch>,
+    /// Invisible worktrees are only weakly held by the project, so the ones created
+    /// for the dependency sources are kept alive for as long as their results are shown.
+    dependency_worktrees: Vec<Model<Worktree>>,
+    /// Set while a semantic search waits for the project to be indexed, and once it did.
+    indexing: Option<IndexingState>,
+    /// The start of every semantic search result in its buffer, with the result's similarity
+    /// to the query.
+    semantic_scores: Vec<(language::Anchor, f32)>,
+    /// The matches of the last text searches, least recently used first, shown again without
+    /// searching the project when the same search runs while the project did not change.
+    search_cache: Vec<CachedSearch>,
+    _project_subscription: Subscription,
+}
+
+/// The parts of a text search query that determine its matches.
+#[derive(Debug, PartialEq, Eq)]
+struct SearchCacheKey {
+    query: String,
+    is_regex: bool,
+    whole_word: bool,
+    case_sensitive: bool,
+    include_ignored: bool,
+    inverted: bool,
+    worktree_id: Option<WorktreeId>,
+    files_to_include: Vec<String>,
+    files_to_exclude: Vec<String>,
+}
+
+impl SearchCacheKey {
+    fn new(query: &SearchQuery) -> Self {
+        Self {
+            query: query.as_str().to_string(),
+            is_regex: query.is_regex(),
+            whole_word: query.whole_word(),
+            case_sensitive: query.case_sensitive(),
+            include_ignored: query.include_ignored(),
+            inverted: query.inverted(),
+            worktree_id: query.worktree_id(),
+            files_to_include: query
+                .files_to_include()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            files_to_exclude: query
+                .files_to_exclude()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+struct CachedSearch {
+    key: SearchCacheKey,
+    buffer_matches: Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>,
+    /// The version of every buffer open in the project once the search completed. Editing or
+    /// reloading any of them may change the matches, so the search is not reused then.
+    buffer_versions: Vec<(BufferId, clock::Global)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IndexingState {
+    Indexing,
+    Complete,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SearchMode {
+    #[default]
+    Text,
+    /// Ranks the chunks of the project's files by their similarity to the query,
+    /// using the global [`SemanticIndex`].
+    Semantic,
+    /// Fuzzy-matches the query against the paths of the project's files, ignoring their contents.
+    Filename,
+    /// Looks for the bytes written as hex pairs in the query in the raw contents of the
+    /// project's files, including the ones that are not valid UTF-8.
+    Hex,
+}
+
+/// The order of the files in the results of a text search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SortOrder {
+    /// Sorted by path.
+    #[default]
+    Path,
+    /// The files whose name contains the query first, then the ones with the most whole-word
+    /// matches, then the most recently modified ones.
+    Relevance,
+}
+
+impl SortOrder {
+    fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Path => "Sort by path",
+            SortOrder::Relevance => "Sort by relevance",
+        }
+    }
+}
+
+/// The syntax that the matches of a text search must be in, e.g. to only search comments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SymbolScope {
+    #[default]
+    All,
+    Comments,
+    Strings,
+    Identifiers,
+}
+
+impl SymbolScope {
+    fn label(&self) -> &'static str {
+        match self {
+            SymbolScope::All => "In: anything",
+            SymbolScope::Comments => "In: comments",
+            SymbolScope::Strings => "In: strings",
+            SymbolScope::Identifiers => "In: identifiers",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            SymbolScope::All => SymbolScope::Comments,
+            SymbolScope::Comments => SymbolScope::Strings,
+            SymbolScope::Strings => SymbolScope::Identifiers,
+            SymbolScope::Identifiers => SymbolScope::All,
+        }
+    }
+
+    /// Keeps the ranges that are inside of the syntax of this scope, according to the kinds of
+    /// the tree-sitter nodes around them. Nothing is kept in files without a syntax tree.
+    fn ranges_in_scope(
+        &self,
+        buffer: &Model<Buffer>,
+        ranges: Vec<Range<language::Anchor>>,
+        cx: &AppContext,
+    ) -> Vec<Range<language::Anchor>> {
+        if *self == SymbolScope::All {
+            return ranges;
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        ranges
+            .into_iter()
+            .filter(|range| self.contains(&snapshot, range.to_offset(&snapshot)))
+            .collect()
+    }
+
+    fn contains(&self, snapshot: &language::BufferSnapshot, range: Range<usize>) -> bool {
+        let Some(mut node) = snapshot.syntax_layer_at(range.start).and_then(|layer| {
+            layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end)
+        }) else {
+            return false;
+        };
+        let kind = match self {
+            SymbolScope::All => return true,
+            SymbolScope::Identifiers => return node.kind().ends_with("identifier"),
+            SymbolScope::Comments => "comment",
+            SymbolScope::Strings => "string",
+        };
+        // Grammars name these nodes differently, e.g. `line_comment` or `string_content`.
+        loop {
+            if node.kind().contains(kind) {
+                return true;
+            }
+            match node.parent() {
+                Some(parent) => node = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+/// A match of a project search, resolved to where it is in its file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatchInfo {
+    /// The path of the file, starting with the name of its worktree.
+    pub path: PathBuf,
+    /// The one-based line of the start of the match.
+    pub line: u32,
+    /// The one-based column of the start of the match, in bytes.
+    pub column: u32,
+    pub text: String,
+}
+
+/// An occurrence of the bytes of a hex search in a file, with the bytes around it.
+#[derive(Clone, Debug, PartialEq)]
+struct HexMatch {
+    path: ProjectPath,
+    /// The offset of the first matched byte in the file.
+    offset: usize,
+    len: usize,
+    /// The offset in the file of the first byte of `context`.
+    context_start: usize,
+    context: Vec<u8>,
+}
+
+/// The search described by a `rg` or `grep` command line, see [`parse_ripgrep_command`].
+#[derive(Clone, Debug, PartialEq)]
+struct RipgrepCommand {
+    query: String,
+    search_options: SearchOptions,
+    files_to_include: Vec<String>,
+    files_to_exclude: Vec<String>,
+    /// The flags and arguments that have no equivalent in project search.
+    ignored_arguments: Vec<String>,
+}
+
+/// A range within a single buffer that a search can be restricted to.
+#[derive(Clone, Debug)]
+struct BufferRange {
+    buffer: Model<Buffer>,
+    range: Range<language::Anchor>,
+}
+
+#[derive(Clone, Debug, Default)]
+enum SearchScope {
+    #[default]
+    Project,
+    /// The buffers that were open in the workspace when the search was deployed.
+    OpenBuffers(Vec<Model<Buffer>>),
+    /// The buffer of the active editor when the search was deployed.
+    CurrentFile(Model<Buffer>),
+    /// The lines that were visible in the active editor when the search was deployed.
+    VisibleArea(BufferRange),
+    /// The multi-line selection of the active editor when the search was deployed.
+    Selection(BufferRange),
+    /// The buffers holding the matches of a previous search.
+    Results(Vec<Model<Buffer>>),
+    /// The files that git reports as added, modified or conflicted, staged or not.
+    ChangedFiles,
+}
+
+impl SearchScope {
+    fn label(&self) -> &'static str {
+        match self {
+            SearchScope::Project => "Project",
+            SearchScope::OpenBuffers(_) => "Open buffers",
+            SearchScope::CurrentFile(_) => "Current file",
+            SearchScope::VisibleArea(_) => "Visible area",
+            SearchScope::Selection(_) => "Selection",
+            SearchScope::Results(_) => "Previous results",
+            SearchScope::ChangedFiles => "Changed files",
+        }
+    }
+
+    /// Returns the buffers to search directly instead of walking the project's worktrees.
+    fn buffers(&self) -> Option<Vec<Model<Buffer>>> {
+        match self {
+            SearchScope::Project | SearchScope::ChangedFiles => None,
+            SearchScope::OpenBuffers(buffers) => Some(buffers.clone()),
+            SearchScope::CurrentFile(buffer) => Some(vec![buffer.clone()]),
+            SearchScope::VisibleArea(area) | SearchScope::Selection(area) => {
+                Some(vec![area.buffer.clone()])
+            }
+            SearchScope::Results(buffers) => Some(buffers.clone()),
+        }
+    }
+
+    fn ranges_in_scope(
+        &self,
+        buffer: &Model<Buffer>,
+        ranges: Vec<Range<language::Anchor>>,
+        cx: &AppContext,
+    ) -> Vec<Range<language::Anchor>> {
+        match self {
+            SearchScope::Project
+            | SearchScope::OpenBuffers(_)
+            | SearchScope::CurrentFile(_)
+            | SearchScope::Results(_)
+            | SearchScope::ChangedFiles => ranges,
+            SearchScope::VisibleArea(area) | SearchScope::Selection(area) => {
+                if &area.buffer != buffer {
+                    return Vec::new();
+                }
+                let snapshot = buffer.read(cx).snapshot();
+                ranges
+                    .into_iter()
+                    .filter(|range| {
+                        range.start.cmp(&area.range.start, &snapshot).is_ge()
+                            && range.end.cmp(&area.range.end, &snapshot).is_le()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A row of the results list, see [`ProjectSearchView::results_list_rows`].
+#[derive(Clone, Debug, PartialEq)]
+enum ResultsListRow {
+    /// The header of the matches in the files of a directory, when grouping them by directory.
+    Directory {
+        path: PathBuf,
+        match_count: usize,
+        collapsed: bool,
+    },
+    Match(usize),
+}
+
+/// A file of the results, as listed in the minimap next to the results editor.
+#[derive(Clone, Debug, PartialEq)]
+struct MinimapEntry {
+    path: PathBuf,
+    match_count: usize,
+    /// The index of the first match of the file in `match_ranges`.
+    first_match_index: usize,
+}
+
+/// The line of a match bookmarked to come back to, which stays bookmarked across searches.
+struct Bookmark {
+    buffer: Model<Buffer>,
+    anchor: language::Anchor,
+}
+
+/// The matches that a replace-all went through, brought back when its transaction is undone.
+struct ReplacedMatches {
+    transaction_id: TransactionId,
+    /// The search the matches were found by; the matches of later searches aren't replaced.
+    search_id: usize,
+    match_ranges: Vec<Range<Anchor>>,
+    total_match_count: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -134,6 +913,12 @@ enum InputPanel {
     Include,
 }
 
+/// The background highlight of the active match in the results editor.
+enum ActiveMatchHighlight {}
+
+/// The greyed out text of the matches that replacing all matches skips.
+enum ExcludedMatchHighlight {}
+
 pub struct ProjectSearchView {
     focus_handle: FocusHandle,
     model: Model<ProjectSearch>,
@@ -143,15 +928,171 @@ pub struct ProjectSearchView {
     search_options: SearchOptions,
     panels_with_errors: HashSet<InputPanel>,
     active_match_index: Option<usize>,
+    /// How many matches the results hold and in how many files, e.g. "42 matches in 9 files".
+    match_summary: Option<SharedString>,
     search_id: usize,
+    /// The folds truncating the long matched lines of every excerpt, kept apart from the folds
+    /// of the user so that they can be recomputed and restored on their own.
+    line_truncations: HashMap<ExcerptId, LineTruncation>,
     query_editor_was_focused: bool,
     included_files_editor: View<Editor>,
     excluded_files_editor: View<Editor>,
+    /// Narrows the found matches down to the ones on lines containing its text.
+    filter_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    /// Whether replacements follow the casing of the matches they replace, e.g. replacing
+    /// `FOO_BAR` with `bazQux` inserts `BAZ_QUX`.
+    case_preserving_replace: bool,
+    /// Whether the search looks for the lines that do not match the query.
+    invert_match: bool,
+    /// Whether the query is split on `|` into literal patterns, any of which is a match.
+    multi_pattern: bool,
+    scope: SearchScope,
+    open_buffers: Vec<Model<Buffer>>,
+    current_buffer: Option<Model<Buffer>>,
+    visible_area: Option<BufferRange>,
+    selection: Option<BufferRange>,
+    result_buffers: Vec<Model<Buffer>>,
+    symbol_kind_filter: Option<lsp::SymbolKind>,
+    symbol_scope: SymbolScope,
+    /// The name of the language whose files are searched, on top of the included globs.
+    language_filter: Option<SharedString>,
+    /// The worktree whose files are searched, or all of the project's worktrees if `None`.
+    worktree_filter: Option<WorktreeId>,
+    include_dependencies: bool,
+    search_mode: SearchMode,
+    /// Whether the query is a glob matched against the paths of the project's files,
+    /// listing the files it matches like a filename search does.
+    treat_query_as_path: bool,
+    /// Whether text searches rerun while the query is typed, without waiting for Enter.
+    live_search: bool,
+    /// Whether the `search.default_excludes` globs are excluded on top of the typed ones.
+    default_excludes_enabled: bool,
+    context_before: u32,
+    context_after: u32,
+    sort_order: SortOrder,
+    pending_review_position: Option<ReviewPosition>,
+    pending_source_location: Option<SourceLocation>,
+    /// Where the active match was when the search was re-run, whose nearest match in the same
+    /// file gets selected once the new search completes instead of the first one.
+    pending_previous_match: Option<SourceLocation>,
+    /// Why the query could not be turned into a search, shown when hovering the query editor.
+    query_error: Option<SharedString>,
+    /// The glob that failed to compile in the include or exclude editor, and why.
+    glob_errors: HashMap<InputPanel, String>,
+    /// The first match of the regex query in `current_buffer`, with its capture groups.
+    regex_preview: Option<SharedString>,
+    regex_preview_task: Option<Task<()>>,
+    /// Set when the regex query looks like it may be slow to run, see [`may_be_slow_regex`].
+    slow_regex_warning: bool,
+    /// The search re-run after the search options, or the query while searching live, stop changing.
+    scheduled_search: Option<Task<()>>,
+    /// The pending write of the review position, replaced whenever the selection or scroll
+    /// position changes again.
+    scheduled_review_position_serialization: Option<Task<()>>,
+    /// Highlights the match index while set, after navigating past an end of the results with
+    /// `search.wrap_around` disabled.
+    end_of_results_flash: Option<Task<()>>,
+    /// Whether the query editor grows to fit queries spanning several lines.
+    multiline_query: bool,
+    /// Whether the results are kept as they are, with new searches opening in their own tab.
+    results_pinned: bool,
+    /// The index of the file that confirming a filename search opens.
+    selected_filename_match: usize,
+    /// Whether the matches are listed one per row instead of shown in excerpts.
+    results_as_list: bool,
+    /// Whether the results list sections the matches by the directory of their files.
+    group_by_directory: bool,
+    /// The directories whose matches are hidden from the results list, until expanded again.
+    collapsed_directories: HashSet<PathBuf>,
+    results_list_scroll_handle: UniformListScrollHandle,
+    /// Whether every match is listed with the text it would be replaced with,
+    /// waiting for the replacements to be confirmed.
+    replace_preview: bool,
+    replace_preview_scroll_handle: UniformListScrollHandle,
+    /// The buffer ranges of the matches that replacing skips, until the next search. Unlike
+    /// indices into the model's `match_ranges`, these survive the excerpts being rebuilt.
+    excluded_matches: HashSet<Range<language::Anchor>>,
+    /// The matches of the last replace-all, until it is undone.
+    replaced_matches: Option<ReplacedMatches>,
+    /// The indices of the matches that were active since the search ran, oldest first, which
+    /// `SearchNavBack` and `SearchNavForward` go through.
+    match_history: Vec<usize>,
+    /// The position in `match_history` of the active match.
+    match_history_position: usize,
+    /// The bookmarked lines, in the order they were bookmarked in, which `NextBookmark` and
+    /// `PrevBookmark` go through.
+    bookmarks: Vec<Bookmark>,
+    /// The index in `bookmarks` of the bookmark last gone to.
+    active_bookmark: Option<usize>,
+    /// The blocks showing the similarity of the semantic search results above their excerpts.
+    semantic_score_blocks: HashSet<BlockId>,
+    _query_editor_subscription: Subscription,
+    workspace: Option<WeakView<Workspace>>,
+    workspace_id: Option<WorkspaceId>,
     _subscriptions: Vec<Subscription>,
 }
 
+/// The cursor position the search query was seeded from, whose match gets selected
+/// once it is found instead of the first one.
+#[derive(Clone, Debug)]
+struct SourceLocation {
+    buffer: Model<Buffer>,
+    position: language::Anchor,
+    /// The id of the search that was displayed when the location was captured,
+    /// so that only the searches started afterwards look for it.
+    search_id: usize,
+}
+
+impl SourceLocation {
+    fn match_index(&self, match_ranges: &[Range<Anchor>], cx: &AppContext) -> Option<usize> {
+        let buffer = self.buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let snapshot = buffer.snapshot();
+        match_ranges.iter().position(|range| {
+            range.start.buffer_id == Some(buffer_id)
+                && range
+                    .start
+                    .text_anchor
+                    .cmp(&self.position, &snapshot)
+                    .is_le()
+                && range.end.text_anchor.cmp(&self.position, &snapshot).is_ge()
+        })
+    }
+
+    /// Returns the index of the match in the same file that starts the closest to the location.
+    fn nearest_match_index(
+        &self,
+        match_ranges: &[Range<Anchor>],
+        cx: &AppContext,
+    ) -> Option<usize> {
+        let buffer = self.buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let snapshot = buffer.snapshot();
+        let offset = self.position.to_offset(&snapshot);
+        match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| range.start.buffer_id == Some(buffer_id))
+            .min_by_key(|(_, range)| {
+                range
+                    .start
+                    .text_anchor
+                    .to_offset(&snapshot)
+                    .abs_diff(offset)
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+/// Where the user was in the results of a search, restored once the search is re-run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ReviewPosition {
+    active_match_index: usize,
+    scroll_position: Point<f32>,
+}
+
 #[derive(Debug, Clone)]
 struct ProjectSearchSettings {
     search_options: SearchOptions,
@@ -167,57 +1108,679 @@ impl ProjectSearch {
     fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
         let replica_id = project.read(cx).replica_id();
         let capability = project.read(cx).capability();
+        let (context_before, context_after) =
+            SearchSettings::get_global(cx).context_lines_around_matches();
+        let project_subscription = Self::subscribe_to_project(&project, cx);
 
         Self {
             project,
             excerpts: cx.new_model(|_| MultiBuffer::new(replica_id, capability)),
             pending_search: Default::default(),
+            match_stream: None,
+            rebuilding_excerpts: false,
+            notify_throttle: None,
+            notify_skipped: false,
             match_ranges: Default::default(),
+            total_match_count: 0,
+            buffer_matches: Vec::new(),
+            buffer_subscriptions: HashMap::default(),
+            context_before: context_before.min(MAX_CONTEXT_LINES),
+            context_after: context_after.min(MAX_CONTEXT_LINES),
+            sort_order: SortOrder::Path,
+            results_filter: String::new(),
             active_query: None,
             last_search_query_text: None,
             search_id: 0,
             no_results: None,
+            showing_previous_results: false,
             limit_reached: false,
+            truncated: false,
+            stopped: false,
+            search_duration: None,
             search_history_cursor: Default::default(),
+            scope: SearchScope::Project,
+            symbol_kind: None,
+            symbol_scope: SymbolScope::All,
+            include_dependencies: false,
+            search_mode: SearchMode::Text,
+            filename_matches: Vec::new(),
+            hex_matches: Vec::new(),
+            dependency_worktrees: Vec::new(),
+            indexing: None,
+            semantic_scores: Vec::new(),
+            search_cache: Vec::new(),
+            _project_subscription: project_subscription,
         }
     }
 
     fn clone(&self, cx: &mut ModelContext<Self>) -> Model<Self> {
-        cx.new_model(|cx| Self {
-            project: self.project.clone(),
-            excerpts: self
-                .excerpts
-                .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
-            pending_search: Default::default(),
-            match_ranges: self.match_ranges.clone(),
-            active_query: self.active_query.clone(),
-            last_search_query_text: self.last_search_query_text.clone(),
-            search_id: self.search_id,
-            no_results: self.no_results,
-            limit_reached: self.limit_reached,
-            search_history_cursor: self.search_history_cursor.clone(),
+        cx.new_model(|cx| {
+            let mut search = Self {
+                project: self.project.clone(),
+                excerpts: self
+                    .excerpts
+                    .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
+                pending_search: Default::default(),
+                match_stream: None,
+                rebuilding_excerpts: false,
+                notify_throttle: None,
+                notify_skipped: false,
+                match_ranges: self.match_ranges.clone(),
+                total_match_count: self.total_match_count,
+                buffer_matches: self.buffer_matches.clone(),
+                buffer_subscriptions: HashMap::default(),
+                context_before: self.context_before,
+                context_after: self.context_after,
+                sort_order: self.sort_order,
+                results_filter: self.results_filter.clone(),
+                active_query: self.active_query.clone(),
+                last_search_query_text: self.last_search_query_text.clone(),
+                search_id: self.search_id,
+                no_results: self.no_results,
+                showing_previous_results: self.showing_previous_results,
+                limit_reached: self.limit_reached,
+                truncated: self.truncated,
+                stopped: self.stopped,
+                search_duration: self.search_duration,
+                search_history_cursor: self.search_history_cursor.clone(),
+                scope: self.scope.clone(),
+                symbol_kind: self.symbol_kind,
+                symbol_scope: self.symbol_scope,
+                include_dependencies: self.include_dependencies,
+                search_mode: self.search_mode,
+                filename_matches: self.filename_matches.clone(),
+                hex_matches: self.hex_matches.clone(),
+                dependency_worktrees: self.dependency_worktrees.clone(),
+                indexing: self.indexing,
+                semantic_scores: self.semantic_scores.clone(),
+                search_cache: Vec::new(),
+                _project_subscription: Self::subscribe_to_project(&self.project, cx),
+            };
+            for (buffer, _) in search.buffer_matches.clone() {
+                search.watch_buffer(&buffer, cx);
+            }
+            search
         })
     }
 
+    /// Files changing on disk may add or remove matches anywhere, so they drop the cached searches.
+    fn subscribe_to_project(project: &Model<Project>, cx: &mut ModelContext<Self>) -> Subscription {
+        cx.subscribe(project, |this, _, event, _| {
+            if let project::Event::WorktreeUpdatedEntries(..) = event {
+                this.search_cache.clear();
+            }
+        })
+    }
+
+    /// Drops the in-flight search task, so that it stops streaming results into this model.
+    fn cancel_pending_search(&mut self) -> bool {
+        self.rebuilding_excerpts = false;
+        self.notify_throttle = None;
+        self.notify_skipped = false;
+        self.match_stream = None;
+        self.pending_search.take().is_some()
+    }
+
+    /// Notifies the observers at most once per [`STREAMING_NOTIFY_INTERVAL`], so that streaming
+    /// many results doesn't re-render the search bar and the results for every file. A held back
+    /// notification goes out at the end of the interval.
+    fn notify_throttled(&mut self, cx: &mut ModelContext<Self>) {
+        if self.notify_throttle.is_some() {
+            self.notify_skipped = true;
+            return;
+        }
+        cx.notify();
+        self.notify_throttle = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(STREAMING_NOTIFY_INTERVAL)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.notify_throttle = None;
+                if mem::take(&mut this.notify_skipped) {
+                    this.notify_throttled(cx);
+                }
+            })
+            .ok();
+        }));
+    }
+
+    /// Sends out the notification held back by [`ProjectSearch::notify_throttled`], if any,
+    /// along with the one about the results being complete.
+    fn flush_notify(&mut self, cx: &mut ModelContext<Self>) {
+        self.notify_throttle = None;
+        self.notify_skipped = false;
+        cx.notify();
+    }
+
+    /// Inserts `range` at its position in the results, so that `match_ranges` stays in the order
+    /// of the results editor whichever order the excerpts are streamed in.
+    /// Inserts the ranges streamed for a batch of excerpts, ordering them in a single snapshot.
+    fn insert_match_ranges(&mut self, ranges: Vec<Range<Anchor>>, cx: &AppContext) {
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        for range in ranges {
+            self.insert_match_range(range, &snapshot);
+        }
+    }
+
+    fn insert_match_range(&mut self, range: Range<Anchor>, snapshot: &MultiBufferSnapshot) {
+        let ix = self
+            .match_ranges
+            .partition_point(|match_range| match_range.start.cmp(&range.start, snapshot).is_le());
+        self.match_ranges.insert(ix, range);
+    }
+
+    /// Stops the running search, keeping the results found so far.
+    fn stop_search(&mut self, cx: &mut ModelContext<Self>) {
+        if self.cancel_pending_search() {
+            self.stopped = true;
+            cx.notify();
+        }
+    }
+
+    fn stopped_label(&self) -> Option<String> {
+        self.stopped.then(|| {
+            let count = self.total_match_count;
+            let noun = if count == 1 { "result" } else { "results" };
+            format!("Stopped ({count} {noun})")
+        })
+    }
+
+    fn duration_label(&self) -> Option<String> {
+        self.search_duration.map(|duration| {
+            let count = self.total_match_count;
+            let noun = if count == 1 { "result" } else { "results" };
+            format!("{count} {noun} in {:.1}s", duration.as_secs_f32())
+        })
+    }
+
+    fn truncated_label(&self, cx: &AppContext) -> Option<String> {
+        self.truncated.then(|| {
+            let max_matches = SearchSettings::get_global(cx).max_matches;
+            format!("Showing first {max_matches} matches (truncated)")
+        })
+    }
+
+    /// Drops the ranges that would take the results past the `max_matches` setting,
+    /// marking them as truncated.
+    fn truncate_to_max_matches(
+        &mut self,
+        ranges: &mut Vec<Range<language::Anchor>>,
+        cx: &AppContext,
+    ) {
+        let max_matches = SearchSettings::get_global(cx).max_matches;
+        let remaining = max_matches.saturating_sub(self.total_match_count);
+        if ranges.len() > remaining {
+            ranges.truncate(remaining);
+            self.truncated = true;
+        }
+    }
+
+    /// Opens the sources of the project's Cargo dependencies as invisible worktrees,
+    /// resolving once all of them are scanned.
+    fn load_dependency_worktrees(&self, cx: &mut ModelContext<Self>) -> Task<Vec<Model<Worktree>>> {
+        let project = self.project.clone();
+        let fs = project.read(cx).fs().clone();
+        let roots = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| Some(worktree.read(cx).as_local()?.abs_path().to_path_buf()))
+            .collect::<Vec<_>>();
+        cx.spawn(|_, mut cx| async move {
+            let dirs = cargo_dependency_dirs(fs.as_ref(), &cargo_home(), &roots)
+                .await
+                .log_err()
+                .unwrap_or_default();
+            let mut worktrees = Vec::new();
+            for dir in dirs {
+                let Some(create_worktree) = project
+                    .update(&mut cx, |project, cx| {
+                        project.find_or_create_local_worktree(&dir, false, cx)
+                    })
+                    .log_err()
+                else {
+                    break;
+                };
+                let Some((worktree, _)) = create_worktree.await.log_err() else {
+                    continue;
+                };
+                let scan_complete = worktree
+                    .update(&mut cx, |worktree, _| {
+                        worktree.as_local().map(|worktree| worktree.scan_complete())
+                    })
+                    .ok()
+                    .flatten();
+                if let Some(scan_complete) = scan_complete {
+                    scan_complete.await;
+                }
+                worktrees.push(worktree);
+            }
+            worktrees
+        })
+    }
+
+    /// Returns the paths of the files with matches, in the order they appear in the results.
+    fn matched_project_paths(&self, cx: &AppContext) -> Vec<ProjectPath> {
+        let mut project_paths = Vec::new();
+        for (buffer, _) in &self.buffer_matches {
+            if let Some(project_path) = buffer.read(cx).project_path(cx) {
+                if !project_paths.contains(&project_path) {
+                    project_paths.push(project_path);
+                }
+            }
+        }
+        project_paths
+    }
+
+    /// Formats the lines of `match_ranges` as `path:line: text`, listing every matched line once.
+    fn match_lines(&self, match_ranges: &[Range<Anchor>], cx: &AppContext) -> String {
+        let mut lines = Vec::new();
+        let mut last_line = None;
+        for range in match_ranges {
+            let Some((path, start, line_text)) = self.match_line(range, cx) else {
+                continue;
+            };
+            let line = (range.start.buffer_id, start.row);
+            if last_line.replace(line) == Some(line) {
+                continue;
+            }
+            lines.push(format!("{}:{}: {line_text}", path.display(), start.row + 1));
+        }
+        lines.join("\n")
+    }
+
+    /// Lists every match as `path:line:column: text`, one per line, the way compilers
+    /// report their diagnostics.
+    fn match_report(&self, cx: &AppContext) -> String {
+        let mut report = String::new();
+        for range in &self.match_ranges {
+            if let Some((path, start, line_text)) = self.match_line(range, cx) {
+                report.push_str(&format!(
+                    "{}:{}:{}: {line_text}\n",
+                    path.display(),
+                    start.row + 1,
+                    start.column + 1
+                ));
+            }
+        }
+        report
+    }
+
+    /// Returns the path of the file `range` is in, where it starts and the text of that line.
+    fn match_line(
+        &self,
+        range: &Range<Anchor>,
+        cx: &AppContext,
+    ) -> Option<(PathBuf, language::Point, String)> {
+        let buffer = range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.excerpts.read(cx).buffer(buffer_id))?;
+        let snapshot = buffer.read(cx).snapshot();
+        let start = (range.start.text_anchor..range.end.text_anchor)
+            .to_point(&snapshot)
+            .start;
+        let path = self
+            .match_path(&range.start, cx)
+            .unwrap_or_else(|| PathBuf::from("untitled"));
+        let line_text = snapshot
+            .text_for_range(
+                language::Point::new(start.row, 0)
+                    ..language::Point::new(start.row, snapshot.line_len(start.row)),
+            )
+            .collect::<String>();
+        Some((path, start, line_text))
+    }
+
+    /// Returns the path of the file `anchor` is in, the way the include and exclude globs see it.
+    fn match_path(&self, anchor: &Anchor, cx: &AppContext) -> Option<PathBuf> {
+        let buffer = anchor
+            .buffer_id
+            .and_then(|buffer_id| self.excerpts.read(cx).buffer(buffer_id))?;
+        let file = buffer.read(cx).file()?;
+        if self.project.read(cx).visible_worktrees(cx).count() > 1 {
+            Some(file.full_path(cx))
+        } else {
+            Some(file.path().to_path_buf())
+        }
+    }
+
+    /// Searches the given buffers directly, without walking the project's worktrees.
+    fn search_buffers(
+        &self,
+        buffers: Vec<Model<Buffer>>,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<project::SearchResult> {
+        let project = self.project.read(cx);
+        let include_root = project.visible_worktrees(cx).count() > 1;
+        let snapshots = buffers
+            .into_iter()
+            .filter_map(|buffer| {
+                let snapshot = buffer.read(cx).snapshot();
+                let file_matches = snapshot.file().map_or(true, |file| {
+                    if include_root {
+                        query.file_matches(Some(&file.full_path(cx)))
+                    } else {
+                        query.file_matches(Some(file.path()))
+                    }
+                });
+                file_matches.then_some((buffer, snapshot))
+            })
+            .collect::<Vec<_>>();
+
+        let (tx, rx) = smol::channel::unbounded();
+        cx.background_executor()
+            .spawn(async move {
+                for (buffer, snapshot) in snapshots {
+                    let ranges = query
+                        .search(&snapshot, None)
+                        .await
+                        .iter()
+                        .map(|range| {
+                            snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                        })
+                        .collect::<Vec<_>>();
+                    if !ranges.is_empty()
+                        && tx
+                            .send(project::SearchResult::Buffer { buffer, ranges })
+                            .await
+                            .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        rx
+    }
+
+    /// Runs `query` over the search's scope and returns the matches of every buffer once the
+    /// search completes, without building any excerpts, e.g. for scripting searches headlessly.
+    /// Both this and [`Self::search`] consume [`Self::stream_matches`], which is where the
+    /// matching itself lives.
+    pub fn find_matches(
+        &mut self,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>> {
+        let (matches, match_stream) = self.stream_matches(query, cx);
+        cx.spawn(|_, _| async move {
+            let _match_stream = match_stream;
+            matches
+                .filter_map(|result| match result {
+                    project::SearchResult::Buffer { buffer, ranges } => Some((buffer, ranges)),
+                    project::SearchResult::LimitReached => None,
+                })
+                .collect()
+                .await
+        })
+    }
+
+    /// Resolves every match in the results to its file and position, for consumers that
+    /// shouldn't depend on the excerpts the results are displayed in.
+    pub fn structured_matches(&self, cx: &AppContext) -> Vec<MatchInfo> {
+        let excerpts = self.excerpts.read(cx);
+        let snapshot = excerpts.snapshot(cx);
+        self.match_ranges
+            .iter()
+            .filter_map(|range| {
+                let buffer = excerpts.buffer(range.start.buffer_id?)?.read(cx);
+                let start = range.start.text_anchor.to_point(buffer);
+                Some(MatchInfo {
+                    path: buffer.file()?.full_path(cx),
+                    line: start.row + 1,
+                    column: start.column + 1,
+                    text: snapshot.text_for_range(range.clone()).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Streams the matches of `query` in the search's scope, keeping only the ones in files
+    /// defining a symbol of the requested kind. The matches stop streaming once the returned
+    /// task is dropped.
+    fn stream_matches(
+        &mut self,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> (Receiver<project::SearchResult>, Task<Option<()>>) {
+        let scope_buffers = self.scope.buffers();
+        let changed_files_search = matches!(self.scope, SearchScope::ChangedFiles)
+            .then(|| (self.open_changed_files(cx), query.clone()));
+        let dependency_search = (scope_buffers.is_none()
+            && changed_files_search.is_none()
+            && self.include_dependencies
+            && self.project.read(cx).is_local())
+        .then(|| {
+            (
+                self.load_dependency_worktrees(cx),
+                query.clone().with_invisible_worktrees(true),
+            )
+        });
+        let search = match scope_buffers {
+            Some(buffers) => Some(self.search_buffers(buffers, query, cx)),
+            // The changed files can only be searched once their buffers are open, and dependency
+            // searches can only start once the dependency worktrees are scanned.
+            None => (changed_files_search.is_none() && dependency_search.is_none()).then(|| {
+                self.project
+                    .update(cx, |project, cx| project.search(query, cx))
+            }),
+        };
+        // The files defining a symbol of the requested kind are listed once, while the project
+        // is being searched, and the matches are then only kept in them.
+        let symbol_search = self.symbol_kind.map(|symbol_kind| {
+            let symbols = self
+                .project
+                .update(cx, |project, cx| project.symbols("", cx));
+            (symbol_kind, symbols)
+        });
+        let symbol_scope = self.symbol_scope;
+
+        let (tx, rx) = smol::channel::unbounded();
+        let match_stream = cx.spawn(|this, mut cx| async move {
+            let mut matches = match search {
+                Some(search) => search,
+                None if changed_files_search.is_some() => {
+                    let (changed_buffers, query) = changed_files_search?;
+                    let changed_buffers = changed_buffers.await;
+                    this.update(&mut cx, |this, cx| {
+                        this.search_buffers(changed_buffers, query, cx)
+                    })
+                    .ok()?
+                }
+                None => {
+                    let (dependency_worktrees, query) = dependency_search?;
+                    let dependency_worktrees = dependency_worktrees.await;
+                    this.update(&mut cx, |this, cx| {
+                        this.dependency_worktrees = dependency_worktrees;
+                        this.project
+                            .update(cx, |project, cx| project.search(query, cx))
+                    })
+                    .ok()?
+                }
+            };
+
+            let symbol_files = match symbol_search {
+                Some((symbol_kind, symbols)) => symbols.await.log_err().map(|symbols| {
+                    symbols
+                        .into_iter()
+                        .filter(|symbol| symbol.kind == symbol_kind)
+                        .map(|symbol| symbol.path)
+                        .collect::<HashSet<_>>()
+                }),
+                None => None,
+            };
+
+            while let Some(result) = matches.next().await {
+                let result = match result {
+                    project::SearchResult::Buffer { buffer, ranges } => {
+                        // Only files defining a symbol of the requested kind are kept. The files
+                        // that no language server can list the symbols of aren't filtered.
+                        if let Some(symbol_files) = &symbol_files {
+                            let is_symbol_file = this
+                                .update(&mut cx, |this, cx| {
+                                    this.is_symbol_file(&buffer, symbol_files, cx)
+                                })
+                                .ok()?;
+                            if !is_symbol_file {
+                                continue;
+                            }
+                        }
+                        if symbol_scope != SymbolScope::All {
+                            // The syntax around the matches is only known once their buffer is parsed.
+                            let (parsed_tx, parsed_rx) = smol::channel::bounded(1);
+                            let _subscription = this
+                                .update(&mut cx, |_, cx| {
+                                    if !buffer.read(cx).is_parsing() {
+                                        parsed_tx.try_send(()).ok();
+                                    }
+                                    cx.observe(&buffer, move |_, buffer, cx| {
+                                        if !buffer.read(cx).is_parsing() {
+                                            parsed_tx.try_send(()).ok();
+                                        }
+                                    })
+                                })
+                                .ok()?;
+                            parsed_rx.recv().await.ok();
+                        }
+                        let ranges = this
+                            .update(&mut cx, |this, cx| {
+                                this.mark_dependency_buffer(&buffer, cx);
+                                this.detect_language(&buffer, cx);
+                                let ranges = this.scope.ranges_in_scope(&buffer, ranges, cx);
+                                this.symbol_scope.ranges_in_scope(&buffer, ranges, cx)
+                            })
+                            .ok()?;
+                        if ranges.is_empty() {
+                            continue;
+                        }
+                        project::SearchResult::Buffer { buffer, ranges }
+                    }
+                    project::SearchResult::LimitReached => project::SearchResult::LimitReached,
+                };
+                // Dropping the search stream stops the project from looking for more matches.
+                if tx.send(result).await.is_err() {
+                    break;
+                }
+            }
+            Some(())
+        });
+        (rx, match_stream)
+    }
+
+    /// Whether `buffer` is one of the `symbol_files` listed by the workspace symbols, or can't
+    /// be, since none of its language servers can list them.
+    fn is_symbol_file(
+        &self,
+        buffer: &Model<Buffer>,
+        symbol_files: &HashSet<ProjectPath>,
+        cx: &AppContext,
+    ) -> bool {
+        let buffer = buffer.read(cx);
+        let Some(project_path) = buffer.project_path(cx) else {
+            return true;
+        };
+        symbol_files.contains(&project_path)
+            || !self
+                .project
+                .read(cx)
+                .language_servers_for_buffer(buffer, cx)
+                .any(|(_, server)| server.capabilities().workspace_symbol_provider.is_some())
+    }
+
+    /// Returns the files of the visible worktrees that git reports as changed. Deleted files
+    /// have no worktree entry left, so they are never part of them.
+    fn changed_files(&self, cx: &AppContext) -> Vec<ProjectPath> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .flat_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| entry.git_status.is_some())
+                    .map(move |entry| ProjectPath {
+                        worktree_id,
+                        path: entry.path.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Opens the buffers of the changed files, so that only they get searched instead of
+    /// filtering the matches of a search over the whole project.
+    fn open_changed_files(&self, cx: &mut ModelContext<Self>) -> Task<Vec<Model<Buffer>>> {
+        let open_buffers = self
+            .changed_files(cx)
+            .into_iter()
+            .map(|project_path| {
+                self.project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))
+            })
+            .collect::<Vec<_>>();
+        cx.background_executor().spawn(async move {
+            futures::future::join_all(open_buffers)
+                .await
+                .into_iter()
+                .filter_map(|buffer| buffer.log_err())
+                .collect()
+        })
+    }
+
+    /// Searches for `query` and shows its matches in the excerpts. The matches are consumed as
+    /// [`Self::stream_matches`] yields them rather than through [`Self::find_matches`], so that
+    /// the results appear while the project is still being searched.
     fn search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
-        let search = self.project.update(cx, |project, cx| {
-            project
-                .search_history_mut()
-                .add(&mut self.search_history_cursor, query.as_str().to_string());
-            project.search(query.clone(), cx)
+        self.cancel_pending_search();
+        self.stopped = false;
+        self.search_duration = None;
+        self.truncated = false;
+        self.indexing = None;
+        self.filename_matches.clear();
+        self.hex_matches.clear();
+        self.project.update(cx, |project, _| {
+            project.search_history_mut().add(
+                &mut self.search_history_cursor,
+                typed_query(&query).to_string(),
+            );
         });
-        self.last_search_query_text = Some(query.as_str().to_string());
+        let cache_key = self.search_cache_key(&query);
+        if let Some(buffer_matches) = cache_key
+            .as_ref()
+            .and_then(|cache_key| self.cached_search(cache_key, cx))
+        {
+            self.last_search_query_text = Some(typed_query(&query).to_string());
+            self.search_id += 1;
+            self.active_query = Some(query);
+            self.show_cached_search(buffer_matches, cx);
+            return;
+        }
+        let started_at = cx.background_executor().now();
+        let (mut matches, match_stream) = self.stream_matches(query.clone(), cx);
+        self.match_stream = Some(match_stream);
+        self.last_search_query_text = Some(typed_query(&query).to_string());
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        self.total_match_count = 0;
+        let keep_previous_results = SearchSettings::get_global(cx).keep_previous_results_on_empty;
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
-            let mut matches = search;
             let this = this.upgrade()?;
             this.update(&mut cx, |this, cx| {
                 this.match_ranges.clear();
-                this.excerpts.update(cx, |this, cx| this.clear(cx));
+                this.total_match_count = 0;
+                this.buffer_matches.clear();
+                this.buffer_subscriptions.clear();
+                // The previous results are replaced once the first match is found instead.
+                this.showing_previous_results =
+                    keep_previous_results && !this.excerpts.read(cx).excerpt_ids().is_empty();
+                if !this.showing_previous_results {
+                    this.excerpts.update(cx, |this, cx| this.clear(cx));
+                }
                 this.no_results = Some(true);
                 this.limit_reached = false;
+                this.truncated = false;
             })
             .ok()?;
 
@@ -225,25 +1788,48 @@ impl ProjectSearch {
             while let Some(result) = matches.next().await {
                 match result {
                     project::SearchResult::Buffer { buffer, ranges } => {
-                        let mut match_ranges = this
+                        let (match_ranges, truncated) = this
                             .update(&mut cx, |this, cx| {
                                 this.no_results = Some(false);
-                                this.excerpts.update(cx, |excerpts, cx| {
+                                this.buffer_matches.push((buffer.clone(), ranges.clone()));
+                                this.watch_buffer(&buffer, cx);
+                                let mut ranges = this.filter_ranges(&buffer, ranges, cx);
+                                this.truncate_to_max_matches(&mut ranges, cx);
+                                if ranges.is_empty() {
+                                    return (None, this.truncated);
+                                }
+                                this.total_match_count += ranges.len();
+                                if mem::take(&mut this.showing_previous_results) {
+                                    this.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+                                }
+                                let context_lines = (this.context_before, this.context_after);
+                                let match_ranges = this.excerpts.update(cx, |excerpts, cx| {
                                     excerpts.stream_excerpts_with_context_lines(
                                         buffer,
                                         ranges,
-                                        editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                                        context_lines,
                                         cx,
                                     )
-                                })
+                                });
+                                (Some(match_ranges), this.truncated)
                             })
                             .ok()?;
 
-                        while let Some(range) = match_ranges.next().await {
-                            this.update(&mut cx, |this, _| this.match_ranges.push(range))
+                        if let Some(match_ranges) = match_ranges {
+                            let mut match_range_batches = batch_match_ranges(match_ranges);
+                            while let Some(ranges) = match_range_batches.next().await {
+                                this.update(&mut cx, |this, cx| {
+                                    this.insert_match_ranges(ranges, cx)
+                                })
+                                .ok()?;
+                            }
+                            this.update(&mut cx, |this, cx| this.notify_throttled(cx))
                                 .ok()?;
                         }
-                        this.update(&mut cx, |_, cx| cx.notify()).ok()?;
+                        // Dropping the search stream stops the project from looking for more matches.
+                        if truncated {
+                            break;
+                        }
                     }
                     project::SearchResult::LimitReached => {
                         limit_reached = true;
@@ -254,7 +1840,17 @@ impl ProjectSearch {
             this.update(&mut cx, |this, cx| {
                 this.limit_reached = limit_reached;
                 this.pending_search.take();
-                cx.notify();
+                this.search_duration = Some(cx.background_executor().now() - started_at);
+                if let Some(cache_key) = cache_key.filter(|_| !limit_reached && !this.truncated) {
+                    this.cache_search(cache_key, cx);
+                }
+                cx.emit(ProjectSearchEvent::SearchCompleted {
+                    match_count: this.total_match_count,
+                });
+                if this.sort_order == SortOrder::Relevance {
+                    this.rebuild_excerpts(cx);
+                }
+                this.flush_notify(cx);
             })
             .ok()?;
 
@@ -262,1450 +1858,12035 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
-}
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum ViewEvent {
-    UpdateTab,
-    Activate,
-    EditorEvent(editor::EditorEvent),
-    Dismiss,
-}
+    /// Searches for a query in other scopes or with a symbol filter depend on more than the
+    /// query, so only the searches of the whole project are cached.
+    fn search_cache_key(&self, query: &SearchQuery) -> Option<SearchCacheKey> {
+        let cacheable = matches!(self.scope, SearchScope::Project)
+            && self.symbol_kind.is_none()
+            && self.symbol_scope == SymbolScope::All
+            && !self.include_dependencies;
+        cacheable.then(|| SearchCacheKey::new(query))
+    }
 
-impl EventEmitter<ViewEvent> for ProjectSearchView {}
+    fn open_buffer_versions(&self, cx: &AppContext) -> Vec<(BufferId, clock::Global)> {
+        let mut versions = self
+            .project
+            .read(cx)
+            .opened_buffers()
+            .into_iter()
+            .map(|buffer| {
+                let buffer = buffer.read(cx);
+                (buffer.remote_id(), buffer.version())
+            })
+            .collect::<Vec<_>>();
+        versions.sort_unstable_by_key(|(buffer_id, _)| *buffer_id);
+        versions
+    }
 
-impl Render for ProjectSearchView {
-    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        if self.has_matches() {
-            div()
-                .flex_1()
-                .size_full()
-                .track_focus(&self.focus_handle)
-                .child(self.results_editor.clone())
-        } else {
-            let model = self.model.read(cx);
-            let has_no_results = model.no_results.unwrap_or(false);
-            let is_search_underway = model.pending_search.is_some();
-            let major_text = if is_search_underway {
-                Label::new("Searching...")
-            } else if has_no_results {
-                Label::new("No results")
-            } else {
-                Label::new("Search all files")
-            };
+    /// Returns the matches of the cached search with the given key, unless a buffer of the
+    /// project changed since it ran.
+    fn cached_search(
+        &mut self,
+        cache_key: &SearchCacheKey,
+        cx: &AppContext,
+    ) -> Option<Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>> {
+        let index = self
+            .search_cache
+            .iter()
+            .position(|cached_search| cached_search.key == *cache_key)?;
+        let cached_search = self.search_cache.remove(index);
+        if cached_search.buffer_versions != self.open_buffer_versions(cx) {
+            return None;
+        }
+        let buffer_matches = cached_search.buffer_matches.clone();
+        self.search_cache.push(cached_search);
+        Some(buffer_matches)
+    }
 
-            let major_text = div().justify_center().max_w_96().child(major_text);
+    fn cache_search(&mut self, cache_key: SearchCacheKey, cx: &AppContext) {
+        self.search_cache
+            .retain(|cached_search| cached_search.key != cache_key);
+        self.search_cache.push(CachedSearch {
+            key: cache_key,
+            buffer_matches: self.buffer_matches.clone(),
+            buffer_versions: self.open_buffer_versions(cx),
+        });
+        if self.search_cache.len() > MAX_CACHED_SEARCHES {
+            self.search_cache.remove(0);
+        }
+    }
 
-            let minor_text: Option<SharedString> = if let Some(no_results) = model.no_results {
-                if model.pending_search.is_none() && no_results {
-                    Some("No results found in this project for the provided query".into())
-                } else {
-                    None
-                }
-            } else {
-                Some(self.landing_text_minor())
-            };
-            let minor_text = minor_text.map(|text| {
-                div()
-                    .items_center()
-                    .max_w_96()
-                    .child(Label::new(text).size(LabelSize::Small))
-            });
-            v_flex()
-                .flex_1()
-                .size_full()
-                .justify_center()
-                .bg(cx.theme().colors().editor_background)
-                .track_focus(&self.focus_handle)
-                .child(
-                    h_flex()
-                        .size_full()
-                        .justify_center()
-                        .child(h_flex().flex_1())
-                        .child(v_flex().child(major_text).children(minor_text))
-                        .child(h_flex().flex_1()),
-                )
+    /// Shows the matches of an identical search that ran before, without searching the project.
+    fn show_cached_search(
+        &mut self,
+        buffer_matches: Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.match_ranges.clear();
+        self.total_match_count = 0;
+        self.buffer_subscriptions.clear();
+        for (buffer, _) in &buffer_matches {
+            self.watch_buffer(buffer, cx);
         }
+        self.no_results = Some(buffer_matches.is_empty());
+        self.showing_previous_results = false;
+        self.limit_reached = false;
+        self.buffer_matches = buffer_matches;
+        if self.buffer_matches.is_empty() {
+            self.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+        } else {
+            self.rebuild_excerpts(cx);
+        }
+        cx.emit(ProjectSearchEvent::SearchCompleted {
+            match_count: self.total_match_count,
+        });
+        cx.notify();
     }
-}
 
-impl FocusableView for ProjectSearchView {
-    fn focus_handle(&self, _: &AppContext) -> gpui::FocusHandle {
-        self.focus_handle.clone()
+    /// Rebuilds the excerpts of a finished search with `context_before` lines before and
+    /// `context_after` lines after every match, without searching the project again.
+    fn set_context_lines(
+        &mut self,
+        context_before: u32,
+        context_after: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if (self.context_before, self.context_after) == (context_before, context_after) {
+            return;
+        }
+        self.context_before = context_before;
+        self.context_after = context_after;
+        self.rebuild_excerpts(cx);
     }
-}
 
-impl Item for ProjectSearchView {
-    type Event = ViewEvent;
-    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
-        let query_text = self.query_editor.read(cx).text(cx);
+    /// Reorders the files in the results of a finished search, without searching the project again.
+    fn set_sort_order(&mut self, sort_order: SortOrder, cx: &mut ModelContext<Self>) {
+        if self.sort_order == sort_order {
+            return;
+        }
+        self.sort_order = sort_order;
+        self.rebuild_excerpts(cx);
+    }
 
-        query_text
-            .is_empty()
-            .not()
-            .then(|| query_text.into())
-            .or_else(|| Some("Project Search".into()))
+    /// Sorts the matches of every file by decreasing relevance to the active query, keeping
+    /// the files that are as relevant in their current order.
+    fn sort_by_relevance(
+        &self,
+        buffer_matches: &mut [(Model<Buffer>, Vec<Range<language::Anchor>>)],
+        cx: &AppContext,
+    ) {
+        let Some(query) = self.active_query.as_ref() else {
+            return;
+        };
+        let query = typed_query(query).to_lowercase();
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        buffer_matches.sort_by_cached_key(|(buffer, ranges)| {
+            let buffer = buffer.read(cx);
+            let file = buffer.file();
+            let query_in_file_name = file.is_some_and(|file| {
+                file.file_name(cx)
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .contains(&query)
+            });
+            let whole_word_matches = ranges
+                .iter()
+                .filter(|range| {
+                    !buffer
+                        .reversed_chars_at(range.start)
+                        .next()
+                        .is_some_and(is_word_char)
+                        && !buffer.chars_at(range.end).next().is_some_and(is_word_char)
+                })
+                .count();
+            let mtime = file.and_then(|file| file.mtime());
+            Reverse((query_in_file_name, whole_word_matches, mtime))
+        });
     }
 
-    fn act_as_type<'a>(
-        &'a self,
-        type_id: TypeId,
-        self_handle: &'a View<Self>,
-        _: &'a AppContext,
-    ) -> Option<AnyView> {
-        if type_id == TypeId::of::<Self>() {
-            Some(self_handle.clone().into())
-        } else if type_id == TypeId::of::<Editor>() {
-            Some(self.results_editor.clone().into())
-        } else {
-            None
+    /// Narrows the results of a finished search down to the matches on lines containing `filter`,
+    /// restoring all of them once it is empty.
+    fn set_results_filter(&mut self, filter: String, cx: &mut ModelContext<Self>) {
+        if self.results_filter == filter {
+            return;
         }
+        self.results_filter = filter;
+        self.rebuild_excerpts(cx);
     }
 
-    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
-        self.results_editor
-            .update(cx, |editor, cx| editor.deactivated(cx));
+    fn filter_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        ranges: Vec<Range<language::Anchor>>,
+        cx: &AppContext,
+    ) -> Vec<Range<language::Anchor>> {
+        if self.results_filter.is_empty() {
+            return ranges;
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        ranges
+            .into_iter()
+            .filter(|range| {
+                let range = range.to_point(&snapshot);
+                let lines = language::Point::new(range.start.row, 0)
+                    ..language::Point::new(range.end.row, snapshot.line_len(range.end.row));
+                snapshot
+                    .text_for_range(lines)
+                    .collect::<String>()
+                    .contains(self.results_filter.as_str())
+            })
+            .collect()
     }
 
-    fn tab_content(&self, params: TabContentParams, cx: &WindowContext<'_>) -> AnyElement {
-        let last_query: Option<SharedString> = self
-            .model
-            .read(cx)
-            .last_search_query_text
-            .as_ref()
-            .map(|query| {
-                let query = query.replace('\n', "");
-                let query_text = util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN);
-                query_text.into()
-            });
-        let tab_name = last_query
-            .filter(|query| !query.is_empty())
-            .unwrap_or_else(|| "Project Search".into());
-        h_flex()
-            .gap_2()
-            .child(
-                Icon::new(IconName::MagnifyingGlass).color(if params.selected {
-                    Color::Default
-                } else {
-                    Color::Muted
-                }),
-            )
-            .child(Label::new(tab_name).color(if params.selected {
-                Color::Default
-            } else {
-                Color::Muted
-            }))
-            .into_any()
-    }
+    fn rebuild_excerpts(&mut self, cx: &mut ModelContext<Self>) {
+        if self.is_searching() || self.buffer_matches.is_empty() {
+            return;
+        }
 
-    fn telemetry_event_text(&self) -> Option<&'static str> {
-        Some("project search")
-    }
+        let context_lines = (self.context_before, self.context_after);
+        let mut all_buffer_matches = self.buffer_matches.clone();
+        if self.sort_order == SortOrder::Relevance && self.search_mode == SearchMode::Text {
+            self.sort_by_relevance(&mut all_buffer_matches, cx);
+        }
+        self.total_match_count = 0;
+        let mut buffer_matches = Vec::new();
+        for (buffer, ranges) in all_buffer_matches {
+            let mut ranges = self.filter_ranges(&buffer, ranges, cx);
+            self.truncate_to_max_matches(&mut ranges, cx);
+            if !ranges.is_empty() {
+                self.total_match_count += ranges.len();
+                buffer_matches.push((buffer, ranges));
+            }
+        }
+        self.match_ranges.clear();
+        self.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+        self.showing_previous_results = false;
+        self.rebuilding_excerpts = true;
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            for (buffer, ranges) in buffer_matches {
+                let match_ranges = this
+                    .update(&mut cx, |this, cx| {
+                        this.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.stream_excerpts_with_context_lines(
+                                buffer,
+                                ranges,
+                                context_lines,
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?;
 
-    fn for_each_project_item(
-        &self,
-        cx: &AppContext,
-        f: &mut dyn FnMut(EntityId, &dyn project::Item),
-    ) {
-        self.results_editor.for_each_project_item(cx, f)
-    }
+                let mut match_range_batches = batch_match_ranges(match_ranges);
+                while let Some(ranges) = match_range_batches.next().await {
+                    this.update(&mut cx, |this, cx| this.insert_match_ranges(ranges, cx))
+                        .ok()?;
+                }
+                this.update(&mut cx, |this, cx| this.notify_throttled(cx))
+                    .ok()?;
+            }
 
-    fn is_singleton(&self, _: &AppContext) -> bool {
-        false
-    }
+            this.update(&mut cx, |this, cx| {
+                this.rebuilding_excerpts = false;
+                this.pending_search.take();
+                this.flush_notify(cx);
+            })
+            .ok()?;
 
-    fn can_save(&self, _: &AppContext) -> bool {
-        true
+            None
+        }));
+        cx.notify();
     }
 
-    fn is_dirty(&self, cx: &AppContext) -> bool {
-        self.results_editor.read(cx).is_dirty(cx)
+    /// Whether a search is looking for matches, as opposed to no task or one rebuilding the
+    /// excerpts of the current matches running.
+    fn is_searching(&self) -> bool {
+        self.pending_search.is_some() && !self.rebuilding_excerpts
     }
 
-    fn has_conflict(&self, cx: &AppContext) -> bool {
-        self.results_editor.read(cx).has_conflict(cx)
+    /// Lists the files of the project's visible worktrees whose paths fuzzy-match `query`,
+    /// or match it as a glob when `as_path` is set and it compiles to one.
+    fn filename_search(&mut self, query: String, as_path: bool, cx: &mut ModelContext<Self>) {
+        self.cancel_pending_search();
+        self.stopped = false;
+        self.search_duration = None;
+        self.hex_matches.clear();
+        let project = self.project.read(cx);
+        let include_root = project.visible_worktrees(cx).count() > 1;
+        let mut project_paths = Vec::new();
+        let mut candidates = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            for entry in worktree.files(false, 0) {
+                let path = if include_root {
+                    Path::new(worktree.root_name()).join(&entry.path)
+                } else {
+                    entry.path.to_path_buf()
+                };
+                candidates.push(StringMatchCandidate::new(
+                    candidates.len(),
+                    path.to_string_lossy().into_owned(),
+                ));
+                project_paths.push(ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                });
+            }
+        }
+        self.project.update(cx, |project, _| {
+            project
+                .search_history_mut()
+                .add(&mut self.search_history_cursor, query.clone());
+        });
+        self.last_search_query_text = Some(query.clone());
+        self.search_id += 1;
+        self.active_query = None;
+        self.match_ranges.clear();
+        self.total_match_count = 0;
+        self.buffer_matches.clear();
+        self.buffer_subscriptions.clear();
+        self.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+        self.showing_previous_results = false;
+        let executor = cx.background_executor().clone();
+        let path_matcher = if as_path {
+            PathMatcher::new(&query).ok()
+        } else {
+            None
+        };
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            let matches = match path_matcher {
+                Some(path_matcher) => {
+                    executor
+                        .spawn(async move {
+                            candidates
+                                .into_iter()
+                                .filter(|candidate| path_matcher.is_match(&candidate.string))
+                                .take(FILENAME_SEARCH_LIMIT)
+                                .map(|candidate| StringMatch {
+                                    candidate_id: candidate.id,
+                                    score: 1.,
+                                    positions: Vec::new(),
+                                    string: candidate.string,
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .await
+                }
+                None => {
+                    fuzzy::match_strings(
+                        &candidates,
+                        &query,
+                        false,
+                        FILENAME_SEARCH_LIMIT,
+                        &AtomicBool::new(false),
+                        executor,
+                    )
+                    .await
+                }
+            };
+            this.update(&mut cx, |this, cx| {
+                this.filename_matches = matches
+                    .into_iter()
+                    .map(|path_match| (project_paths[path_match.candidate_id].clone(), path_match))
+                    .collect();
+                this.no_results = Some(this.filename_matches.is_empty());
+                this.limit_reached = false;
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
+            None
+        }));
+        cx.notify();
     }
 
-    fn save(
-        &mut self,
-        format: bool,
-        project: Model<Project>,
-        cx: &mut ViewContext<Self>,
-    ) -> Task<anyhow::Result<()>> {
-        self.results_editor
-            .update(cx, |editor, cx| editor.save(format, project, cx))
+    /// Lists the offsets at which the files of the project's visible worktrees contain `pattern`,
+    /// reading them from the file system since their buffers only hold valid UTF-8.
+    fn hex_search(&mut self, query: String, pattern: Vec<u8>, cx: &mut ModelContext<Self>) {
+        self.cancel_pending_search();
+        self.stopped = false;
+        self.search_duration = None;
+        self.filename_matches.clear();
+        let project = self.project.read(cx);
+        let fs = project.fs().clone();
+        // The files of remote projects can't be read from here.
+        let files = if project.is_local() {
+            project
+                .visible_worktrees(cx)
+                .flat_map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    let worktree_id = worktree.id();
+                    let abs_path = worktree.abs_path();
+                    worktree.files(false, 0).map(move |entry| {
+                        let project_path = ProjectPath {
+                            worktree_id,
+                            path: entry.path.clone(),
+                        };
+                        (project_path, abs_path.join(&entry.path))
+                    })
+                })
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        self.project.update(cx, |project, _| {
+            project
+                .search_history_mut()
+                .add(&mut self.search_history_cursor, query.clone());
+        });
+        self.last_search_query_text = Some(query);
+        self.search_id += 1;
+        self.active_query = None;
+        self.match_ranges.clear();
+        self.total_match_count = 0;
+        self.hex_matches.clear();
+        self.limit_reached = false;
+        self.truncated = false;
+        self.buffer_matches.clear();
+        self.buffer_subscriptions.clear();
+        self.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+        self.showing_previous_results = false;
+
+        // One more match than the results can hold is looked for, to tell whether any were left.
+        let max_matches = SearchSettings::get_global(cx).max_matches;
+        let (tx, rx) = smol::channel::unbounded::<Vec<HexMatch>>();
+        cx.background_executor()
+            .spawn(async move {
+                let mut match_count = 0;
+                for (project_path, abs_path) in files {
+                    // The search was stopped once its matches are no longer received.
+                    if tx.is_closed() || match_count > max_matches {
+                        break;
+                    }
+                    let Some(file) = fs.open_sync(&abs_path).await.log_err() else {
+                        continue;
+                    };
+                    let Some(file_matches) = find_hex_matches(
+                        &project_path,
+                        file,
+                        &pattern,
+                        max_matches + 1 - match_count,
+                    )
+                    .log_err() else {
+                        continue;
+                    };
+                    match_count += file_matches.len();
+                    if !file_matches.is_empty() && tx.send(file_matches).await.is_err() {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            while let Ok(mut file_matches) = rx.recv().await {
+                this.update(&mut cx, |this, cx| {
+                    let remaining = max_matches.saturating_sub(this.hex_matches.len());
+                    if file_matches.len() > remaining {
+                        file_matches.truncate(remaining);
+                        this.truncated = true;
+                    }
+                    this.hex_matches.extend(file_matches);
+                    this.notify_throttled(cx);
+                })
+                .ok()?;
+            }
+            this.update(&mut cx, |this, cx| {
+                this.no_results = Some(this.hex_matches.is_empty());
+                this.pending_search.take();
+                this.flush_notify(cx);
+            })
+            .ok()?;
+            None
+        }));
+        cx.notify();
     }
 
-    fn save_as(
+    /// Fills the results with the chunks of the project most similar to `query`,
+    /// in order of decreasing similarity rather than by path.
+    ///
+    /// Only the chunks of the files that `path_filter` searches are kept, see
+    /// [`SearchQuery::file_matches`].
+    fn semantic_search(
         &mut self,
-        _: Model<Project>,
-        _: PathBuf,
-        _: &mut ViewContext<Self>,
-    ) -> Task<anyhow::Result<()>> {
-        unreachable!("save_as should not have been called")
-    }
+        query: String,
+        path_filter: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !cx.has_global::<SemanticIndex>() {
+            return;
+        }
+        self.cancel_pending_search();
+        self.stopped = false;
+        self.search_duration = None;
+        self.filename_matches.clear();
+        self.hex_matches.clear();
+        let project = self.project.clone();
+        let project_index = cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+            semantic_index.project_index(project.clone(), cx)
+        });
+        // Searching an index that is still being built would miss the files not embedded yet.
+        let (status_tx, status_rx) = smol::channel::unbounded();
+        let index_status = (project_index.read(cx).status() == Status::Scanning).then(|| {
+            let subscription = cx.subscribe(&project_index, move |_, _, status, _| {
+                status_tx.try_send(*status).ok();
+            });
+            (subscription, status_rx)
+        });
+        self.indexing = index_status.is_some().then_some(IndexingState::Indexing);
+        self.project.update(cx, |project, _| {
+            project
+                .search_history_mut()
+                .add(&mut self.search_history_cursor, query.clone());
+        });
+        self.last_search_query_text = Some(query.clone());
+        self.search_id += 1;
+        self.active_query = None;
+        self.match_ranges.clear();
+        self.total_match_count = 0;
+        let threshold = SearchSettings::get_global(cx).semantic_search_threshold;
+        let include_root = project.read(cx).visible_worktrees(cx).count() > 1;
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            let this = this.upgrade()?;
+            if let Some((_subscription, mut status_rx)) = index_status {
+                while let Some(status) = status_rx.next().await {
+                    if status == Status::Idle {
+                        break;
+                    }
+                }
+                this.update(&mut cx, |this, cx| {
+                    this.indexing = Some(IndexingState::Complete);
+                    cx.notify();
+                })
+                .ok()?;
+            }
+            let results = project_index
+                .update(&mut cx, |project_index, cx| {
+                    project_index.search(&query, SEMANTIC_SEARCH_LIMIT, cx)
+                })
+                .ok()?
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.match_ranges.clear();
+                this.total_match_count = 0;
+                this.buffer_matches.clear();
+                this.buffer_subscriptions.clear();
+                this.semantic_scores.clear();
+                this.excerpts.update(cx, |this, cx| this.clear(cx));
+                this.showing_previous_results = false;
+                this.no_results = Some(true);
+                this.limit_reached = false;
+                this.truncated = false;
+            })
+            .ok()?;
 
-    fn reload(
-        &mut self,
-        project: Model<Project>,
-        cx: &mut ViewContext<Self>,
-    ) -> Task<anyhow::Result<()>> {
-        self.results_editor
-            .update(cx, |editor, cx| editor.reload(project, cx))
-    }
+            for result in results
+                .into_iter()
+                .filter(|result| result.score >= threshold)
+            {
+                let (worktree_id, full_path) = result
+                    .worktree
+                    .read_with(&cx, |worktree, _| {
+                        (
+                            worktree.id(),
+                            Path::new(worktree.root_name()).join(&result.path),
+                        )
+                    })
+                    .ok()?;
+                let path: &Path = if include_root {
+                    &full_path
+                } else {
+                    &result.path
+                };
+                if path_filter
+                    .worktree_id()
+                    .is_some_and(|filtered_worktree_id| filtered_worktree_id != worktree_id)
+                    || !path_filter.file_matches(Some(path))
+                {
+                    continue;
+                }
+                let project_path = ProjectPath {
+                    worktree_id,
+                    path: result.path.clone(),
+                };
+                let open_buffer = project
+                    .update(&mut cx, |project, cx| project.open_buffer(project_path, cx))
+                    .ok()?;
+                let Some(buffer) = open_buffer.await.log_err() else {
+                    continue;
+                };
+                // The file may have changed since it was indexed.
+                let range = buffer
+                    .read_with(&cx, |buffer, _| {
+                        let start =
+                            buffer.clip_offset(result.range.start.min(buffer.len()), Bias::Left);
+                        let end =
+                            buffer.clip_offset(result.range.end.min(buffer.len()), Bias::Right);
+                        buffer.anchor_before(start)..buffer.anchor_after(end)
+                    })
+                    .ok()?;
+                let match_ranges = this
+                    .update(&mut cx, |this, cx| {
+                        this.no_results = Some(false);
+                        this.total_match_count += 1;
+                        this.semantic_scores.push((range.start, result.score));
+                        this.detect_language(&buffer, cx);
+                        this.buffer_matches
+                            .push((buffer.clone(), vec![range.clone()]));
+                        let context_lines = (this.context_before, this.context_after);
+                        this.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.stream_excerpts_with_context_lines(
+                                buffer,
+                                vec![range],
+                                context_lines,
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?;
 
-    fn clone_on_split(
-        &self,
-        _workspace_id: WorkspaceId,
-        cx: &mut ViewContext<Self>,
-    ) -> Option<View<Self>>
-    where
-        Self: Sized,
-    {
-        let model = self.model.update(cx, |model, cx| model.clone(cx));
-        Some(cx.new_view(|cx| Self::new(model, cx, None)))
-    }
+                let mut match_range_batches = batch_match_ranges(match_ranges);
+                while let Some(ranges) = match_range_batches.next().await {
+                    this.update(&mut cx, |this, cx| this.insert_match_ranges(ranges, cx))
+                        .ok()?;
+                }
+                this.update(&mut cx, |_, cx| cx.notify()).ok()?;
+            }
 
-    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
-        self.results_editor
-            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
-    }
+            this.update(&mut cx, |this, cx| {
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
 
-    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
-        self.results_editor.update(cx, |editor, _| {
-            editor.set_nav_history(Some(nav_history));
-        });
+            None
+        }));
+        cx.notify();
     }
 
-    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
-        self.results_editor
-            .update(cx, |editor, cx| editor.navigate(data, cx))
+    /// Creates a search whose results are the given byte ranges of the project files, e.g. the
+    /// spans reported by an external tool, instead of the matches of a query.
+    pub fn from_ranges(
+        project: Model<Project>,
+        spans: Vec<(ProjectPath, Range<usize>)>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let mut search = Self::new(project, cx);
+        search.show_ranges(spans, cx);
+        search
     }
 
-    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
-        match event {
-            ViewEvent::UpdateTab => {
-                f(ItemEvent::UpdateBreadcrumbs);
-                f(ItemEvent::UpdateTab);
-            }
-            ViewEvent::EditorEvent(editor_event) => {
-                Editor::to_item_events(editor_event, f);
+    fn show_ranges(
+        &mut self,
+        spans: Vec<(ProjectPath, Range<usize>)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.cancel_pending_search();
+        self.search_id += 1;
+        self.active_query = None;
+        self.no_results = Some(true);
+        let mut file_spans = Vec::<(ProjectPath, Vec<Range<usize>>)>::new();
+        for (project_path, range) in spans {
+            match file_spans
+                .iter_mut()
+                .find(|(path, _)| *path == project_path)
+            {
+                Some((_, ranges)) => ranges.push(range),
+                None => file_spans.push((project_path, vec![range])),
             }
-            ViewEvent::Dismiss => f(ItemEvent::CloseItem),
-            _ => {}
         }
-    }
-
-    fn breadcrumb_location(&self) -> ToolbarItemLocation {
-        if self.has_matches() {
-            ToolbarItemLocation::Secondary
-        } else {
-            ToolbarItemLocation::Hidden
-        }
-    }
-
-    fn breadcrumbs(&self, theme: &theme::Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
-        self.results_editor.breadcrumbs(theme, cx)
-    }
-
-    fn serialized_item_kind() -> Option<&'static str> {
-        None
-    }
+        let project = self.project.clone();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            for (project_path, mut ranges) in file_spans {
+                let open_buffer = project
+                    .update(&mut cx, |project, cx| project.open_buffer(project_path, cx))
+                    .ok()?;
+                let Some(buffer) = open_buffer.await.log_err() else {
+                    continue;
+                };
+                ranges.sort_by_key(|range| (range.start, Reverse(range.end)));
+                let ranges = buffer
+                    .read_with(&cx, |buffer, _| {
+                        ranges
+                            .into_iter()
+                            .map(|range| {
+                                let start =
+                                    buffer.clip_offset(range.start.min(buffer.len()), Bias::Left);
+                                let end =
+                                    buffer.clip_offset(range.end.min(buffer.len()), Bias::Right);
+                                buffer.anchor_before(start)..buffer.anchor_after(end)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()?;
+                let match_ranges = this
+                    .update(&mut cx, |this, cx| {
+                        this.no_results = Some(false);
+                        this.total_match_count += ranges.len();
+                        this.detect_language(&buffer, cx);
+                        this.buffer_matches.push((buffer.clone(), ranges.clone()));
+                        let context_lines = (this.context_before, this.context_after);
+                        this.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.stream_excerpts_with_context_lines(
+                                buffer,
+                                ranges,
+                                context_lines,
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?;
 
-    fn deserialize(
-        _project: Model<Project>,
-        _workspace: WeakView<Workspace>,
-        _workspace_id: workspace::WorkspaceId,
-        _item_id: workspace::ItemId,
-        _cx: &mut ViewContext<Pane>,
-    ) -> Task<anyhow::Result<View<Self>>> {
-        unimplemented!()
-    }
-}
+                let mut match_range_batches = batch_match_ranges(match_ranges);
+                while let Some(ranges) = match_range_batches.next().await {
+                    this.update(&mut cx, |this, cx| this.insert_match_ranges(ranges, cx))
+                        .ok()?;
+                }
+                this.update(&mut cx, |_, cx| cx.notify()).ok()?;
+            }
 
-impl ProjectSearchView {
-    fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) {
-        self.filters_enabled = !self.filters_enabled;
-        cx.update_global(|state: &mut ActiveSettings, cx| {
-            state.0.insert(
-                self.model.read(cx).project.downgrade(),
-                self.current_settings(),
-            );
-        });
-    }
+            this.update(&mut cx, |this, cx| {
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
 
-    fn current_settings(&self) -> ProjectSearchSettings {
-        ProjectSearchSettings {
-            search_options: self.search_options,
-            filters_enabled: self.filters_enabled,
-        }
-    }
-    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
-        self.search_options.toggle(option);
-        cx.update_global(|state: &mut ActiveSettings, cx| {
-            state.0.insert(
-                self.model.read(cx).project.downgrade(),
-                self.current_settings(),
-            );
-        });
+            None
+        }));
+        cx.notify();
     }
 
-    fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
-        if self.model.read(cx).match_ranges.is_empty() {
-            return;
-        }
-        let Some(active_index) = self.active_match_index else {
+    /// Dependency sources are not meant to be edited, so their buffers are opened read-only.
+    fn mark_dependency_buffer(&self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
             return;
         };
-
-        let query = self.model.read(cx).active_query.clone();
-        if let Some(query) = query {
-            let query = query.with_replacement(self.replacement(cx));
-
-            // TODO: Do we need the clone here?
-            let mat = self.model.read(cx).match_ranges[active_index].clone();
-            self.results_editor.update(cx, |editor, cx| {
-                editor.replace(&mat, &query, cx);
+        let is_dependency = self
+            .dependency_worktrees
+            .iter()
+            .any(|worktree| worktree.read(cx).id() == project_path.worktree_id);
+        if is_dependency && buffer.read(cx).capability() != Capability::ReadOnly {
+            buffer.update(cx, |buffer, cx| {
+                buffer.set_capability(Capability::ReadOnly, cx)
             });
-            self.select_match(Direction::Next, cx)
         }
     }
-    pub fn replacement(&self, cx: &AppContext) -> String {
-        self.replacement_editor.read(cx).text(cx)
-    }
-    fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
-        if self.active_match_index.is_none() {
-            return;
-        }
-
-        let Some(query) = self.model.read(cx).active_query.as_ref() else {
-            return;
-        };
-        let query = query.clone().with_replacement(self.replacement(cx));
 
-        let match_ranges = self
-            .model
-            .update(cx, |model, _| mem::take(&mut model.match_ranges));
-        if match_ranges.is_empty() {
+    fn watch_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.buffer_subscriptions.contains_key(&buffer_id) {
             return;
         }
-
-        self.results_editor.update(cx, |editor, cx| {
-            for item in &match_ranges {
-                editor.replace(item, &query, cx);
+        let subscription = cx.subscribe(buffer, |this, buffer, event, cx| {
+            if let language::Event::Reloaded = event {
+                this.refresh_buffer_matches(buffer, cx);
             }
         });
-
-        self.model.update(cx, |model, _cx| {
-            model.match_ranges = match_ranges;
-        });
+        self.buffer_subscriptions.insert(buffer_id, subscription);
     }
 
-    fn new(
-        model: Model<ProjectSearch>,
-        cx: &mut ViewContext<Self>,
-        settings: Option<ProjectSearchSettings>,
-    ) -> Self {
-        let project;
-        let excerpts;
-        let mut replacement_text = None;
-        let mut query_text = String::new();
-        let mut subscriptions = Vec::new();
-
-        // Read in settings if available
-        let (mut options, filters_enabled) = if let Some(settings) = settings {
-            (settings.search_options, settings.filters_enabled)
-        } else {
-            (SearchOptions::NONE, false)
-        };
-
-        {
-            let model = model.read(cx);
-            project = model.project.clone();
-            excerpts = model.excerpts.clone();
-            if let Some(active_query) = model.active_query.as_ref() {
-                query_text = active_query.as_str().to_string();
-                replacement_text = active_query.replacement().map(ToOwned::to_owned);
-                options = SearchOptions::from_query(active_query);
-            }
+    /// Looks for the matches of the active query in a result buffer again once it was reloaded
+    /// from disk, replacing its excerpts with the ones of the matches it still has.
+    fn refresh_buffer_matches(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        if self.search_mode != SearchMode::Text || self.is_searching() {
+            return;
         }
-        subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
-
-        let query_editor = cx.new_view(|cx| {
-            let mut editor = Editor::single_line(cx);
-            editor.set_placeholder_text("Search all files..", cx);
-            editor.set_text(query_text, cx);
-            editor
-        });
-        // Subscribe to query_editor in order to reraise editor events for workspace item activation purposes
-        subscriptions.push(
-            cx.subscribe(&query_editor, |_, _, event: &EditorEvent, cx| {
-                cx.emit(ViewEvent::EditorEvent(event.clone()))
-            }),
-        );
-        let replacement_editor = cx.new_view(|cx| {
-            let mut editor = Editor::single_line(cx);
-            editor.set_placeholder_text("Replace in project..", cx);
-            if let Some(text) = replacement_text {
-                editor.set_text(text, cx);
-            }
-            editor
-        });
-        let results_editor = cx.new_view(|cx| {
-            let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), cx);
-            editor.set_searchable(false);
-            editor
+        let Some(query) = self.active_query.clone() else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let ranges = cx.background_executor().spawn(async move {
+            query
+                .search(&snapshot, None)
+                .await
+                .iter()
+                .map(|range| snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end))
+                .collect::<Vec<_>>()
         });
-        subscriptions.push(cx.observe(&results_editor, |_, _, cx| cx.emit(ViewEvent::UpdateTab)));
-
-        subscriptions.push(
-            cx.subscribe(&results_editor, |this, _, event: &EditorEvent, cx| {
-                if matches!(event, editor::EditorEvent::SelectionsChanged { .. }) {
-                    this.update_match_index(cx);
+        cx.spawn(|this, mut cx| async move {
+            let ranges = ranges.await;
+            this.update(&mut cx, |this, cx| {
+                if this.is_searching() {
+                    return;
                 }
-                // Reraise editor events for workspace item activation purposes
-                cx.emit(ViewEvent::EditorEvent(event.clone()));
-            }),
-        );
-
-        let included_files_editor = cx.new_view(|cx| {
-            let mut editor = Editor::single_line(cx);
-            editor.set_placeholder_text("Include: crates/**/*.toml", cx);
-
-            editor
-        });
-        // Subscribe to include_files_editor in order to reraise editor events for workspace item activation purposes
-        subscriptions.push(
-            cx.subscribe(&included_files_editor, |_, _, event: &EditorEvent, cx| {
-                cx.emit(ViewEvent::EditorEvent(event.clone()))
-            }),
-        );
-
-        let excluded_files_editor = cx.new_view(|cx| {
-            let mut editor = Editor::single_line(cx);
-            editor.set_placeholder_text("Exclude: vendor/*, *.lock", cx);
-
-            editor
-        });
-        // Subscribe to excluded_files_editor in order to reraise editor events for workspace item activation purposes
-        subscriptions.push(
-            cx.subscribe(&excluded_files_editor, |_, _, event: &EditorEvent, cx| {
-                cx.emit(ViewEvent::EditorEvent(event.clone()))
-            }),
-        );
-
-        let focus_handle = cx.focus_handle();
-        subscriptions.push(cx.on_focus_in(&focus_handle, |this, cx| {
-            if this.focus_handle.is_focused(cx) {
-                if this.has_matches() {
-                    this.results_editor.focus_handle(cx).focus(cx);
+                let Some(index) = this
+                    .buffer_matches
+                    .iter()
+                    .position(|(matched_buffer, _)| *matched_buffer == buffer)
+                else {
+                    return;
+                };
+                let ranges = this.scope.ranges_in_scope(&buffer, ranges, cx);
+                let ranges = this.symbol_scope.ranges_in_scope(&buffer, ranges, cx);
+                if ranges.is_empty() {
+                    this.buffer_matches.remove(index);
                 } else {
-                    this.query_editor.focus_handle(cx).focus(cx);
+                    this.buffer_matches[index].1 = ranges;
                 }
-            }
-        }));
-
-        // Check if Worktrees have all been previously indexed
-        let mut this = ProjectSearchView {
-            focus_handle,
-            replacement_editor,
-            search_id: model.read(cx).search_id,
-            model,
-            query_editor,
-            results_editor,
-            search_options: options,
-            panels_with_errors: HashSet::default(),
-            active_match_index: None,
-            query_editor_was_focused: false,
-            included_files_editor,
-            excluded_files_editor,
-            filters_enabled,
-            replace_enabled: false,
-            _subscriptions: subscriptions,
-        };
-        this.model_changed(cx);
-        this
+                if this.buffer_matches.is_empty() {
+                    this.match_ranges.clear();
+                    this.total_match_count = 0;
+                    this.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
+                    this.no_results = Some(true);
+                    cx.notify();
+                } else {
+                    this.rebuild_excerpts(cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
     }
 
-    pub fn new_search_in_directory(
-        workspace: &mut Workspace,
-        dir_path: &Path,
-        cx: &mut ViewContext<Workspace>,
-    ) {
-        let Some(filter_str) = dir_path.to_str() else {
+    /// Infers the language of a matched buffer from its path and contents when the project
+    /// could not tell it yet, e.g. because the language was still loading when the buffer was
+    /// opened, so that its excerpts are syntax highlighted.
+    fn detect_language(&self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer_ref = buffer.read(cx);
+        if buffer_ref.language().is_some() {
+            return;
+        }
+        let Some(file) = buffer_ref.file() else {
             return;
         };
-
-        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
-        let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
-        workspace.add_item_to_active_pane(Box::new(search.clone()), cx);
-        search.update(cx, |search, cx| {
-            search
-                .included_files_editor
-                .update(cx, |editor, cx| editor.set_text(filter_str, cx));
-            search.filters_enabled = true;
-            search.focus_query_editor(cx)
-        });
+        let language = self.project.read(cx).languages().language_for_file(
+            file,
+            Some(buffer_ref.as_rope()),
+            cx,
+        );
+        let buffer = buffer.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            // Files of unknown languages stay plain text.
+            let language = language.await.ok()?;
+            buffer
+                .update(&mut cx, |buffer, cx| {
+                    if buffer.language().is_none() {
+                        buffer.set_language(Some(language), cx);
+                    }
+                })
+                .ok()
+        })
+        .detach();
     }
+}
 
-    // Re-activate the most recently activated search in this pane or the most recent if it has been closed.
-    // If no search exists in the workspace, create a new one.
-    fn deploy_search(
-        workspace: &mut Workspace,
-        action: &workspace::DeploySearch,
-        cx: &mut ViewContext<Workspace>,
-    ) {
-        let existing = workspace
-            .active_pane()
-            .read(cx)
-            .items()
-            .find_map(|item| item.downcast::<ProjectSearchView>());
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ProjectSearchEvent {
+    SearchCompleted { match_count: usize },
+}
 
-        Self::existing_or_new_search(workspace, existing, action, cx);
-    }
+impl EventEmitter<ProjectSearchEvent> for ProjectSearch {}
 
-    fn search_in_new(workspace: &mut Workspace, _: &SearchInNew, cx: &mut ViewContext<Workspace>) {
-        if let Some(search_view) = workspace
-            .active_item(cx)
-            .and_then(|item| item.downcast::<ProjectSearchView>())
-        {
-            let new_query = search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.build_search_query(cx);
-                if new_query.is_some() {
-                    if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
-                        search_view.query_editor.update(cx, |editor, cx| {
-                            editor.set_text(old_query.as_str(), cx);
-                        });
-                        search_view.search_options = SearchOptions::from_query(&old_query);
-                    }
+pub enum ViewEvent {
+    UpdateTab,
+    Activate,
+    EditorEvent(editor::EditorEvent),
+    Dismiss,
+    /// A project search went through the whole project, or up to the `max_matches` setting.
+    SearchCompleted {
+        match_count: usize,
+    },
+}
+
+impl EventEmitter<ViewEvent> for ProjectSearchView {}
+
+impl Render for ProjectSearchView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.has_matches() && self.replace_preview && self.can_replace(cx) {
+            div()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .bg(cx.theme().colors().editor_background)
+                .child(self.render_replace_preview(cx))
+        } else if self.has_matches() && self.results_as_list {
+            div()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .bg(cx.theme().colors().editor_background)
+                .child(self.render_results_list(cx))
+        } else if self.has_matches() {
+            let minimap_entries = self.minimap_entries(cx);
+            v_flex()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .when(!self.bookmarks.is_empty(), |this| {
+                    this.child(self.render_bookmarks(cx))
+                })
+                .child(
+                    h_flex()
+                        .flex_1()
+                        .size_full()
+                        .child(
+                            div()
+                                .flex_1()
+                                .size_full()
+                                .child(self.results_editor.clone()),
+                        )
+                        .when(minimap_entries.len() > 1, |this| {
+                            this.child(self.render_minimap(minimap_entries, cx))
+                        }),
+                )
+        } else if self.has_filename_matches(cx) {
+            div()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .bg(cx.theme().colors().editor_background)
+                .child(self.render_filename_matches(cx))
+        } else if self.has_hex_matches(cx) {
+            div()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .bg(cx.theme().colors().editor_background)
+                .child(self.render_hex_matches(cx))
+        } else if self.model.read(cx).showing_previous_results {
+            div()
+                .flex_1()
+                .size_full()
+                .key_context("ProjectSearchView")
+                .track_focus(&self.focus_handle)
+                .opacity(0.5)
+                .child(self.results_editor.clone())
+        } else {
+            let model = self.model.read(cx);
+            let has_no_results = model.no_results.unwrap_or(false);
+            let is_search_underway = model.pending_search.is_some();
+            let indexing = model.indexing.filter(|_| is_search_underway);
+            let major_text = if indexing == Some(IndexingState::Indexing) {
+                Label::new("Indexing...")
+            } else if indexing == Some(IndexingState::Complete) {
+                Label::new("Indexing complete")
+            } else if is_search_underway {
+                Label::new("Searching...")
+            } else if let Some(stopped_label) = model.stopped_label() {
+                Label::new(stopped_label)
+            } else if has_no_results {
+                Label::new("No results")
+            } else {
+                Label::new("Search all files")
+            };
+
+            let major_text = div().justify_center().max_w_96().child(major_text);
+            // The index only tells whether it is scanning, so the bar sweeps instead of filling up.
+            let indexing_bar = (indexing == Some(IndexingState::Indexing)).then(|| {
+                div()
+                    .mt_2()
+                    .w_48()
+                    .h_1()
+                    .rounded_sm()
+                    .overflow_hidden()
+                    .bg(cx.theme().colors().element_background)
+                    .child(
+                        div()
+                            .h_full()
+                            .w_1_4()
+                            .rounded_sm()
+                            .bg(cx.theme().colors().text_accent)
+                            .with_animation(
+                                "project-search-indexing",
+                                Animation::new(Duration::from_secs(2)).repeat(),
+                                |bar, delta| bar.ml(relative(delta * 0.75)),
+                            ),
+                    )
+            });
+
+            let minor_text: Option<SharedString> = if let Some(no_results) = model.no_results {
+                if model.pending_search.is_none() && !model.stopped && no_results {
+                    Some("No results found in this project for the provided query".into())
+                } else {
+                    None
                 }
-                new_query
+            } else {
+                Some(self.landing_text_minor())
+            };
+            let minor_text = minor_text.map(|text| {
+                div()
+                    .items_center()
+                    .max_w_96()
+                    .child(Label::new(text).size(LabelSize::Small))
             });
-            if let Some(new_query) = new_query {
-                let model = cx.new_model(|cx| {
-                    let mut model = ProjectSearch::new(workspace.project().clone(), cx);
-                    model.search(new_query, cx);
-                    model
-                });
-                workspace.add_item_to_active_pane(
-                    Box::new(cx.new_view(|cx| ProjectSearchView::new(model, cx, None))),
-                    cx,
-                );
-            }
+            v_flex()
+                .flex_1()
+                .size_full()
+                .justify_center()
+                .bg(cx.theme().colors().editor_background)
+                .track_focus(&self.focus_handle)
+                .child(
+                    h_flex()
+                        .size_full()
+                        .justify_center()
+                        .child(h_flex().flex_1())
+                        .child(
+                            v_flex()
+                                .child(major_text)
+                                .children(indexing_bar)
+                                .children(minor_text),
+                        )
+                        .child(h_flex().flex_1()),
+                )
         }
     }
+}
 
-    // Add another search tab to the workspace.
-    fn new_search(
-        workspace: &mut Workspace,
-        _: &workspace::NewSearch,
-        cx: &mut ViewContext<Workspace>,
-    ) {
-        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx)
+impl FocusableView for ProjectSearchView {
+    fn focus_handle(&self, _: &AppContext) -> gpui::FocusHandle {
+        self.focus_handle.clone()
     }
+}
 
-    fn existing_or_new_search(
-        workspace: &mut Workspace,
-        existing: Option<View<ProjectSearchView>>,
-        action: &workspace::DeploySearch,
-        cx: &mut ViewContext<Workspace>,
-    ) {
-        let query = workspace.active_item(cx).and_then(|item| {
-            let editor = item.act_as::<Editor>(cx)?;
-            let query = editor.query_suggestion(cx);
-            if query.is_empty() {
-                None
-            } else {
-                Some(query)
-            }
-        });
+impl Item for ProjectSearchView {
+    type Event = ViewEvent;
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let query_text = self.query_editor.read(cx).text(cx);
 
-        let search = if let Some(existing) = existing {
-            workspace.activate_item(&existing, cx);
-            existing
+        query_text
+            .is_empty()
+            .not()
+            .then(|| query_text.into())
+            .or_else(|| Some("Project Search".into()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.results_editor.clone().into())
         } else {
-            let settings = cx
-                .global::<ActiveSettings>()
-                .0
-                .get(&workspace.project().downgrade());
+            None
+        }
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.results_editor
+            .update(cx, |editor, cx| editor.deactivated(cx));
+    }
 
-            let settings = if let Some(settings) = settings {
-                Some(settings.clone())
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext<'_>) -> AnyElement {
+        let tab_name = self.tab_title(cx);
+        h_flex()
+            .gap_2()
+            .child(
+                Icon::new(IconName::MagnifyingGlass).color(if params.selected {
+                    Color::Default
+                } else {
+                    Color::Muted
+                }),
+            )
+            .child(Label::new(tab_name).color(if params.selected {
+                Color::Default
             } else {
-                None
-            };
+                Color::Muted
+            }))
+            .when(self.results_pinned, |this| {
+                this.child(Indicator::dot().color(Color::Accent))
+            })
+            .into_any()
+    }
 
-            let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
-            let view = cx.new_view(|cx| ProjectSearchView::new(model, cx, settings));
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("project search")
+    }
 
-            workspace.add_item_to_active_pane(Box::new(view.clone()), cx);
-            view
-        };
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(EntityId, &dyn project::Item),
+    ) {
+        self.results_editor.for_each_project_item(cx, f)
+    }
 
-        search.update(cx, |search, cx| {
-            search.replace_enabled = action.replace_enabled;
-            if let Some(query) = query {
-                search.set_query(&query, cx);
-            }
-            search.focus_query_editor(cx)
-        });
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
     }
 
-    fn search(&mut self, cx: &mut ViewContext<Self>) {
-        if let Some(query) = self.build_search_query(cx) {
-            self.model.update(cx, |model, cx| model.search(query, cx));
-        }
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
     }
 
-    fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
-        // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
-        let text = self.query_editor.read(cx).text(cx);
-        let included_files =
-            match Self::parse_path_matches(&self.included_files_editor.read(cx).text(cx)) {
-                Ok(included_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-                    included_files
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
-                }
-            };
-        let excluded_files =
-            match Self::parse_path_matches(&self.excluded_files_editor.read(cx).text(cx)) {
-                Ok(excluded_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.results_editor.read(cx).is_dirty(cx)
+    }
 
-                    excluded_files
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
-                }
-            };
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.results_editor.read(cx).has_conflict(cx)
+    }
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
-            match SearchQuery::regex(
-                text,
-                self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
-                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
-                included_files,
-                excluded_files,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.results_editor
+            .update(cx, |editor, cx| editor.save(format, project, cx))
+    }
 
-                    Some(query)
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
-                    if should_mark_error {
-                        cx.notify();
-                    }
+    /// Writes a report of the matches to `abs_path`, leaving the matched buffers untouched.
+    fn save_as(
+        &mut self,
+        project: Model<Project>,
+        abs_path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let report = self.model.read(cx).match_report(cx);
+        let fs = project.read(cx).fs().clone();
+        cx.background_executor()
+            .spawn(async move { fs.atomic_write(abs_path, report).await })
+    }
 
-                    None
-                }
+    fn reload(
+        &mut self,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.results_editor
+            .update(cx, |editor, cx| editor.reload(project, cx))
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        let model = self.model.update(cx, |model, cx| model.clone(cx));
+        Some(cx.new_view(|cx| Self::new(model, cx, None)))
+    }
+
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.results_editor
+            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+        self.workspace = Some(workspace.weak_handle());
+
+        let workspace_id = workspace.database_id();
+        match self.workspace_id.replace(workspace_id) {
+            Some(old_workspace_id) if old_workspace_id != workspace_id => {
+                cx.background_executor()
+                    .spawn(PROJECT_SEARCH_DB.update_workspace_id(
+                        workspace_id,
+                        old_workspace_id,
+                        cx.entity_id().as_u64(),
+                    ))
+                    .detach();
             }
-        } else {
-            match SearchQuery::text(
-                text,
-                self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
-                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
-                included_files,
-                excluded_files,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
+            _ => self.serialize(cx),
+        }
+    }
 
-                    Some(query)
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
-                    if should_mark_error {
-                        cx.notify();
-                    }
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.results_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
 
-                    None
-                }
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.results_editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            ViewEvent::UpdateTab => {
+                f(ItemEvent::UpdateBreadcrumbs);
+                f(ItemEvent::UpdateTab);
             }
-        };
-        if !self.panels_with_errors.is_empty() {
-            return None;
+            ViewEvent::EditorEvent(editor_event) => {
+                Editor::to_item_events(editor_event, f);
+            }
+            ViewEvent::Dismiss => f(ItemEvent::CloseItem),
+            _ => {}
         }
-        if query.as_ref().is_some_and(|query| query.is_empty()) {
-            return None;
+    }
+
+    fn breadcrumb_location(&self) -> ToolbarItemLocation {
+        if self.has_matches() {
+            ToolbarItemLocation::Secondary
+        } else {
+            ToolbarItemLocation::Hidden
         }
-        query
     }
 
-    fn parse_path_matches(text: &str) -> anyhow::Result<Vec<PathMatcher>> {
-        text.split(',')
-            .map(str::trim)
-            .filter(|maybe_glob_str| !maybe_glob_str.is_empty())
-            .map(|maybe_glob_str| {
-                PathMatcher::new(maybe_glob_str)
-                    .with_context(|| format!("parsing {maybe_glob_str} as path matcher"))
-            })
-            .collect()
+    /// Shows where the active match is in its file, wherever the cursor is in the results.
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
+        let model = self.model.read(cx);
+        let Some((path, start, _)) = self
+            .active_match_index
+            .and_then(|index| model.match_ranges.get(index))
+            .and_then(|range| model.match_line(range, cx))
+        else {
+            return self.results_editor.breadcrumbs(theme, cx);
+        };
+        Some(vec![BreadcrumbText {
+            text: format!("{}:{}", path.display(), start.row + 1),
+            highlights: None,
+            font: Some(ThemeSettings::get_global(cx).buffer_font.clone()),
+        }])
     }
 
-    fn select_match(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
-        if let Some(index) = self.active_match_index {
-            let match_ranges = self.model.read(cx).match_ranges.clone();
-            let new_index = self.results_editor.update(cx, |editor, cx| {
-                editor.match_index_for_direction(&match_ranges, index, direction, 1, cx)
-            });
+    fn serialized_item_kind() -> Option<&'static str> {
+        Some(SERIALIZED_ITEM_KIND)
+    }
 
-            let range_to_select = match_ranges[new_index].clone();
-            self.results_editor.update(cx, |editor, cx| {
-                let range_to_select = editor.range_for_match(&range_to_select);
-                editor.unfold_ranges([range_to_select.clone()], false, true, cx);
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.select_ranges([range_to_select])
+    fn deserialize(
+        project: Model<Project>,
+        _workspace: WeakView<Workspace>,
+        workspace_id: workspace::WorkspaceId,
+        item_id: workspace::ItemId,
+        cx: &mut ViewContext<Pane>,
+    ) -> Task<anyhow::Result<View<Self>>> {
+        cx.spawn(|pane, mut cx| async move {
+            PROJECT_SEARCH_DB
+                .delete_closed_searches(workspace_id, SERIALIZED_ITEM_KIND.to_string())
+                .await
+                .log_err();
+            let (
+                query,
+                regex,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                files_to_include,
+                files_to_exclude,
+                regex_flags,
+            ) = PROJECT_SEARCH_DB
+                .get_query(item_id, workspace_id)?
+                .context("No query stored for this project search")?;
+            let mut search_options =
+                SearchOptions::from_regex_flags(regex_flags.as_deref().unwrap_or_default());
+            search_options.set(SearchOptions::REGEX, regex);
+            search_options.set(SearchOptions::WHOLE_WORD, whole_word);
+            search_options.set(SearchOptions::CASE_SENSITIVE, case_sensitive);
+            search_options.set(SearchOptions::INCLUDE_IGNORED, include_ignored);
+            let review_position = PROJECT_SEARCH_DB
+                .get_review_position(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .and_then(|(active_match_index, scroll_x, scroll_y)| {
+                    Some(ReviewPosition {
+                        active_match_index: active_match_index? as usize,
+                        scroll_position: Point::new(
+                            scroll_x.unwrap_or_default(),
+                            scroll_y.unwrap_or_default(),
+                        ),
+                    })
                 });
-            });
-        }
-    }
 
-    fn focus_query_editor(&mut self, cx: &mut ViewContext<Self>) {
-        self.query_editor.update(cx, |query_editor, cx| {
-            query_editor.select_all(&SelectAll, cx);
-        });
-        self.query_editor_was_focused = true;
-        let editor_handle = self.query_editor.focus_handle(cx);
-        cx.focus(&editor_handle);
+            pane.update(&mut cx, |_, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(project, cx));
+                cx.new_view(|cx| {
+                    let mut view = Self::new(model, cx, None);
+                    view.restore(
+                        query,
+                        search_options,
+                        files_to_include,
+                        files_to_exclude,
+                        review_position,
+                        cx,
+                    );
+                    view
+                })
+            })
+        })
     }
+}
 
-    fn set_query(&mut self, query: &str, cx: &mut ViewContext<Self>) {
-        self.query_editor
-            .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+impl ProjectSearchView {
+    fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) {
+        self.filters_enabled = !self.filters_enabled;
+        self.remember_settings(cx);
     }
 
-    fn focus_results_editor(&mut self, cx: &mut ViewContext<Self>) {
-        self.query_editor.update(cx, |query_editor, cx| {
-            let cursor = query_editor.selections.newest_anchor().head();
-            query_editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+    /// Stores the search options and filters visibility as the ones the project's new searches
+    /// start with.
+    fn remember_settings(&self, cx: &mut ViewContext<Self>) {
+        cx.update_global(|state: &mut ActiveSettings, cx| {
+            state.0.insert(
+                self.model.read(cx).project.downgrade(),
+                self.current_settings(),
+            );
         });
-        self.query_editor_was_focused = false;
-        let results_handle = self.results_editor.focus_handle(cx);
-        cx.focus(&results_handle);
     }
 
-    fn model_changed(&mut self, cx: &mut ViewContext<Self>) {
-        let match_ranges = self.model.read(cx).match_ranges.clone();
-        if match_ranges.is_empty() {
-            self.active_match_index = None;
-        } else {
-            self.active_match_index = Some(0);
-            self.update_match_index(cx);
-            let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
-            let is_new_search = self.search_id != prev_search_id;
-            self.results_editor.update(cx, |editor, cx| {
-                if is_new_search {
-                    let range_to_select = match_ranges
-                        .first()
-                        .map(|range| editor.range_for_match(range));
-                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                        s.select_ranges(range_to_select)
-                    });
-                    editor.scroll(Point::default(), Some(Axis::Vertical), cx);
-                }
-                editor.highlight_background::<Self>(
-                    &match_ranges,
-                    |theme| theme.search_match_background,
-                    cx,
-                );
-            });
-            if is_new_search && self.query_editor.focus_handle(cx).is_focused(cx) {
-                self.focus_results_editor(cx);
-            }
+    /// Adds the file of the active match to the excluded files, and searches again without it.
+    fn exclude_current_file(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let Some(path) = self
+            .active_match_index
+            .and_then(|index| model.match_ranges.get(index))
+            .and_then(|range| model.match_path(&range.start, cx))
+        else {
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        let excluded_files = self.excluded_files_editor.read(cx).text(cx);
+        if !excluded_files.split(',').any(|glob| glob.trim() == path) {
+            let excluded_files = excluded_files.trim().trim_end_matches(',');
+            let excluded_files = if excluded_files.is_empty() {
+                path
+            } else {
+                format!("{excluded_files}, {path}")
+            };
+            self.excluded_files_editor
+                .update(cx, |editor, cx| editor.set_text(excluded_files, cx));
         }
+        if !self.filters_enabled {
+            self.toggle_filters(cx);
+        }
+        self.search(cx);
+    }
 
-        cx.emit(ViewEvent::UpdateTab);
-        cx.notify();
+    /// Selects the file of the active match in the project panel, expanding its directories.
+    fn reveal_active_match_in_project_panel(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let Some(entry_id) = self
+            .active_match_index
+            .and_then(|index| model.match_ranges.get(index))
+            .and_then(|range| range.start.buffer_id)
+            .and_then(|buffer_id| model.excerpts.read(cx).buffer(buffer_id))
+            .and_then(|buffer| {
+                project::File::from_dyn(buffer.read(cx).file())?.project_entry_id(cx)
+            })
+        else {
+            return;
+        };
+        let project = model.project.clone();
+        project.update(cx, |_, cx| {
+            cx.emit(project::Event::RevealInProjectPanel(entry_id))
+        });
     }
 
-    fn update_match_index(&mut self, cx: &mut ViewContext<Self>) {
-        let results_editor = self.results_editor.read(cx);
-        let new_index = active_match_index(
-            &self.model.read(cx).match_ranges,
-            &results_editor.selections.newest_anchor().head(),
-            &results_editor.buffer().read(cx).snapshot(cx),
+    /// Swaps the included and excluded globs, along with their errors, and searches again.
+    fn swap_include_exclude(&mut self, cx: &mut ViewContext<Self>) {
+        let included_files = self.included_files_editor.read(cx).text(cx);
+        let excluded_files = self.excluded_files_editor.read(cx).text(cx);
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text(excluded_files, cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(included_files, cx));
+
+        let include_error = self.glob_errors.remove(&InputPanel::Include);
+        let exclude_error = self.glob_errors.remove(&InputPanel::Exclude);
+        self.glob_errors.extend(
+            [
+                (InputPanel::Include, exclude_error),
+                (InputPanel::Exclude, include_error),
+            ]
+            .into_iter()
+            .filter_map(|(panel, error)| Some((panel, error?))),
         );
-        if self.active_match_index != new_index {
-            self.active_match_index = new_index;
-            cx.notify();
+        let include_has_error = self.panels_with_errors.remove(&InputPanel::Include);
+        let exclude_has_error = self.panels_with_errors.remove(&InputPanel::Exclude);
+        if exclude_has_error {
+            self.panels_with_errors.insert(InputPanel::Include);
         }
+        if include_has_error {
+            self.panels_with_errors.insert(InputPanel::Exclude);
+        }
+
+        if !self.filters_enabled {
+            self.toggle_filters(cx);
+        }
+        self.search(cx);
+        cx.notify();
     }
 
-    pub fn has_matches(&self) -> bool {
-        self.active_match_index.is_some()
+    fn current_settings(&self) -> ProjectSearchSettings {
+        ProjectSearchSettings {
+            search_options: self.search_options,
+            filters_enabled: self.filters_enabled,
+        }
     }
 
-    fn landing_text_minor(&self) -> SharedString {
-        "Include/exclude specific paths with the filter option. Matching exact word and/or casing is available too.".into()
+    /// Stores the search inputs, so that the search can be re-run when the workspace is restored.
+    fn serialize(&self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        let item_id = cx.entity_id().as_u64() as ItemId;
+        let query = self.query_editor.read(cx).text(cx);
+        let files_to_include = self.included_files_editor.read(cx).text(cx);
+        let files_to_exclude = self.excluded_files_editor.read(cx).text(cx);
+        let options = self.search_options;
+        cx.background_executor()
+            .spawn(async move {
+                PROJECT_SEARCH_DB
+                    .save_query(
+                        item_id,
+                        workspace_id,
+                        query,
+                        options.contains(SearchOptions::REGEX),
+                        options.contains(SearchOptions::WHOLE_WORD),
+                        options.contains(SearchOptions::CASE_SENSITIVE),
+                        options.contains(SearchOptions::INCLUDE_IGNORED),
+                        files_to_include,
+                        files_to_exclude,
+                        options.regex_flags(),
+                    )
+                    .await
+                    .log_err()
+            })
+            .detach();
     }
 
-    fn border_color_for(&self, panel: InputPanel, cx: &WindowContext) -> Hsla {
-        if self.panels_with_errors.contains(&panel) {
-            Color::Error.color(cx)
-        } else {
-            cx.theme().colors().border
+    fn serialize_review_position(&mut self, cx: &mut ViewContext<Self>) {
+        // Until a restored search completes, the stored position is still the one to restore.
+        if self.pending_review_position.is_some() {
+            return;
         }
+        let (Some(workspace_id), Some(active_match_index)) =
+            (self.workspace_id, self.active_match_index)
+        else {
+            return;
+        };
+        let item_id = cx.entity_id().as_u64() as ItemId;
+        let scroll_position = self
+            .results_editor
+            .update(cx, |editor, cx| editor.scroll_position(cx));
+        // Scrolling emits an event per frame, so only write once the position stops changing.
+        self.scheduled_review_position_serialization = Some(cx.spawn(|_, cx| async move {
+            cx.background_executor()
+                .timer(REVIEW_POSITION_SERIALIZATION_DEBOUNCE)
+                .await;
+            PROJECT_SEARCH_DB
+                .save_review_position(
+                    item_id,
+                    workspace_id,
+                    active_match_index as u32,
+                    scroll_position.x,
+                    scroll_position.y,
+                )
+                .await
+                .log_err();
+        }));
     }
 
-    fn move_focus_to_results(&mut self, cx: &mut ViewContext<Self>) {
-        if !self.results_editor.focus_handle(cx).is_focused(cx)
-            && !self.model.read(cx).match_ranges.is_empty()
-        {
-            cx.stop_propagation();
-            return self.focus_results_editor(cx);
+    /// Fills in the inputs of a deserialized search and re-runs it, unless the query is no longer valid.
+    fn restore(
+        &mut self,
+        query: String,
+        search_options: SearchOptions,
+        files_to_include: String,
+        files_to_exclude: String,
+        review_position: Option<ReviewPosition>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.search_options = search_options;
+        self.filters_enabled = !files_to_include.is_empty() || !files_to_exclude.is_empty();
+        self.set_query(&query, cx);
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text(files_to_include, cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(files_to_exclude, cx));
+        if query.is_empty() {
+            return;
+        }
+        if let Some(query) = self.build_search_query(cx) {
+            self.pending_review_position = review_position;
+            self.search_with_query(query, cx);
         }
     }
-}
 
-impl ProjectSearchBar {
-    pub fn new() -> Self {
-        Self {
-            active_project_search: None,
-            subscription: None,
-        }
+    /// Folds the results of every file but the one containing the active match, which is
+    /// unfolded if needed so that the active match stays selected and visible.
+    fn collapse_all_groups(&mut self, cx: &mut ViewContext<Self>) {
+        let active_match_index = self.active_match_index;
+        let active_match = active_match_index
+            .and_then(|index| self.model.read(cx).match_ranges.get(index).cloned());
+        self.results_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut file_ranges: Vec<(BufferId, Range<Anchor>)> = Vec::new();
+            for (excerpt_id, buffer, range) in snapshot.excerpts() {
+                let (Some(start), Some(end)) = (
+                    snapshot.anchor_in_excerpt(excerpt_id, range.context.start),
+                    snapshot.anchor_in_excerpt(excerpt_id, range.context.end),
+                ) else {
+                    continue;
+                };
+                match file_ranges.last_mut() {
+                    Some((buffer_id, file_range)) if *buffer_id == buffer.remote_id() => {
+                        file_range.end = end;
+                    }
+                    _ => file_ranges.push((buffer.remote_id(), start..end)),
+                }
+            }
+            let active_buffer_id = active_match
+                .as_ref()
+                .and_then(|range| range.start.buffer_id);
+            let (active_file_ranges, file_ranges): (Vec<_>, Vec<_>) = file_ranges
+                .into_iter()
+                .partition(|(buffer_id, _)| Some(*buffer_id) == active_buffer_id);
+            editor.fold_ranges(file_ranges.into_iter().map(|(_, range)| range), false, cx);
+            editor.unfold_ranges(
+                active_file_ranges.into_iter().map(|(_, range)| range),
+                true,
+                false,
+                cx,
+            );
+            if let Some(active_match) = active_match {
+                let range_to_select = editor.range_for_match(&active_match);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges([range_to_select])
+                });
+            }
+        });
+        // Folding moves the selections, which must not move the active match along with them.
+        self.active_match_index = active_match_index;
+        self.truncate_long_lines(cx);
+        cx.notify();
     }
 
-    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                if !search_view
-                    .replacement_editor
-                    .focus_handle(cx)
-                    .is_focused(cx)
-                {
-                    cx.stop_propagation();
-                    search_view.search(cx);
-                }
-            });
+    fn expand_all_groups(&mut self, cx: &mut ViewContext<Self>) {
+        let active_match = self
+            .active_match_index
+            .and_then(|index| self.model.read(cx).match_ranges.get(index).cloned());
+        self.results_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor.unfold_ranges([0..snapshot.len()], true, false, cx);
+            if let Some(active_match) = active_match {
+                let range_to_select = editor.range_for_match(&active_match);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges([range_to_select])
+                });
+            }
+        });
+        // Expanding the results unfolds the truncated lines too, which stay truncated.
+        self.truncate_long_lines(cx);
+        cx.notify();
+    }
+
+    /// Returns the scopes captured when the search was deployed, in the order they are cycled through.
+    fn available_scopes(&self, cx: &AppContext) -> Vec<SearchScope> {
+        let mut scopes = vec![SearchScope::Project];
+        if !self.open_buffers.is_empty() {
+            scopes.push(SearchScope::OpenBuffers(self.open_buffers.clone()));
+        }
+        if let Some(current_buffer) = &self.current_buffer {
+            scopes.push(SearchScope::CurrentFile(current_buffer.clone()));
+        }
+        if let Some(visible_area) = &self.visible_area {
+            scopes.push(SearchScope::VisibleArea(visible_area.clone()));
+        }
+        if let Some(selection) = &self.selection {
+            scopes.push(SearchScope::Selection(selection.clone()));
+        }
+        if !self.result_buffers.is_empty() {
+            scopes.push(SearchScope::Results(self.result_buffers.clone()));
         }
+        if self.has_git_repositories(cx) {
+            scopes.push(SearchScope::ChangedFiles);
+        }
+        scopes
     }
 
-    fn tab(&mut self, _: &editor::actions::Tab, cx: &mut ViewContext<Self>) {
-        self.cycle_field(Direction::Next, cx);
+    fn cycle_scope(&mut self, cx: &mut ViewContext<Self>) {
+        let scopes = self.available_scopes(cx);
+        let next_index = scopes
+            .iter()
+            .position(|scope| scope.label() == self.scope.label())
+            .map_or(0, |index| (index + 1) % scopes.len());
+        self.scope = scopes[next_index].clone();
+        self.schedule_search(cx);
+        cx.notify();
     }
 
-    fn tab_previous(&mut self, _: &editor::actions::TabPrev, cx: &mut ViewContext<Self>) {
-        self.cycle_field(Direction::Prev, cx);
+    /// Restricts the next searches to the files holding the current matches, so that the query
+    /// typed next is looked for in them only.
+    fn search_within_results(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let excerpts = model.excerpts.read(cx);
+        let mut buffers = Vec::new();
+        for range in &model.match_ranges {
+            let Some(buffer) = range
+                .start
+                .buffer_id
+                .and_then(|buffer_id| excerpts.buffer(buffer_id))
+            else {
+                continue;
+            };
+            if !buffers.contains(&buffer) {
+                buffers.push(buffer);
+            }
+        }
+        if buffers.is_empty() {
+            return;
+        }
+        self.result_buffers = buffers.clone();
+        self.scope = SearchScope::Results(buffers);
+        self.focus_query_editor(cx);
+        cx.notify();
     }
 
-    fn focus_search(&mut self, cx: &mut ViewContext<Self>) {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                search_view.query_editor.focus_handle(cx).focus(cx);
-            });
+    /// Switches between searching the selection captured when the search was deployed and
+    /// searching the whole project.
+    fn toggle_search_in_selection(&mut self, cx: &mut ViewContext<Self>) {
+        self.scope = match (&self.scope, &self.selection) {
+            (SearchScope::Selection(_), _) | (_, None) => SearchScope::Project,
+            (_, Some(selection)) => SearchScope::Selection(selection.clone()),
+        };
+        self.search(cx);
+        cx.notify();
+    }
+
+    fn cycle_symbol_kind_filter(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_language_servers(cx) {
+            self.symbol_kind_filter = None;
+            return;
         }
+        let next_index = match self.symbol_kind_filter {
+            Some(kind) => SYMBOL_KIND_FILTERS
+                .iter()
+                .position(|(filter_kind, _)| *filter_kind == kind)
+                .map_or(0, |index| index + 1),
+            None => 0,
+        };
+        self.symbol_kind_filter = SYMBOL_KIND_FILTERS.get(next_index).map(|(kind, _)| *kind);
+        cx.notify();
     }
 
-    fn cycle_field(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
-        let active_project_search = match &self.active_project_search {
-            Some(active_project_search) => active_project_search,
+    fn cycle_symbol_scope(&mut self, cx: &mut ViewContext<Self>) {
+        self.symbol_scope = self.symbol_scope.next();
+        cx.notify();
+    }
 
-            None => {
-                return;
-            }
+    fn set_language_filter(&mut self, language: Option<SharedString>, cx: &mut ViewContext<Self>) {
+        self.language_filter = language;
+        cx.notify();
+    }
+
+    /// Globs matching the files of the language filter, for every path suffix of the language.
+    fn language_filter_globs(&self, cx: &AppContext) -> Vec<PathMatcher> {
+        let Some(language) = self.language_filter.as_ref() else {
+            return Vec::new();
         };
+        self.model
+            .read(cx)
+            .project
+            .read(cx)
+            .languages()
+            .path_suffixes_for_language(language)
+            .into_iter()
+            .flat_map(|suffix| [format!("**/*.{suffix}"), format!("**/{suffix}")])
+            .filter_map(|glob| PathMatcher::new(&glob).log_err())
+            .collect()
+    }
 
-        active_project_search.update(cx, |project_view, cx| {
-            let mut views = vec![&project_view.query_editor];
-            if project_view.replace_enabled {
-                views.push(&project_view.replacement_editor);
-            }
-            if project_view.filters_enabled {
-                views.extend([
-                    &project_view.included_files_editor,
-                    &project_view.excluded_files_editor,
-                ]);
-            }
-            let current_index = match views
-                .iter()
-                .enumerate()
-                .find(|(_, view)| view.focus_handle(cx).is_focused(cx))
-            {
-                Some((index, _)) => index,
-                None => return,
-            };
+    fn set_worktree_filter(&mut self, worktree_id: Option<WorktreeId>, cx: &mut ViewContext<Self>) {
+        self.worktree_filter = worktree_id;
+        cx.notify();
+    }
 
-            let new_index = match direction {
-                Direction::Next => (current_index + 1) % views.len(),
-                Direction::Prev if current_index == 0 => views.len() - 1,
-                Direction::Prev => (current_index - 1) % views.len(),
+    fn worktree_filter_label(&self, cx: &AppContext) -> SharedString {
+        self.worktree_filter
+            .and_then(|worktree_id| {
+                self.model
+                    .read(cx)
+                    .project
+                    .read(cx)
+                    .worktree_for_id(worktree_id, cx)
+            })
+            .map(|worktree| SharedString::from(worktree.read(cx).root_name().to_string()))
+            .unwrap_or_else(|| "All worktrees".into())
+    }
+
+    fn worktree_filter_menu(
+        search_view: WeakView<Self>,
+        cx: &mut WindowContext,
+    ) -> View<ContextMenu> {
+        let worktrees = search_view
+            .upgrade()
+            .map(|search_view| {
+                let project = search_view.read(cx).model.read(cx).project.read(cx);
+                project
+                    .visible_worktrees(cx)
+                    .map(|worktree| {
+                        let worktree = worktree.read(cx);
+                        (worktree.id(), worktree.root_name().to_string())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        ContextMenu::build(cx, move |menu, _| {
+            let all_worktrees = {
+                let search_view = search_view.clone();
+                menu.entry("All worktrees", None, move |cx| {
+                    search_view
+                        .update(cx, |search_view, cx| {
+                            search_view.set_worktree_filter(None, cx)
+                        })
+                        .ok();
+                })
             };
-            let next_focus_handle = views[new_index].focus_handle(cx);
-            cx.focus(&next_focus_handle);
-            cx.stop_propagation();
-        });
+            worktrees
+                .into_iter()
+                .fold(all_worktrees, |menu, (worktree_id, root_name)| {
+                    let search_view = search_view.clone();
+                    menu.entry(root_name, None, move |cx| {
+                        search_view
+                            .update(cx, |search_view, cx| {
+                                search_view.set_worktree_filter(Some(worktree_id), cx)
+                            })
+                            .ok();
+                    })
+                })
+        })
     }
 
-    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) -> bool {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                search_view.toggle_search_option(option, cx);
-                search_view.search(cx);
-            });
+    fn language_filter_menu(
+        search_view: WeakView<Self>,
+        cx: &mut WindowContext,
+    ) -> View<ContextMenu> {
+        let language_names = search_view
+            .upgrade()
+            .map(|search_view| {
+                search_view
+                    .read(cx)
+                    .model
+                    .read(cx)
+                    .project
+                    .read(cx)
+                    .languages()
+                    .language_names()
+            })
+            .unwrap_or_default();
+        ContextMenu::build(cx, move |menu, _| {
+            let any_language = {
+                let search_view = search_view.clone();
+                menu.entry("Any Language", None, move |cx| {
+                    search_view
+                        .update(cx, |search_view, cx| {
+                            search_view.set_language_filter(None, cx)
+                        })
+                        .ok();
+                })
+            };
+            language_names
+                .into_iter()
+                .fold(any_language, |menu, language_name| {
+                    let search_view = search_view.clone();
+                    menu.entry(language_name.clone(), None, move |cx| {
+                        let language_name = SharedString::from(language_name.clone());
+                        search_view
+                            .update(cx, |search_view, cx| {
+                                search_view.set_language_filter(Some(language_name), cx)
+                            })
+                            .ok();
+                    })
+                })
+        })
+    }
 
-            cx.notify();
-            true
-        } else {
-            false
+    fn symbol_kind_filter_label(&self) -> SharedString {
+        let kind = self.symbol_kind_filter.and_then(|kind| {
+            SYMBOL_KIND_FILTERS
+                .iter()
+                .find(|(filter_kind, _)| *filter_kind == kind)
+        });
+        match kind {
+            Some((_, label)) => format!("Files with: {label}").into(),
+            None => "Files with: any symbol".into(),
         }
     }
 
-    fn toggle_replace(&mut self, _: &ToggleReplace, cx: &mut ViewContext<Self>) {
-        if let Some(search) = &self.active_project_search {
-            search.update(cx, |this, cx| {
-                this.replace_enabled = !this.replace_enabled;
-                let editor_to_focus = if this.replace_enabled {
-                    this.replacement_editor.focus_handle(cx)
-                } else {
-                    this.query_editor.focus_handle(cx)
-                };
-                cx.focus(&editor_to_focus);
-                cx.notify();
-            });
-        }
+    fn toggle_semantic_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Text | SearchMode::Filename | SearchMode::Hex
+                if cx.has_global::<SemanticIndex>() =>
+            {
+                SearchMode::Semantic
+            }
+            _ => SearchMode::Text,
+        };
+        cx.notify();
     }
 
-    fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                search_view.toggle_filters(cx);
-                search_view
-                    .included_files_editor
-                    .update(cx, |_, cx| cx.notify());
-                search_view
-                    .excluded_files_editor
-                    .update(cx, |_, cx| cx.notify());
-                cx.refresh();
-                cx.notify();
-            });
-            cx.notify();
-            true
-        } else {
-            false
-        }
+    fn toggle_filename_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Filename => SearchMode::Text,
+            SearchMode::Text | SearchMode::Semantic | SearchMode::Hex => SearchMode::Filename,
+        };
+        cx.notify();
     }
 
-    fn move_focus_to_results(&self, cx: &mut ViewContext<Self>) {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                search_view.move_focus_to_results(cx);
-            });
-            cx.notify();
-        }
+    fn toggle_hex_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_mode = match self.search_mode {
+            SearchMode::Hex => SearchMode::Text,
+            SearchMode::Text | SearchMode::Semantic | SearchMode::Filename => SearchMode::Hex,
+        };
+        cx.notify();
     }
 
-    fn is_option_enabled(&self, option: SearchOptions, cx: &AppContext) -> bool {
-        if let Some(search) = self.active_project_search.as_ref() {
-            search.read(cx).search_options.contains(option)
-        } else {
-            false
-        }
+    fn toggle_treat_query_as_path(&mut self, cx: &mut ViewContext<Self>) {
+        self.treat_query_as_path = !self.treat_query_as_path;
+        cx.notify();
     }
 
-    fn next_history_query(&mut self, _: &NextHistoryQuery, cx: &mut ViewContext<Self>) {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.model.update(cx, |model, cx| {
-                    if let Some(new_query) = model.project.update(cx, |project, _| {
-                        project
-                            .search_history_mut()
-                            .next(&mut model.search_history_cursor)
-                            .map(str::to_string)
-                    }) {
-                        new_query
-                    } else {
-                        model.search_history_cursor.reset();
-                        String::new()
-                    }
-                });
-                search_view.set_query(&new_query, cx);
-            });
+    fn toggle_live_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.live_search = !self.live_search;
+        if !self.live_search {
+            self.scheduled_search.take();
         }
+        cx.notify();
     }
 
-    fn previous_history_query(&mut self, _: &PreviousHistoryQuery, cx: &mut ViewContext<Self>) {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                if search_view.query_editor.read(cx).text(cx).is_empty() {
-                    if let Some(new_query) = search_view
-                        .model
-                        .read(cx)
-                        .project
-                        .read(cx)
-                        .search_history()
-                        .current(&search_view.model.read(cx).search_history_cursor)
-                        .map(str::to_string)
+    /// Searches for the typed query once it stops changing, if searching live.
+    ///
+    /// Regexes are only searched for once they compile, so that the errors of the
+    /// partially typed ones do not replace the results of the last valid one.
+    fn schedule_live_search(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.live_search || self.treat_query_as_path {
+            return;
+        }
+        let debounce = match self.search_mode {
+            SearchMode::Text => LIVE_SEARCH_DEBOUNCE,
+            SearchMode::Semantic => {
+                // The results of a previous query are stale, so stop ranking the index for it
+                // rather than letting it run until the new query is searched for.
+                let text = self.query_editor.read(cx).text(cx);
+                self.model.update(cx, |model, cx| {
+                    if model.last_search_query_text.as_ref() != Some(&text)
+                        && model.cancel_pending_search()
                     {
-                        search_view.set_query(&new_query, cx);
-                        return;
+                        cx.notify();
                     }
+                });
+                SEMANTIC_SEARCH_DEBOUNCE
+            }
+            SearchMode::Filename | SearchMode::Hex => return,
+        };
+        self.scheduled_search = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(debounce).await;
+            this.update(&mut cx, |this, cx| {
+                let text = this.query_editor.read(cx).text(cx);
+                if text.is_empty() {
+                    return;
                 }
-
-                if let Some(new_query) = search_view.model.update(cx, |model, cx| {
-                    model.project.update(cx, |project, _| {
-                        project
-                            .search_history_mut()
-                            .previous(&mut model.search_history_cursor)
-                            .map(str::to_string)
-                    })
-                }) {
-                    search_view.set_query(&new_query, cx);
+                let options = this.search_options;
+                if this.search_mode == SearchMode::Text
+                    && options.contains(SearchOptions::REGEX)
+                    && SearchQuery::regex(
+                        options.regex_pattern(&text),
+                        options.contains(SearchOptions::WHOLE_WORD),
+                        options.contains(SearchOptions::CASE_SENSITIVE),
+                        false,
+                        Vec::new(),
+                        Vec::new(),
+                    )
+                    .is_err()
+                {
+                    return;
                 }
-            });
-        }
+                this.search(cx);
+            })
+            .ok();
+        }));
     }
 
-    fn select_next_match(&mut self, _: &SelectNextMatch, cx: &mut ViewContext<Self>) {
-        if let Some(search) = self.active_project_search.as_ref() {
-            search.update(cx, |this, cx| {
-                this.select_match(Direction::Next, cx);
-            })
-        }
+    fn has_filename_matches(&self, cx: &AppContext) -> bool {
+        let model = self.model.read(cx);
+        model.search_mode == SearchMode::Filename && !model.filename_matches.is_empty()
     }
 
-    fn select_prev_match(&mut self, _: &SelectPrevMatch, cx: &mut ViewContext<Self>) {
-        if let Some(search) = self.active_project_search.as_ref() {
-            search.update(cx, |this, cx| {
-                this.select_match(Direction::Prev, cx);
-            })
-        }
+    fn render_filename_matches(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let filename_matches = self.model.read(cx).filename_matches.clone();
+        v_flex()
+            .id("project-search-filename-matches")
+            .size_full()
+            .p_2()
+            .overflow_y_scroll()
+            .children(
+                filename_matches
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, (_, path_match))| {
+                        ListItem::new(ix)
+                            .inset(true)
+                            .selected(ix == self.selected_filename_match)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.selected_filename_match = ix;
+                                this.open_selected_filename_match(cx);
+                            }))
+                            .child(HighlightedLabel::new(
+                                path_match.string,
+                                path_match.positions,
+                            ))
+                    }),
+            )
     }
 
-    fn render_text_input(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
-        let settings = ThemeSettings::get_global(cx);
-        let text_style = TextStyle {
-            color: if editor.read(cx).read_only(cx) {
-                cx.theme().colors().text_disabled
-            } else {
-                cx.theme().colors().text
+    fn has_hex_matches(&self, cx: &AppContext) -> bool {
+        let model = self.model.read(cx);
+        model.search_mode == SearchMode::Hex && !model.hex_matches.is_empty()
+    }
+
+    fn render_hex_matches(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let match_count = self.model.read(cx).hex_matches.len();
+        uniform_list(
+            cx.view().clone(),
+            "project-search-hex-matches",
+            match_count,
+            |this, range, cx| {
+                let hex_matches = &this.model.read(cx).hex_matches;
+                range
+                    .filter_map(|ix| {
+                        let hex_match = hex_matches.get(ix)?;
+                        let (excerpt, positions) = hex_excerpt(hex_match);
+                        Some(
+                            ListItem::new(ix).inset(true).child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Label::new(format!(
+                                            "{}:{:#010x}",
+                                            hex_match.path.path.display(),
+                                            hex_match.offset
+                                        ))
+                                        .color(Color::Muted),
+                                    )
+                                    .child(HighlightedLabel::new(excerpt, positions)),
+                            ),
+                        )
+                    })
+                    .collect()
             },
-            font_family: settings.buffer_font.family.clone(),
-            font_features: settings.buffer_font.features,
-            font_size: rems(0.875).into(),
-            font_weight: FontWeight::NORMAL,
-            font_style: FontStyle::Normal,
-            line_height: relative(1.3),
-            background_color: None,
-            underline: None,
-            strikethrough: None,
-            white_space: WhiteSpace::Normal,
-        };
+        )
+        .size_full()
+        .p_2()
+    }
 
-        EditorElement::new(
-            &editor,
-            EditorStyle {
-                background: cx.theme().colors().editor_background,
-                local_player: cx.theme().players().local(),
-                text: text_style,
-                ..Default::default()
+    fn render_results_list(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let rows = self.results_list_rows(cx);
+        let indent_level = usize::from(self.group_by_directory);
+        uniform_list(
+            cx.view().clone(),
+            "project-search-results-list",
+            rows.len(),
+            move |this, range, cx| {
+                range
+                    .filter_map(|row_ix| {
+                        let ix = match &rows[row_ix] {
+                            ResultsListRow::Directory {
+                                path,
+                                match_count,
+                                collapsed,
+                            } => {
+                                let path = path.clone();
+                                let label = if path.as_os_str().is_empty() {
+                                    ".".to_string()
+                                } else {
+                                    path.display().to_string()
+                                };
+                                return Some(
+                                    ListItem::new(row_ix)
+                                        .inset(true)
+                                        .toggle(!collapsed)
+                                        .on_click(cx.listener(move |this, _, cx| {
+                                            this.toggle_directory_collapsed(path.clone(), cx);
+                                        }))
+                                        .child(Label::new(label))
+                                        .end_slot(
+                                            Label::new(match_count.to_string()).color(Color::Muted),
+                                        ),
+                                );
+                            }
+                            ResultsListRow::Match(ix) => *ix,
+                        };
+                        let (location, line_text) = this.results_list_row(ix, cx)?;
+                        Some(
+                            ListItem::new(row_ix)
+                                .inset(true)
+                                .indent_level(indent_level)
+                                .selected(this.active_match_index == Some(ix))
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.open_match(ix, cx);
+                                }))
+                                .child(
+                                    h_flex()
+                                        .gap_2()
+                                        .child(Label::new(location).color(Color::Muted))
+                                        .child(
+                                            Label::new(line_text)
+                                                .when(this.is_match_excluded(ix, cx), |label| {
+                                                    label.color(Color::Disabled)
+                                                }),
+                                        ),
+                                ),
+                        )
+                    })
+                    .collect()
             },
         )
+        .size_full()
+        .p_2()
+        .track_scroll(self.results_list_scroll_handle.clone())
     }
-}
 
-impl Render for ProjectSearchBar {
-    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let Some(search) = self.active_project_search.clone() else {
-            return div();
+    /// Lists the files of the results with their match count, in the order of the results.
+    fn minimap_entries(&self, cx: &AppContext) -> Vec<MinimapEntry> {
+        let model = self.model.read(cx);
+        let mut entries = Vec::<MinimapEntry>::new();
+        for (ix, range) in model.match_ranges.iter().enumerate() {
+            if let Some(entry) = entries.last_mut() {
+                if model.match_ranges[entry.first_match_index].start.buffer_id
+                    == range.start.buffer_id
+                {
+                    entry.match_count += 1;
+                    continue;
+                }
+            }
+            entries.push(MinimapEntry {
+                path: model.match_path(&range.start, cx).unwrap_or_default(),
+                match_count: 1,
+                first_match_index: ix,
+            });
+        }
+        entries
+    }
+
+    /// Returns the minimap entry of the file at the top of the results editor.
+    fn viewed_minimap_entry(
+        &self,
+        entries: &[MinimapEntry],
+        cx: &mut ViewContext<Self>,
+    ) -> Option<usize> {
+        let buffer_id = self.results_editor.update(cx, |editor, cx| {
+            let top_row = editor.scroll_position(cx).y.max(0.) as u32;
+            let snapshot = editor.snapshot(cx);
+            let point = DisplayPoint::new(top_row, 0).to_point(&snapshot);
+            snapshot.buffer_snapshot.anchor_after(point).buffer_id
+        })?;
+        let match_ranges = &self.model.read(cx).match_ranges;
+        entries.iter().position(|entry| {
+            match_ranges[entry.first_match_index].start.buffer_id == Some(buffer_id)
+        })
+    }
+
+    /// Selects the first match of a file listed in the minimap, scrolling it to the top of the
+    /// results editor.
+    fn select_file_matches(&mut self, first_match_index: usize, cx: &mut ViewContext<Self>) {
+        let Some(range) = self
+            .model
+            .read(cx)
+            .match_ranges
+            .get(first_match_index)
+            .cloned()
+        else {
+            return;
         };
-        let search = search.read(cx);
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(&range);
+            editor.unfold_ranges([range_to_select.clone()], false, true, cx);
+            editor.change_selections(Some(Autoscroll::top_relative(0)), cx, |s| {
+                s.select_ranges([range_to_select])
+            });
+        });
+        self.focus_results_editor(cx);
+    }
 
-        let query_column = h_flex()
-            .flex_1()
-            .h_8()
-            .mr_2()
-            .px_2()
-            .py_1()
-            .border_1()
-            .border_color(search.border_color_for(InputPanel::Query, cx))
-            .rounded_lg()
-            .min_w(rems(MIN_INPUT_WIDTH_REMS))
-            .max_w(rems(MAX_INPUT_WIDTH_REMS))
-            .on_action(cx.listener(|this, action, cx| this.confirm(action, cx)))
-            .on_action(cx.listener(|this, action, cx| this.previous_history_query(action, cx)))
-            .on_action(cx.listener(|this, action, cx| this.next_history_query(action, cx)))
-            .child(self.render_text_input(&search.query_editor, cx))
-            .child(
-                h_flex()
-                    .child(SearchOptions::CASE_SENSITIVE.as_button(
-                        self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
-                        }),
-                    ))
-                    .child(SearchOptions::WHOLE_WORD.as_button(
-                        self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
-                        }),
-                    ))
-                    .child(SearchOptions::REGEX.as_button(
-                        self.is_option_enabled(SearchOptions::REGEX, cx),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::REGEX, cx);
-                        }),
-                    )),
-            );
+    fn render_minimap(
+        &self,
+        entries: Vec<MinimapEntry>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let viewed_entry = self.viewed_minimap_entry(&entries, cx);
+        uniform_list(
+            cx.view().clone(),
+            "project-search-minimap",
+            entries.len(),
+            move |_, range, cx| {
+                range
+                    .map(|ix| {
+                        let entry = &entries[ix];
+                        let first_match_index = entry.first_match_index;
+                        let path = SharedString::from(entry.path.display().to_string());
+                        let file_name = entry
+                            .path
+                            .file_name()
+                            .map(|file_name| file_name.to_string_lossy().to_string())
+                            .unwrap_or_else(|| path.to_string());
+                        ListItem::new(ix)
+                            .inset(true)
+                            .selected(viewed_entry == Some(ix))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.select_file_matches(first_match_index, cx);
+                            }))
+                            .tooltip(move |cx| Tooltip::text(path.clone(), cx))
+                            .child(Label::new(file_name).size(LabelSize::Small))
+                            .end_slot(
+                                Label::new(entry.match_count.to_string())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                    })
+                    .collect()
+            },
+        )
+        .h_full()
+        .w(rems(12.))
+        .p_1()
+        .border_l_1()
+        .border_color(cx.theme().colors().border)
+        .bg(cx.theme().colors().editor_background)
+    }
 
-        let mode_column = v_flex().items_start().justify_start().child(
-            h_flex()
-                .child(
-                    IconButton::new("project-search-filter-button", IconName::Filter)
-                        .tooltip(|cx| Tooltip::for_action("Toggle filters", &ToggleFilters, cx))
-                        .on_click(cx.listener(|this, _, cx| {
-                            this.toggle_filters(cx);
-                        }))
-                        .selected(
-                            self.active_project_search
-                                .as_ref()
-                                .map(|search| search.read(cx).filters_enabled)
-                                .unwrap_or_default(),
-                        ),
-                )
-                .child(
-                    IconButton::new("project-search-toggle-replace", IconName::Replace)
-                        .on_click(cx.listener(|this, _, cx| {
-                            this.toggle_replace(&ToggleReplace, cx);
-                        }))
-                        .selected(
-                            self.active_project_search
-                                .as_ref()
-                                .map(|search| search.read(cx).replace_enabled)
-                                .unwrap_or_default(),
-                        )
-                        .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
-                ),
-        );
+    /// Lists the matches, each under the header of the directory of its file when grouping them
+    /// by directory. Directories come in the order of their first match, so that sorting the
+    /// results sorts the directories too.
+    fn results_list_rows(&self, cx: &AppContext) -> Vec<ResultsListRow> {
+        let model = self.model.read(cx);
+        if !self.group_by_directory {
+            return (0..model.match_ranges.len())
+                .map(ResultsListRow::Match)
+                .collect();
+        }
+        let mut directories: Vec<(PathBuf, Vec<usize>)> = Vec::new();
+        let mut directory_indices = HashMap::default();
+        for (ix, range) in model.match_ranges.iter().enumerate() {
+            let directory = model
+                .match_path(&range.start, cx)
+                .and_then(|path| path.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            let directory_ix = *directory_indices
+                .entry(directory.clone())
+                .or_insert_with(|| {
+                    directories.push((directory, Vec::new()));
+                    directories.len() - 1
+                });
+            directories[directory_ix].1.push(ix);
+        }
+        let mut rows = Vec::new();
+        for (path, match_indices) in directories {
+            let collapsed = self.collapsed_directories.contains(&path);
+            rows.push(ResultsListRow::Directory {
+                path,
+                match_count: match_indices.len(),
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(match_indices.into_iter().map(ResultsListRow::Match));
+            }
+        }
+        rows
+    }
 
-        let match_text = search
+    /// Returns the row of the results list showing the match at `ix`, unless its directory is
+    /// collapsed.
+    fn results_list_row_index(&self, ix: usize, cx: &AppContext) -> Option<usize> {
+        if !self.group_by_directory {
+            return Some(ix);
+        }
+        self.results_list_rows(cx)
+            .iter()
+            .position(|row| *row == ResultsListRow::Match(ix))
+    }
+
+    fn toggle_group_by_directory(&mut self, cx: &mut ViewContext<Self>) {
+        self.group_by_directory = !self.group_by_directory;
+        if self.group_by_directory && !self.results_as_list {
+            self.toggle_results_list_view(cx);
+        }
+        if let Some(row_ix) = self
             .active_match_index
-            .and_then(|index| {
-                let index = index + 1;
-                let match_quantity = search.model.read(cx).match_ranges.len();
-                if match_quantity > 0 {
-                    debug_assert!(match_quantity >= index);
-                    Some(format!("{index}/{match_quantity}").to_string())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| "0/0".to_string());
+            .and_then(|index| self.results_list_row_index(index, cx))
+        {
+            self.results_list_scroll_handle.scroll_to_item(row_ix);
+        }
+        cx.notify();
+    }
 
-        let limit_reached = search.model.read(cx).limit_reached;
+    fn toggle_directory_collapsed(&mut self, path: PathBuf, cx: &mut ViewContext<Self>) {
+        if !self.collapsed_directories.remove(&path) {
+            self.collapsed_directories.insert(path);
+        }
+        cx.notify();
+    }
 
-        let matches_column = h_flex()
-            .child(
-                IconButton::new("project-search-prev-match", IconName::ChevronLeft)
-                    .disabled(search.active_match_index.is_none())
-                    .on_click(cx.listener(|this, _, cx| {
-                        if let Some(search) = this.active_project_search.as_ref() {
-                            search.update(cx, |this, cx| {
-                                this.select_match(Direction::Prev, cx);
-                            })
-                        }
-                    }))
-                    .tooltip(|cx| {
-                        Tooltip::for_action("Go to previous match", &SelectPrevMatch, cx)
-                    }),
-            )
-            .child(
-                IconButton::new("project-search-next-match", IconName::ChevronRight)
-                    .disabled(search.active_match_index.is_none())
-                    .on_click(cx.listener(|this, _, cx| {
-                        if let Some(search) = this.active_project_search.as_ref() {
-                            search.update(cx, |this, cx| {
-                                this.select_match(Direction::Next, cx);
-                            })
-                        }
-                    }))
-                    .tooltip(|cx| Tooltip::for_action("Go to next match", &SelectNextMatch, cx)),
-            )
+    /// Returns the `path:line` of the match at `ix` and the text of its line, truncated.
+    fn results_list_row(&self, ix: usize, cx: &AppContext) -> Option<(String, String)> {
+        let model = self.model.read(cx);
+        let (path, start, line_text) = model.match_line(model.match_ranges.get(ix)?, cx)?;
+        Some((
+            format!("{}:{}", path.display(), start.row + 1),
+            truncate_and_trailoff(line_text.trim(), MAX_RESULTS_LIST_LINE_LEN),
+        ))
+    }
+
+    fn render_replace_preview(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let match_ranges = &self.model.read(cx).match_ranges;
+        let match_count = match_ranges.len();
+        let replacement_count = match_ranges
+            .iter()
+            .filter(|range| !self.excluded_matches.contains(&match_buffer_range(range)))
+            .count();
+        let noun = if replacement_count == 1 {
+            "replacement"
+        } else {
+            "replacements"
+        };
+        v_flex()
+            .size_full()
             .child(
                 h_flex()
-                    .min_w(rems_from_px(40.))
+                    .p_2()
+                    .gap_2()
+                    .child(Label::new(format!("{replacement_count} {noun}")))
                     .child(
-                        Label::new(match_text).color(if search.active_match_index.is_some() {
-                            Color::Default
-                        } else {
-                            Color::Disabled
-                        }),
+                        Button::new("project-search-confirm-replace-all", "Replace All").on_click(
+                            cx.listener(|this, _, cx| {
+                                this.confirm_replace_preview(cx);
+                            }),
+                        ),
+                    )
+                    .child(
+                        Button::new("project-search-cancel-replace-all", "Cancel").on_click(
+                            cx.listener(|this, _, cx| {
+                                this.toggle_replace_preview(cx);
+                            }),
+                        ),
                     ),
             )
-            .when(limit_reached, |this| {
-                this.child(
-                    div()
-                        .child(Label::new("Search limit reached").color(Color::Warning))
-                        .ml_2(),
+            .child(
+                uniform_list(
+                    cx.view().clone(),
+                    "project-search-replace-preview",
+                    match_count,
+                    |this, range, cx| {
+                        let Some(query) = this.replace_preview_query(cx) else {
+                            return Vec::new();
+                        };
+                        range
+                            .filter_map(|ix| {
+                                let (location, before, after) =
+                                    this.replace_preview_row(ix, &query, cx)?;
+                                let excluded = this.is_match_excluded(ix, cx);
+                                Some(
+                                    ListItem::new(ix)
+                                        .inset(true)
+                                        .on_click(cx.listener(move |this, _, cx| {
+                                            this.open_match(ix, cx);
+                                        }))
+                                        .child(
+                                            h_flex()
+                                                .gap_2()
+                                                .child(Label::new(location).color(Color::Muted))
+                                                .child(Label::new(before).color(if excluded {
+                                                    Color::Disabled
+                                                } else {
+                                                    Color::Deleted
+                                                }))
+                                                .when(!excluded, |this| {
+                                                    this.child(Label::new("→").color(Color::Muted))
+                                                        .child(
+                                                            Label::new(after).color(Color::Created),
+                                                        )
+                                                }),
+                                        ),
+                                )
+                            })
+                            .collect()
+                    },
                 )
-            });
+                .size_full()
+                .p_2()
+                .track_scroll(self.replace_preview_scroll_handle.clone()),
+            )
+    }
 
-        let search_line = h_flex()
-            .flex_1()
-            .child(query_column)
-            .child(mode_column)
-            .child(matches_column);
+    /// The active query, replacing its matches with the text of the replacement editor.
+    fn replace_preview_query(&self, cx: &AppContext) -> Option<SearchQuery> {
+        let query = self.model.read(cx).active_query.clone()?;
+        Some(query.with_replacement(self.replacement(cx)))
+    }
 
-        let replace_line = search.replace_enabled.then(|| {
-            let replace_column = h_flex()
-                .flex_1()
-                .min_w(rems(MIN_INPUT_WIDTH_REMS))
-                .max_w(rems(MAX_INPUT_WIDTH_REMS))
-                .h_8()
-                .px_2()
-                .py_1()
-                .border_1()
-                .border_color(cx.theme().colors().border)
-                .rounded_lg()
-                .child(self.render_text_input(&search.replacement_editor, cx));
-            let replace_actions = h_flex().when(search.replace_enabled, |this| {
-                this.child(
-                    IconButton::new("project-search-replace-next", IconName::ReplaceNext)
-                        .on_click(cx.listener(|this, _, cx| {
-                            if let Some(search) = this.active_project_search.as_ref() {
-                                search.update(cx, |this, cx| {
-                                    this.replace_next(&ReplaceNext, cx);
-                                })
-                            }
-                        }))
-                        .tooltip(|cx| Tooltip::for_action("Replace next match", &ReplaceNext, cx)),
-                )
-                .child(
-                    IconButton::new("project-search-replace-all", IconName::ReplaceAll)
-                        .on_click(cx.listener(|this, _, cx| {
-                            if let Some(search) = this.active_project_search.as_ref() {
-                                search.update(cx, |this, cx| {
-                                    this.replace_all(&ReplaceAll, cx);
-                                })
-                            }
-                        }))
-                        .tooltip(|cx| Tooltip::for_action("Replace all matches", &ReplaceAll, cx)),
-                )
+    /// Returns the `path:line` of the match at `ix`, and the text of its line before
+    /// and after replacing the match, truncated.
+    fn replace_preview_row(
+        &self,
+        ix: usize,
+        query: &SearchQuery,
+        cx: &AppContext,
+    ) -> Option<(String, String, String)> {
+        let model = self.model.read(cx);
+        let range = model.match_ranges.get(ix)?;
+        let (path, start, line_text) = model.match_line(range, cx)?;
+        let (matched_text, replacement) = self.replacement_for_match(range, query, cx)?;
+        let match_start = start.column as usize;
+        let before_match = line_text.get(..match_start).unwrap_or_default();
+        // Matches spanning several lines replace the rest of their first line.
+        let after_match = line_text
+            .get(match_start + matched_text.len()..)
+            .unwrap_or_default();
+        let replaced_line = format!("{before_match}{replacement}{after_match}");
+        Some((
+            format!("{}:{}", path.display(), start.row + 1),
+            truncate_and_trailoff(line_text.trim(), MAX_RESULTS_LIST_LINE_LEN),
+            truncate_and_trailoff(replaced_line.trim(), MAX_RESULTS_LIST_LINE_LEN),
+        ))
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.replace_preview && !self.can_replace(cx) {
+            return;
+        }
+        self.replace_preview = !self.replace_preview;
+        if self.replace_preview {
+            if self.results_editor.focus_handle(cx).is_focused(cx) {
+                cx.focus(&self.focus_handle);
+            }
+        } else if self.focus_handle.is_focused(cx) && self.has_matches() {
+            self.focus_results_editor(cx);
+        }
+        cx.notify();
+    }
+
+    fn confirm_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.replace_all(&ReplaceAll, cx);
+        self.toggle_replace_preview(cx);
+    }
+
+    /// Opens the file of the match at `ix` in its own editor, with the match selected.
+    fn open_match(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let Some(match_range) = model.match_ranges.get(ix) else {
+            return;
+        };
+        let Some(buffer) = match_range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| model.excerpts.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        let match_range = match_range.clone();
+        let range = match_range.start.text_anchor..match_range.end.text_anchor;
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return;
+        };
+        self.select_match_range(&match_range, cx);
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([range])
+                    });
+                });
             });
-            h_flex()
-                .gap_2()
-                .child(replace_column)
-                .child(replace_actions)
         });
+    }
 
-        let filter_line = search.filters_enabled.then(|| {
-            h_flex()
-                .w_full()
-                .gap_2()
-                .child(
-                    h_flex()
-                        .flex_1()
-                        .min_w(rems(MIN_INPUT_WIDTH_REMS))
-                        .max_w(rems(MAX_INPUT_WIDTH_REMS))
-                        .h_8()
-                        .px_2()
-                        .py_1()
-                        .border_1()
-                        .border_color(search.border_color_for(InputPanel::Include, cx))
-                        .rounded_lg()
-                        .child(self.render_text_input(&search.included_files_editor, cx))
-                        .child(
-                            SearchOptions::INCLUDE_IGNORED.as_button(
-                                search
-                                    .search_options
-                                    .contains(SearchOptions::INCLUDE_IGNORED),
-                                cx.listener(|this, _, cx| {
-                                    this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
-                                }),
-                            ),
-                        ),
-                )
-                .child(
-                    h_flex()
-                        .flex_1()
-                        .min_w(rems(MIN_INPUT_WIDTH_REMS))
-                        .max_w(rems(MAX_INPUT_WIDTH_REMS))
-                        .h_8()
-                        .px_2()
-                        .py_1()
-                        .border_1()
-                        .border_color(search.border_color_for(InputPanel::Exclude, cx))
-                        .rounded_lg()
-                        .child(self.render_text_input(&search.excluded_files_editor, cx)),
-                )
+    /// Opens an editor on the results in a pane split to the right, which keeps showing the
+    /// matches of this search as it is rerun.
+    fn open_results_in_split(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return;
+        };
+        let model = self.model.read(cx);
+        let excerpts = model.excerpts.clone();
+        let project = model.project.clone();
+        let active_match = self
+            .active_match_index
+            .and_then(|index| model.match_ranges.get(index))
+            .cloned();
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(excerpts, Some(project), cx);
+                    if let Some(active_match) = active_match {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_ranges([active_match])
+                        });
+                    }
+                    editor
+                });
+                workspace.split_item(SplitDirection::Right, Box::new(editor), cx);
+            });
         });
+    }
 
-        v_flex()
-            .key_context("ProjectSearchBar")
-            .on_action(cx.listener(|this, _: &ToggleFocus, cx| this.move_focus_to_results(cx)))
-            .on_action(cx.listener(|this, _: &ToggleFilters, cx| {
-                this.toggle_filters(cx);
-            }))
-            .capture_action(cx.listener(|this, action, cx| {
-                this.tab(action, cx);
-                cx.stop_propagation();
-            }))
-            .capture_action(cx.listener(|this, action, cx| {
-                this.tab_previous(action, cx);
-                cx.stop_propagation();
-            }))
-            .on_action(cx.listener(|this, action, cx| this.confirm(action, cx)))
-            .on_action(cx.listener(|this, action, cx| {
-                this.toggle_replace(action, cx);
-            }))
-            .on_action(cx.listener(|this, _: &ToggleWholeWord, cx| {
-                this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
-            }))
-            .on_action(cx.listener(|this, _: &ToggleCaseSensitive, cx| {
-                this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
-            }))
-            .on_action(cx.listener(|this, action, cx| {
-                if let Some(search) = this.active_project_search.as_ref() {
-                    search.update(cx, |this, cx| {
-                        this.replace_next(action, cx);
-                    })
-                }
-            }))
-            .on_action(cx.listener(|this, action, cx| {
-                if let Some(search) = this.active_project_search.as_ref() {
-                    search.update(cx, |this, cx| {
-                        this.replace_all(action, cx);
-                    })
-                }
-            }))
-            .when(search.filters_enabled, |this| {
-                this.on_action(cx.listener(|this, _: &ToggleIncludeIgnored, cx| {
-                    this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
-                }))
-            })
-            .on_action(cx.listener(Self::select_next_match))
-            .on_action(cx.listener(Self::select_prev_match))
-            .gap_2()
-            .w_full()
-            .child(search_line)
-            .children(replace_line)
-            .children(filter_line)
+    fn toggle_results_list_view(&mut self, cx: &mut ViewContext<Self>) {
+        self.results_as_list = !self.results_as_list;
+        if self.results_as_list {
+            if let Some(row_ix) = self
+                .active_match_index
+                .and_then(|index| self.results_list_row_index(index, cx))
+            {
+                self.results_list_scroll_handle.scroll_to_item(row_ix);
+            }
+            if self.results_editor.focus_handle(cx).is_focused(cx) {
+                cx.focus(&self.focus_handle);
+            }
+        } else if self.focus_handle.is_focused(cx) && self.has_matches() {
+            self.focus_results_editor(cx);
+        }
+        cx.notify();
     }
-}
 
-impl EventEmitter<ToolbarItemEvent> for ProjectSearchBar {}
+    /// Opens the selected file of a filename search, returning `false` if there is none.
+    fn open_selected_filename_match(&mut self, cx: &mut ViewContext<Self>) -> bool {
+        if !self.has_filename_matches(cx) {
+            return false;
+        }
+        let Some((project_path, _)) = self
+            .model
+            .read(cx)
+            .filename_matches
+            .get(self.selected_filename_match)
+            .cloned()
+        else {
+            return false;
+        };
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return false;
+        };
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .open_path(project_path, None, true, cx)
+                    .detach_and_log_err(cx);
+            });
+        });
+        true
+    }
 
-impl ToolbarItemView for ProjectSearchBar {
-    fn set_active_pane_item(
+    fn toggle_include_dependencies(&mut self, cx: &mut ViewContext<Self>) {
+        self.include_dependencies =
+            !self.include_dependencies && self.model.read(cx).project.read(cx).is_local();
+        cx.notify();
+    }
+
+    fn toggle_default_excludes(&mut self, cx: &mut ViewContext<Self>) {
+        self.default_excludes_enabled = !self.default_excludes_enabled;
+        cx.notify();
+    }
+
+    fn new_query_editor(
+        text: &str,
+        multiline: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> (View<Editor>, Subscription) {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = if multiline {
+                Editor::auto_height(MAX_MULTILINE_QUERY_ROWS, cx)
+            } else {
+                Editor::single_line(cx)
+            };
+            editor.set_placeholder_text("Search all files..", cx);
+            editor.set_text(text, cx);
+            editor
+        });
+        // Subscribe to query_editor in order to reraise editor events for workspace item activation purposes
+        let subscription = cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+            if matches!(event, EditorEvent::Edited) {
+                this.schedule_regex_preview(cx);
+                this.schedule_live_search(cx);
+            }
+            cx.emit(ViewEvent::EditorEvent(event.clone()))
+        });
+        (query_editor, subscription)
+    }
+
+    /// Swaps the query editor for one that can hold newlines, or back to a single line one.
+    fn toggle_multiline(&mut self, cx: &mut ViewContext<Self>) {
+        self.multiline_query = !self.multiline_query;
+        let text = self.query_editor.read(cx).text(cx);
+        let (query_editor, subscription) = Self::new_query_editor(&text, self.multiline_query, cx);
+        self.query_editor = query_editor;
+        self._query_editor_subscription = subscription;
+        self.focus_query_editor(cx);
+        cx.notify();
+    }
+
+    fn toggle_sort_order(&mut self, cx: &mut ViewContext<Self>) {
+        self.sort_order = match self.sort_order {
+            SortOrder::Path => SortOrder::Relevance,
+            SortOrder::Relevance => SortOrder::Path,
+        };
+        let sort_order = self.sort_order;
+        self.model
+            .update(cx, |model, cx| model.set_sort_order(sort_order, cx));
+        cx.notify();
+    }
+
+    fn change_context_lines(
         &mut self,
-        active_pane_item: Option<&dyn ItemHandle>,
+        before_delta: i32,
+        after_delta: i32,
         cx: &mut ViewContext<Self>,
-    ) -> ToolbarItemLocation {
+    ) {
+        self.context_before = self
+            .context_before
+            .saturating_add_signed(before_delta)
+            .min(MAX_CONTEXT_LINES);
+        self.context_after = self
+            .context_after
+            .saturating_add_signed(after_delta)
+            .min(MAX_CONTEXT_LINES);
+        let (context_before, context_after) = (self.context_before, self.context_after);
+        self.model.update(cx, |model, cx| {
+            model.set_context_lines(context_before, context_after, cx)
+        });
         cx.notify();
-        self.subscription = None;
-        self.active_project_search = None;
-        if let Some(search) = active_pane_item.and_then(|i| i.downcast::<ProjectSearchView>()) {
-            self.subscription = Some(cx.observe(&search, |_, _, cx| cx.notify()));
-            self.active_project_search = Some(search);
-            ToolbarItemLocation::PrimaryLeft {}
-        } else {
-            ToolbarItemLocation::Hidden
-        }
     }
 
-    fn row_count(&self, cx: &WindowContext<'_>) -> usize {
-        if let Some(search) = self.active_project_search.as_ref() {
-            if search.read(cx).filters_enabled {
-                return 2;
+    /// Returns the distinct queries searched for in the project, most recent first.
+    fn recent_queries(&self, cx: &AppContext) -> Vec<String> {
+        let mut queries = Vec::new();
+        let project = self.model.read(cx).project.read(cx);
+        for query in project.search_history().queries().rev() {
+            if queries.len() == MAX_RECENT_QUERIES {
+                break;
+            }
+            if !queries.iter().any(|recent_query| recent_query == query) {
+                queries.push(query.to_string());
             }
         }
-        1
+        queries
     }
-}
 
-fn register_workspace_action<A: Action>(
-    workspace: &mut Workspace,
-    callback: fn(&mut ProjectSearchBar, &A, &mut ViewContext<ProjectSearchBar>),
-) {
-    workspace.register_action(move |workspace, action: &A, cx| {
-        if workspace.has_active_modal(cx) {
-            cx.propagate();
+    /// Searches for the most recent query of the history other than the current one, so that
+    /// repeated swaps go back and forth between the last two queries.
+    fn swap_with_previous_query(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let Some(previous_query) = self
+            .recent_queries(cx)
+            .into_iter()
+            .find(|recent_query| *recent_query != query)
+        else {
             return;
-        }
+        };
+        self.set_query(&previous_query, cx);
+        self.search(cx);
+    }
 
-        workspace.active_pane().update(cx, |pane, cx| {
-            pane.toolbar().update(cx, move |workspace, cx| {
-                if let Some(search_bar) = workspace.item_of_type::<ProjectSearchBar>() {
-                    search_bar.update(cx, move |search_bar, cx| {
-                        if search_bar.active_project_search.is_some() {
-                            callback(search_bar, action, cx);
-                            cx.notify();
-                        } else {
-                            cx.propagate();
-                        }
-                    });
-                }
-            });
+    /// Restores the default of every option narrowing down or rewriting the matches, and reruns
+    /// the search. The case, whole word and regex options go back to the ones configured in the
+    /// `search` settings, which aren't necessarily a plain literal search. The search mode,
+    /// context lines and sort order only change how the results are shown, so they're kept.
+    fn reset_search_options(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_options = SearchSettings::get_global(cx).default_search_options();
+        self.invert_match = false;
+        self.multi_pattern = false;
+        self.scope = SearchScope::default();
+        self.symbol_scope = SymbolScope::default();
+        self.symbol_kind_filter = None;
+        self.include_dependencies = false;
+        self.case_preserving_replace = false;
+        self.remember_settings(cx);
+        self.schedule_regex_preview(cx);
+        self.search(cx);
+    }
+
+    fn recent_queries_menu(
+        search_view: WeakView<Self>,
+        queries: Vec<String>,
+        cx: &mut WindowContext,
+    ) -> View<ContextMenu> {
+        ContextMenu::build(cx, move |menu, _| {
+            queries
+                .into_iter()
+                .fold(menu.header("Recent Searches"), |menu, query| {
+                    let search_view = search_view.clone();
+                    menu.entry(query.clone(), None, move |cx| {
+                        search_view
+                            .update(cx, |search_view, cx| {
+                                search_view.set_query(&query, cx);
+                                search_view.search(cx);
+                            })
+                            .ok();
+                    })
+                })
         })
-    });
-}
+    }
 
-fn register_workspace_action_for_present_search<A: Action>(
-    workspace: &mut Workspace,
-    callback: fn(&mut Workspace, &A, &mut ViewContext<Workspace>),
-) {
-    workspace.register_action(move |workspace, action: &A, cx| {
-        if workspace.has_active_modal(cx) {
-            cx.propagate();
-            return;
-        }
+    fn has_language_servers(&self, cx: &AppContext) -> bool {
+        self.model
+            .read(cx)
+            .project
+            .read(cx)
+            .language_servers()
+            .next()
+            .is_some()
+    }
 
-        let should_notify = workspace
-            .active_pane()
+    fn has_git_repositories(&self, cx: &AppContext) -> bool {
+        self.model
             .read(cx)
-            .toolbar()
+            .project
             .read(cx)
-            .item_of_type::<ProjectSearchBar>()
-            .map(|search_bar| search_bar.read(cx).active_project_search.is_some())
-            .unwrap_or(false);
-        if should_notify {
-            callback(workspace, action, cx);
-            cx.notify();
-        } else {
-            cx.propagate();
+            .visible_worktrees(cx)
+            .any(|worktree| worktree.read(cx).repositories().next().is_some())
+    }
+
+    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
+        self.search_options.toggle(option);
+        self.remember_settings(cx);
+        self.schedule_regex_preview(cx);
+    }
+
+    /// Once the regex query stops changing, compiles it and previews its first match
+    /// in the file the search was deployed from.
+    fn schedule_regex_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.regex_preview = None;
+        if !self.search_options.contains(SearchOptions::REGEX) {
+            self.regex_preview_task = None;
+            return;
         }
-    });
-}
+        let whole_word = self.search_options.contains(SearchOptions::WHOLE_WORD);
+        let case_sensitive = self.search_options.contains(SearchOptions::CASE_SENSITIVE);
+        let search_options = self.search_options;
+        self.regex_preview_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REGEX_PREVIEW_DEBOUNCE).await;
+            let preview = this.update(&mut cx, |this, cx| {
+                let text = this.query_editor.read(cx).text(cx);
+                if text.is_empty() {
+                    return None;
+                }
+                let query = SearchQuery::regex(
+                    search_options.regex_pattern(&text),
+                    whole_word,
+                    case_sensitive,
+                    false,
+                    Vec::new(),
+                    Vec::new(),
+                );
+                cx.notify();
+                match query {
+                    Ok(query) => {
+                        this.query_error = None;
+                        this.panels_with_errors.remove(&InputPanel::Query);
+                        let snapshot = this.current_buffer.as_ref()?.read(cx).snapshot();
+                        Some(
+                            cx.background_executor()
+                                .spawn(async move { regex_preview(&query, &snapshot).await }),
+                        )
+                    }
+                    Err(error) => {
+                        this.query_error = Some(error.to_string().into());
+                        this.panels_with_errors.insert(InputPanel::Query);
+                        None
+                    }
+                }
+            });
+            let Some(preview) = preview.ok().flatten() else {
+                return;
+            };
+            let preview = preview.await;
+            this.update(&mut cx, |this, cx| {
+                this.regex_preview = preview.map(SharedString::from);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    /// Inverted results are the lines that don't match the query, which have nothing to replace.
+    fn can_replace(&self, cx: &AppContext) -> bool {
+        !self.invert_match
+            && !self
+                .model
+                .read(cx)
+                .active_query
+                .as_ref()
+                .is_some_and(|query| query.inverted())
+    }
+
+    fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
+        if self.model.read(cx).match_ranges.is_empty() || !self.can_replace(cx) {
+            return;
+        }
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        if self.is_match_excluded(active_index, cx) {
+            self.select_match(Direction::Next, cx);
+            return;
+        }
+
+        let query = self.model.read(cx).active_query.clone();
+        if let Some(query) = query {
+            let query = query.with_replacement(self.replacement(cx));
+
+            // TODO: Do we need the clone here?
+            let mat = self.model.read(cx).match_ranges[active_index].clone();
+            self.replace_match(&mat, &query, cx);
+            self.select_match(Direction::Next, cx)
+        }
+    }
+    pub fn replacement(&self, cx: &AppContext) -> String {
+        self.replacement_editor.read(cx).text(cx)
+    }
+    /// Returns the text of the match, and the text that replacing it with `query` inserts.
+    fn replacement_for_match(
+        &self,
+        mat: &Range<Anchor>,
+        query: &SearchQuery,
+        cx: &AppContext,
+    ) -> Option<(String, String)> {
+        let matched_text = self
+            .model
+            .read(cx)
+            .excerpts
+            .read(cx)
+            .snapshot(cx)
+            .text_for_range(mat.clone())
+            .collect::<String>();
+        let replacement = query.replacement_for(&matched_text)?;
+        let replacement = if self.case_preserving_replace {
+            with_case_of(&matched_text, &replacement)
+        } else {
+            replacement.into_owned()
+        };
+        Some((matched_text, replacement))
+    }
+    fn replace_match(
+        &mut self,
+        mat: &Range<Anchor>,
+        query: &SearchQuery,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !self.case_preserving_replace {
+            self.results_editor
+                .update(cx, |editor, cx| editor.replace(mat, query, cx));
+            return;
+        }
+        if let Some((_, replacement)) = self.replacement_for_match(mat, query, cx) {
+            self.results_editor.update(cx, |editor, cx| {
+                editor.transact(cx, |editor, cx| {
+                    editor.edit([(mat.clone(), replacement)], cx);
+                });
+            });
+        }
+    }
+    fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
+        if self.active_match_index.is_none() || !self.can_replace(cx) {
+            return;
+        }
+        self.replace_matches(|_| true, cx);
+    }
+
+    /// Replaces the matches accepted by `should_replace` that aren't excluded, as a single
+    /// transaction, and drops them from the results until that transaction is undone.
+    fn replace_matches(
+        &mut self,
+        should_replace: impl Fn(&Range<Anchor>) -> bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(query) = self.model.read(cx).active_query.as_ref() else {
+            return;
+        };
+        let query = query.clone().with_replacement(self.replacement(cx));
+
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let mut replaced_indices = HashSet::default();
+        let mut edits = Vec::new();
+        for (index, range) in match_ranges.iter().enumerate() {
+            if self.excluded_matches.contains(&match_buffer_range(range)) || !should_replace(range)
+            {
+                continue;
+            }
+            if let Some((_, replacement)) = self.replacement_for_match(range, &query, cx) {
+                replaced_indices.insert(index);
+                edits.push((range.clone(), replacement));
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+        let transaction_id = self.results_editor.update(cx, |editor, cx| {
+            // Keep other edits out of the transaction, so undoing it only reverts the replacements.
+            editor.finalize_last_transaction(cx);
+            let transaction_id = editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+            editor.finalize_last_transaction(cx);
+            transaction_id
+        });
+
+        // The replaced matches are gone, the excluded ones and those not replaced are left.
+        let remaining_ranges = match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !replaced_indices.contains(index))
+            .map(|(_, range)| range.clone())
+            .collect::<Vec<_>>();
+        self.match_history.clear();
+        let total_match_count = self.model.read(cx).total_match_count;
+        self.replaced_matches = transaction_id.map(|transaction_id| ReplacedMatches {
+            transaction_id,
+            search_id: self.model.read(cx).search_id,
+            match_ranges,
+            total_match_count,
+        });
+        self.results_editor.update(cx, |editor, cx| {
+            editor.highlight_background::<Self>(
+                &remaining_ranges,
+                |theme| theme.search_match_background,
+                cx,
+            );
+        });
+        self.model.update(cx, |model, cx| {
+            model.match_ranges = remaining_ranges;
+            model.total_match_count = total_match_count.saturating_sub(replaced_indices.len());
+            cx.notify();
+        });
+    }
+
+    /// Brings back the matches that a replace-all went through, once its transaction is undone.
+    fn restore_replaced_matches(
+        &mut self,
+        transaction_id: TransactionId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let search_id = self.model.read(cx).search_id;
+        let replaced = match self.replaced_matches.take() {
+            Some(replaced)
+                if replaced.transaction_id == transaction_id && replaced.search_id == search_id =>
+            {
+                replaced
+            }
+            replaced => {
+                self.replaced_matches = replaced;
+                return;
+            }
+        };
+        self.match_history.clear();
+        self.model.update(cx, |model, cx| {
+            model.match_ranges = replaced.match_ranges;
+            model.total_match_count = replaced.total_match_count;
+            cx.notify();
+        });
+    }
+
+    /// Replaces the matches in the file of the active match only, as a single transaction.
+    fn replace_all_in_file(&mut self, _: &ReplaceAllInFile, cx: &mut ViewContext<Self>) {
+        if !self.can_replace(cx) {
+            return;
+        }
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let Some(buffer_id) = self
+            .model
+            .read(cx)
+            .match_ranges
+            .get(active_index)
+            .and_then(|range| range.start.buffer_id)
+        else {
+            return;
+        };
+        self.replace_matches(|range| range.start.buffer_id == Some(buffer_id), cx);
+    }
+
+    fn new(
+        model: Model<ProjectSearch>,
+        cx: &mut ViewContext<Self>,
+        settings: Option<ProjectSearchSettings>,
+    ) -> Self {
+        let project;
+        let excerpts;
+        let mut replacement_text = None;
+        let mut invert_match = false;
+        let mut worktree_filter = None;
+        let mut query_text = String::new();
+        let mut subscriptions = Vec::new();
+
+        // Read in settings if available, falling back to the last ones used in the project.
+        let settings = settings.or_else(|| {
+            cx.global::<ActiveSettings>()
+                .0
+                .get(&model.read(cx).project.downgrade())
+                .cloned()
+        });
+        let (mut options, mut filters_enabled) = if let Some(settings) = settings {
+            (settings.search_options, settings.filters_enabled)
+        } else {
+            (
+                SearchSettings::get_global(cx).default_search_options(),
+                false,
+            )
+        };
+        let mut path_filters = cx
+            .global::<RecentPathFilters>()
+            .0
+            .get(&model.read(cx).project.downgrade())
+            .cloned()
+            .unwrap_or_default();
+
+        let scope;
+        let symbol_kind_filter;
+        let symbol_scope;
+        let include_dependencies;
+        let search_mode;
+        let context_before;
+        let context_after;
+        let sort_order;
+        {
+            let model = model.read(cx);
+            project = model.project.clone();
+            excerpts = model.excerpts.clone();
+            scope = model.scope.clone();
+            symbol_kind_filter = model.symbol_kind;
+            symbol_scope = model.symbol_scope;
+            include_dependencies = model.include_dependencies;
+            search_mode = model.search_mode;
+            context_before = model.context_before;
+            context_after = model.context_after;
+            sort_order = model.sort_order;
+            if let Some(active_query) = model.active_query.as_ref() {
+                query_text = typed_query(active_query).to_string();
+                replacement_text = active_query.replacement().map(ToOwned::to_owned);
+                options = SearchOptions::from_query(active_query);
+                invert_match = active_query.inverted();
+                worktree_filter = active_query.worktree_id();
+                path_filters = PathFilters::from_query(active_query);
+            }
+        }
+        if !path_filters.files_to_include.is_empty() || !path_filters.files_to_exclude.is_empty() {
+            filters_enabled = true;
+        }
+        subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
+        subscriptions.push(cx.subscribe(&model, |_, _, event, cx| match event {
+            ProjectSearchEvent::SearchCompleted { match_count } => {
+                cx.emit(ViewEvent::SearchCompleted {
+                    match_count: *match_count,
+                })
+            }
+        }));
+        // Keep the worktree selector in sync with the project's worktrees.
+        subscriptions.push(cx.subscribe(&project, |this, _, event, cx| match event {
+            project::Event::WorktreeRemoved(worktree_id) => {
+                if this.worktree_filter == Some(*worktree_id) {
+                    this.worktree_filter = None;
+                }
+                cx.notify();
+            }
+            project::Event::WorktreeAdded => cx.notify(),
+            _ => {}
+        }));
+
+        let multiline_query = query_text.contains('\n');
+        let (query_editor, query_editor_subscription) =
+            Self::new_query_editor(&query_text, multiline_query, cx);
+        let replacement_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Replace in project..", cx);
+            if let Some(text) = replacement_text {
+                editor.set_text(text, cx);
+            }
+            editor
+        });
+        let results_editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), cx);
+            editor.set_searchable(false);
+            editor.set_excerpt_header_path(|file, cx| {
+                excerpt_header_path(SearchSettings::get_global(cx).path_display, file, cx)
+            });
+            editor
+        });
+        subscriptions.push(cx.observe(&results_editor, |_, _, cx| cx.emit(ViewEvent::UpdateTab)));
+
+        subscriptions.push(
+            cx.subscribe(&results_editor, |this, _, event: &EditorEvent, cx| {
+                if matches!(event, editor::EditorEvent::SelectionsChanged { .. }) {
+                    this.update_match_index(cx);
+                }
+                if let editor::EditorEvent::TransactionUndone { transaction_id } = event {
+                    this.restore_replaced_matches(*transaction_id, cx);
+                }
+                if matches!(
+                    event,
+                    editor::EditorEvent::SelectionsChanged { local: true }
+                        | editor::EditorEvent::ScrollPositionChanged { local: true, .. }
+                ) {
+                    this.serialize_review_position(cx);
+                }
+                // The minimap highlights the file scrolled to.
+                if matches!(event, editor::EditorEvent::ScrollPositionChanged { .. }) {
+                    cx.notify();
+                }
+                // Reraise editor events for workspace item activation purposes
+                cx.emit(ViewEvent::EditorEvent(event.clone()));
+            }),
+        );
+
+        let included_files_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Include: crates/**/*.toml", cx);
+            editor.set_text(path_filters.files_to_include, cx);
+            editor
+        });
+        // Subscribe to include_files_editor in order to reraise editor events for workspace item activation purposes
+        subscriptions.push(
+            cx.subscribe(&included_files_editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(ViewEvent::EditorEvent(event.clone()))
+            }),
+        );
+
+        let excluded_files_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Exclude: vendor/*, *.lock", cx);
+            editor.set_text(path_filters.files_to_exclude, cx);
+            editor
+        });
+        // Subscribe to excluded_files_editor in order to reraise editor events for workspace item activation purposes
+        subscriptions.push(
+            cx.subscribe(&excluded_files_editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(ViewEvent::EditorEvent(event.clone()))
+            }),
+        );
+
+        let filter_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter results..", cx);
+            editor
+        });
+        subscriptions.push(cx.subscribe(
+            &filter_editor,
+            |this, editor, event: &EditorEvent, cx| {
+                if matches!(event, EditorEvent::Edited) {
+                    let filter = editor.read(cx).text(cx);
+                    this.model
+                        .update(cx, |model, cx| model.set_results_filter(filter, cx));
+                }
+            },
+        ));
+
+        let focus_handle = cx.focus_handle();
+        subscriptions.push(cx.on_focus_in(&focus_handle, |this, cx| {
+            if this.focus_handle.is_focused(cx) {
+                if this.has_matches() {
+                    this.results_editor.focus_handle(cx).focus(cx);
+                } else {
+                    this.query_editor.focus_handle(cx).focus(cx);
+                }
+            }
+        }));
+
+        // Check if Worktrees have all been previously indexed
+        let mut this = ProjectSearchView {
+            focus_handle,
+            replacement_editor,
+            search_id: model.read(cx).search_id,
+            model,
+            query_editor,
+            results_editor,
+            search_options: options,
+            panels_with_errors: HashSet::default(),
+            active_match_index: None,
+            match_summary: None,
+            line_truncations: HashMap::default(),
+            query_editor_was_focused: false,
+            included_files_editor,
+            excluded_files_editor,
+            filter_editor,
+            filters_enabled,
+            replace_enabled: false,
+            case_preserving_replace: false,
+            invert_match,
+            multi_pattern: false,
+            open_buffers: match &scope {
+                SearchScope::OpenBuffers(buffers) => buffers.clone(),
+                _ => Vec::new(),
+            },
+            current_buffer: match &scope {
+                SearchScope::CurrentFile(buffer) => Some(buffer.clone()),
+                _ => None,
+            },
+            visible_area: match &scope {
+                SearchScope::VisibleArea(visible_area) => Some(visible_area.clone()),
+                _ => None,
+            },
+            selection: match &scope {
+                SearchScope::Selection(selection) => Some(selection.clone()),
+                _ => None,
+            },
+            result_buffers: match &scope {
+                SearchScope::Results(buffers) => buffers.clone(),
+                _ => Vec::new(),
+            },
+            scope,
+            symbol_kind_filter,
+            symbol_scope,
+            language_filter: None,
+            worktree_filter,
+            include_dependencies,
+            search_mode,
+            treat_query_as_path: false,
+            live_search: false,
+            default_excludes_enabled: true,
+            context_before,
+            context_after,
+            sort_order,
+            pending_review_position: None,
+            pending_source_location: None,
+            pending_previous_match: None,
+            query_error: None,
+            glob_errors: HashMap::default(),
+            regex_preview: None,
+            regex_preview_task: None,
+            slow_regex_warning: false,
+            scheduled_search: None,
+            scheduled_review_position_serialization: None,
+            end_of_results_flash: None,
+            multiline_query,
+            results_pinned: false,
+            selected_filename_match: 0,
+            results_as_list: false,
+            group_by_directory: false,
+            collapsed_directories: HashSet::default(),
+            results_list_scroll_handle: UniformListScrollHandle::new(),
+            replace_preview: false,
+            replace_preview_scroll_handle: UniformListScrollHandle::new(),
+            excluded_matches: HashSet::default(),
+            replaced_matches: None,
+            match_history: Vec::new(),
+            match_history_position: 0,
+            bookmarks: Vec::new(),
+            active_bookmark: None,
+            semantic_score_blocks: HashSet::default(),
+            _query_editor_subscription: query_editor_subscription,
+            workspace: None,
+            workspace_id: None,
+            _subscriptions: subscriptions,
+        };
+        this.model_changed(cx);
+        this
+    }
+
+    pub fn new_search_in_directory(
+        workspace: &mut Workspace,
+        dir_path: &Path,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(filter_str) = dir_path.to_str() else {
+            return;
+        };
+
+        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search.clone()), cx);
+        search.update(cx, |search, cx| {
+            search
+                .included_files_editor
+                .update(cx, |editor, cx| editor.set_text(filter_str, cx));
+            search.filters_enabled = true;
+            search.focus_query_editor(cx)
+        });
+    }
+
+    // Re-activate the most recently activated search in this pane or the most recent if it has been closed.
+    // If no search exists in the workspace, create a new one.
+    fn deploy_search(
+        workspace: &mut Workspace,
+        action: &workspace::DeploySearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+
+        Self::existing_or_new_search(workspace, existing, action, cx);
+    }
+
+    // Run the built-in TODOs query in the most recently activated search of this pane, or in a new one.
+    fn search_todos(workspace: &mut Workspace, _: &SearchTodos, cx: &mut ViewContext<Workspace>) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        let search = Self::existing_or_new_search(workspace, existing, &DeploySearch::find(), cx);
+        search.update(cx, |search, cx| search.search_todos_in_view(cx));
+    }
+
+    /// Runs the query of the most recently activated search again, e.g. after editing files,
+    /// keeping its query rather than taking one from the active editor. Without any search in
+    /// the workspace, a new one is deployed instead.
+    fn repeat_last_search(
+        workspace: &mut Workspace,
+        _: &RepeatLastProjectSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let active_pane = workspace.active_pane().read(cx);
+        let existing = active_pane
+            .activation_history()
+            .iter()
+            .rev()
+            .find_map(|item_id| {
+                active_pane
+                    .items()
+                    .find(|item| item.item_id() == *item_id)?
+                    .downcast::<ProjectSearchView>()
+            })
+            .or_else(|| workspace.items_of_type::<ProjectSearchView>(cx).next());
+        let search = match existing {
+            Some(existing) => {
+                workspace.activate_item(&existing, cx);
+                existing
+            }
+            None => Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx),
+        };
+        search.update(cx, |search, cx| search.search(cx));
+    }
+
+    /// Configures the search of this pane from the `rg` or `grep` command in the clipboard and
+    /// runs it, noting which of the command's flags were left out.
+    fn import_ripgrep_command(
+        workspace: &mut Workspace,
+        _: &ImportRipgrepCommand,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        struct ImportRipgrepCommandToast;
+
+        let Some(command) = cx
+            .read_from_clipboard()
+            .and_then(|item| parse_ripgrep_command(item.text()))
+        else {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<ImportRipgrepCommandToast>(),
+                    "The clipboard does not contain an rg or grep command",
+                ),
+                cx,
+            );
+            return;
+        };
+        if !command.ignored_arguments.is_empty() {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<ImportRipgrepCommandToast>(),
+                    format!(
+                        "Ignored unsupported arguments: {}",
+                        command.ignored_arguments.join(" ")
+                    ),
+                ),
+                cx,
+            );
+        }
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        let search = Self::existing_or_new_search(workspace, existing, &DeploySearch::find(), cx);
+        search.update(cx, |search, cx| search.apply_ripgrep_command(command, cx));
+    }
+
+    fn apply_ripgrep_command(&mut self, command: RipgrepCommand, cx: &mut ViewContext<Self>) {
+        self.search_mode = SearchMode::Text;
+        self.treat_query_as_path = false;
+        self.restore(
+            command.query,
+            command.search_options,
+            command.files_to_include.join(", "),
+            command.files_to_exclude.join(", "),
+            None,
+            cx,
+        );
+        cx.notify();
+    }
+
+    fn search_todos_in_view(&mut self, cx: &mut ViewContext<Self>) {
+        let tokens = &SearchSettings::get_global(cx).todo_tokens;
+        if tokens.is_empty() {
+            return;
+        }
+        let query = format!(
+            r"\b({})\b",
+            tokens
+                .iter()
+                .map(|token| regex::escape(token))
+                .collect::<Vec<_>>()
+                .join("|")
+        );
+        self.search_options.insert(SearchOptions::REGEX);
+        self.set_query(&query, cx);
+        self.search(cx);
+    }
+
+    // Search the project for the word under the cursor of the active editor and jump straight to
+    // its only other occurrence if there is one, falling back to a regular project search otherwise.
+    fn find_and_select(
+        workspace: &mut Workspace,
+        _: &FindAndSelect,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<Editor>())
+        else {
+            cx.propagate();
+            return;
+        };
+        let query_text = editor.query_suggestion(cx);
+        if query_text.is_empty() {
+            return;
+        }
+        let Some(query) =
+            SearchQuery::text(query_text, true, true, false, Vec::new(), Vec::new()).log_err()
+        else {
+            return;
+        };
+        let cursor = editor.update(cx, |editor, cx| {
+            let cursor = editor.selections.newest_anchor().head();
+            editor
+                .buffer()
+                .read(cx)
+                .text_anchor_for_position(cursor, cx)
+        });
+
+        let mut matches = workspace
+            .project()
+            .update(cx, |project, cx| project.search(query, cx));
+        cx.spawn(|workspace, mut cx| async move {
+            // Two occurrences besides the one under the cursor are enough to know there's no single target.
+            let mut occurrences = Vec::new();
+            while let Some(result) = matches.next().await {
+                match result {
+                    project::SearchResult::Buffer { buffer, ranges } => {
+                        occurrences.extend(ranges.into_iter().map(|range| (buffer.clone(), range)));
+                        if occurrences.len() > 2 {
+                            break;
+                        }
+                    }
+                    project::SearchResult::LimitReached => break,
+                }
+            }
+            drop(matches);
+
+            workspace.update(&mut cx, |workspace, cx| {
+                occurrences.retain(|(buffer, range)| {
+                    let Some((cursor_buffer, cursor)) = &cursor else {
+                        return true;
+                    };
+                    let snapshot = buffer.read(cx).snapshot();
+                    buffer != cursor_buffer
+                        || range.start.cmp(cursor, &snapshot).is_gt()
+                        || range.end.cmp(cursor, &snapshot).is_lt()
+                });
+
+                if let [(buffer, range)] = occurrences.as_slice() {
+                    let range = range.to_offset(&buffer.read(cx).snapshot());
+                    let pane = workspace.active_pane().clone();
+                    let editor = workspace.open_project_item::<Editor>(pane, buffer.clone(), cx);
+                    editor.update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_ranges([range])
+                        });
+                    });
+                } else {
+                    let search =
+                        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx);
+                    search.update(cx, |search, cx| search.search(cx));
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn search_in_new(workspace: &mut Workspace, _: &SearchInNew, cx: &mut ViewContext<Workspace>) {
+        if let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        {
+            let new_query = search_view.update(cx, |search_view, cx| {
+                let new_query = search_view.build_search_query(cx);
+                if new_query.is_some() {
+                    if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
+                        search_view.query_editor.update(cx, |editor, cx| {
+                            editor.set_text(typed_query(&old_query), cx);
+                        });
+                        search_view.search_options = SearchOptions::from_query(&old_query);
+                    }
+                }
+                new_query
+            });
+            if let Some(new_query) = new_query {
+                Self::new_in_workspace(workspace, Some(new_query), cx);
+            }
+        }
+    }
+
+    /// Adds a project search tab to the active pane of the workspace. With a `query`, the tab
+    /// runs it and its inputs are filled in from it, otherwise they start out with the options
+    /// and filters last used in the project.
+    pub fn new_in_workspace(
+        workspace: &mut Workspace,
+        query: Option<SearchQuery>,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<ProjectSearchView> {
+        cx.update_global(|settings: &mut ActiveSettings, _| {
+            settings.0.retain(|project, _| project.upgrade().is_some());
+        });
+        cx.update_global(|recent_path_filters: &mut RecentPathFilters, _| {
+            recent_path_filters
+                .0
+                .retain(|project, _| project.upgrade().is_some());
+        });
+        let settings = cx
+            .global::<ActiveSettings>()
+            .0
+            .get(&workspace.project().downgrade())
+            .cloned();
+
+        let model = cx.new_model(|cx| {
+            let mut model = ProjectSearch::new(workspace.project().clone(), cx);
+            if let Some(query) = query {
+                model.search(query, cx);
+            }
+            model
+        });
+        let view = cx.new_view(|cx| ProjectSearchView::new(model, cx, settings));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), cx);
+        view
+    }
+
+    // Open every file with matches in the active search in its own editor, asking first when there are many.
+    fn open_all_matches(
+        workspace: &mut Workspace,
+        _: &OpenAllMatches,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let project_paths = search_view
+            .read(cx)
+            .model
+            .read(cx)
+            .matched_project_paths(cx);
+        if project_paths.is_empty() {
+            return;
+        }
+
+        cx.spawn(|workspace, mut cx| async move {
+            if project_paths.len() > MAX_FILES_TO_OPEN_WITHOUT_CONFIRMATION {
+                let answer = workspace.update(&mut cx, |_, cx| {
+                    cx.prompt(
+                        PromptLevel::Info,
+                        &format!("Open {} files with matches?", project_paths.len()),
+                        None,
+                        &["Open All", "Cancel"],
+                    )
+                })?;
+                if answer.await != Ok(0) {
+                    return Ok(());
+                }
+            }
+
+            let open_tasks = workspace.update(&mut cx, |workspace, cx| {
+                project_paths
+                    .into_iter()
+                    .map(|project_path| workspace.open_path(project_path, None, false, cx))
+                    .collect::<Vec<_>>()
+            })?;
+            for open_task in open_tasks {
+                open_task.await.log_err();
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    // Add another search tab to the workspace.
+    fn new_search(
+        workspace: &mut Workspace,
+        _: &workspace::NewSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), cx);
+    }
+
+    fn existing_or_new_search(
+        workspace: &mut Workspace,
+        existing: Option<View<ProjectSearchView>>,
+        action: &workspace::DeploySearch,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<ProjectSearchView> {
+        let query = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            let query = editor.query_suggestion(cx);
+            if query.is_empty() {
+                None
+            } else {
+                Some(query)
+            }
+        });
+        let visible_area = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            editor.update(cx, |editor, cx| Self::visible_area(editor, cx))
+        });
+        let selection = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            editor.update(cx, |editor, cx| Self::multiline_selection(editor, cx))
+        });
+        let current_buffer = workspace.active_item(cx).and_then(|item| {
+            let editor = item.act_as::<Editor>(cx)?;
+            editor.read(cx).buffer().read(cx).as_singleton()
+        });
+        let mut open_buffers = Vec::new();
+        for item in workspace.items(cx) {
+            let Some(editor) = item.act_as::<Editor>(cx) else {
+                continue;
+            };
+            if let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() {
+                if !open_buffers.contains(&buffer) {
+                    open_buffers.push(buffer);
+                }
+            }
+        }
+        let source_location = query.as_ref().and_then(|_| {
+            let editor = workspace.active_item(cx)?.act_as::<Editor>(cx)?;
+            let editor = editor.read(cx);
+            let cursor = editor.selections.newest_anchor().head();
+            editor
+                .buffer()
+                .read(cx)
+                .text_anchor_for_position(cursor, cx)
+        });
+
+        let search = if let Some(existing) = existing {
+            workspace.activate_item(&existing, cx);
+            existing
+        } else {
+            Self::new_in_workspace(workspace, None, cx)
+        };
+
+        search.update(cx, |search, cx| {
+            search.replace_enabled = action.replace_enabled;
+            match &search.scope {
+                SearchScope::OpenBuffers(_) if open_buffers.is_empty() => {
+                    search.scope = SearchScope::Project;
+                }
+                SearchScope::OpenBuffers(_) => {
+                    search.scope = SearchScope::OpenBuffers(open_buffers.clone());
+                }
+                SearchScope::CurrentFile(_) => match &current_buffer {
+                    Some(current_buffer) => {
+                        search.scope = SearchScope::CurrentFile(current_buffer.clone());
+                    }
+                    None => search.scope = SearchScope::Project,
+                },
+                _ => {}
+            }
+            search.open_buffers = open_buffers;
+            if let Some(current_buffer) = current_buffer {
+                search.current_buffer = Some(current_buffer);
+            }
+            if let Some(visible_area) = visible_area {
+                if let SearchScope::VisibleArea(_) = search.scope {
+                    search.scope = SearchScope::VisibleArea(visible_area.clone());
+                }
+                search.visible_area = Some(visible_area);
+            }
+            match selection {
+                Some(selection) => {
+                    if let SearchScope::Selection(_) = search.scope {
+                        search.scope = SearchScope::Selection(selection.clone());
+                    }
+                    search.selection = Some(selection);
+                }
+                None => {
+                    if let SearchScope::Selection(_) = search.scope {
+                        search.scope = SearchScope::Project;
+                    }
+                    search.selection = None;
+                }
+            }
+            if let Some(query) = query {
+                search.set_query(&query, cx);
+            }
+            search.pending_source_location =
+                source_location.map(|(buffer, position)| SourceLocation {
+                    buffer,
+                    position,
+                    search_id: search.model.read(cx).search_id,
+                });
+            search.focus_query_editor(cx)
+        });
+        search
+    }
+
+    /// Returns the buffer lines currently displayed by a singleton editor.
+    fn visible_area(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Option<BufferRange> {
+        let buffer = editor.buffer().read(cx).as_singleton()?;
+        let visible_line_count = editor.visible_line_count()?;
+        let top_row = editor.scroll_position(cx).y.max(0.) as u32;
+        let snapshot = editor.snapshot(cx);
+        let start = DisplayPoint::new(top_row, 0).to_point(&snapshot);
+        let end = snapshot
+            .clip_point(
+                DisplayPoint::new(top_row + visible_line_count.ceil() as u32, 0),
+                Bias::Left,
+            )
+            .to_point(&snapshot);
+
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let end_row = end.row.min(buffer_snapshot.max_point().row);
+        let range = buffer_snapshot.anchor_before(language::Point::new(start.row, 0))
+            ..buffer_snapshot.anchor_after(language::Point::new(
+                end_row,
+                buffer_snapshot.line_len(end_row),
+            ));
+        Some(BufferRange { buffer, range })
+    }
+
+    /// Returns the newest selection of a singleton editor, if it spans several lines.
+    fn multiline_selection(
+        editor: &mut Editor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<BufferRange> {
+        let buffer = editor.buffer().read(cx).as_singleton()?;
+        let selection = editor.selections.newest::<language::Point>(cx);
+        if selection.is_empty() || selection.start.row == selection.end.row {
+            return None;
+        }
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let range = buffer_snapshot.anchor_before(selection.start)
+            ..buffer_snapshot.anchor_after(selection.end);
+        Some(BufferRange { buffer, range })
+    }
+
+    /// Searches once no other option was toggled for a little while, so that toggling
+    /// several options in a row only searches the project once.
+    fn schedule_search(&mut self, cx: &mut ViewContext<Self>) {
+        self.scheduled_search = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(OPTION_TOGGLE_DEBOUNCE).await;
+            this.update(&mut cx, |this, cx| this.search(cx)).ok();
+        }));
+    }
+
+    fn search(&mut self, cx: &mut ViewContext<Self>) {
+        self.scheduled_search.take();
+        if self.search_mode == SearchMode::Semantic {
+            let query = self.query_editor.read(cx).text(cx);
+            let (included_files, excluded_files) = self.build_path_matchers(cx);
+            if query.is_empty()
+                || self.panels_with_errors.contains(&InputPanel::Include)
+                || self.panels_with_errors.contains(&InputPanel::Exclude)
+            {
+                return;
+            }
+            // The Semantic mode can only be chosen with a semantic index, but a search restored
+            // in that mode can still lack one.
+            if !cx.has_global::<SemanticIndex>() {
+                self.query_error =
+                    Some("Semantic search is unavailable without a semantic index".into());
+                self.panels_with_errors.insert(InputPanel::Query);
+                cx.notify();
+                return;
+            }
+            self.query_error = None;
+            self.panels_with_errors.remove(&InputPanel::Query);
+            // The results are scoped to the same files as the ones of a text search.
+            let Some(path_filter) = SearchQuery::text(
+                query.clone(),
+                false,
+                false,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            )
+            .log_err() else {
+                return;
+            };
+            let path_filter = path_filter.with_worktree(self.worktree_filter);
+            self.model.update(cx, |model, cx| {
+                model.search_mode = SearchMode::Semantic;
+                model.semantic_search(query, path_filter, cx)
+            });
+            return;
+        }
+        if self.search_mode == SearchMode::Hex {
+            let query = self.query_editor.read(cx).text(cx);
+            if query.is_empty() {
+                return;
+            }
+            match parse_hex_pattern(&query) {
+                Some(pattern) => {
+                    self.query_error = None;
+                    self.panels_with_errors.remove(&InputPanel::Query);
+                    self.model.update(cx, |model, cx| {
+                        model.search_mode = SearchMode::Hex;
+                        model.hex_search(query, pattern, cx)
+                    });
+                }
+                None => {
+                    self.query_error =
+                        Some("Expected pairs of hex digits, like DE AD BE EF".into());
+                    self.panels_with_errors.insert(InputPanel::Query);
+                }
+            }
+            cx.notify();
+            return;
+        }
+        if self.search_mode == SearchMode::Filename || self.treat_query_as_path {
+            let query = self.query_editor.read(cx).text(cx);
+            if !query.is_empty() {
+                self.selected_filename_match = 0;
+                let as_path = self.treat_query_as_path;
+                self.model.update(cx, |model, cx| {
+                    model.search_mode = SearchMode::Filename;
+                    model.filename_search(query, as_path, cx)
+                });
+            }
+            return;
+        }
+        if let Some(query) = self.build_search_query(cx) {
+            self.remember_path_filters(cx);
+            if self.results_pinned && self.search_in_new_tab(query.clone(), cx) {
+                return;
+            }
+            self.serialize(cx);
+            self.search_with_query(query, cx);
+        }
+    }
+
+    /// Runs `query` in a new tab, restoring the inputs of this one to match its pinned results.
+    /// Returns `false` if the search is not part of a workspace to open the tab in.
+    fn search_in_new_tab(&mut self, query: SearchQuery, cx: &mut ViewContext<Self>) -> bool {
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return false;
+        };
+        if let Some(active_query) = self.model.read(cx).active_query.clone() {
+            self.search_options = SearchOptions::from_query(&active_query);
+            self.invert_match = active_query.inverted();
+            self.set_query(typed_query(&active_query), cx);
+        }
+        // The workspace may be the one dispatching the action that triggered the search.
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                ProjectSearchView::new_in_workspace(workspace, Some(query), cx);
+            });
+        });
+        cx.notify();
+        true
+    }
+
+    /// Copies the lines of all matches, or of the active one only, to the clipboard.
+    fn copy_matches(&mut self, active_match_only: bool, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let match_ranges = if active_match_only {
+            let Some(active_match) = self
+                .active_match_index
+                .and_then(|index| model.match_ranges.get(index..index + 1))
+            else {
+                return;
+            };
+            active_match
+        } else {
+            &model.match_ranges
+        };
+        if match_ranges.is_empty() {
+            return;
+        }
+        let text = model.match_lines(match_ranges, cx);
+        cx.write_to_clipboard(ClipboardItem::new(text));
+    }
+
+    /// Saves the report of the matches to a new file, since the workspace only offers
+    /// "Save As" for items backed by a single file.
+    fn export_results(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_matches() {
+            return;
+        }
+        let project = self.model.read(cx).project.clone();
+        let start_abs_path = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .and_then(|worktree| Some(worktree.read(cx).as_local()?.abs_path().to_path_buf()))
+            .unwrap_or_else(|| Path::new("").into());
+        let abs_path = cx.prompt_for_new_path(&start_abs_path);
+        cx.spawn(|this, mut cx| async move {
+            let Some(abs_path) = abs_path.await.ok().flatten() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| this.save_as(project, abs_path, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// The last query followed by its match count, which grows as the matches stream in. The
+    /// query is truncated for the count to still fit within `MAX_TAB_TITLE_LEN`.
+    fn tab_title(&self, cx: &AppContext) -> SharedString {
+        let model = self.model.read(cx);
+        let Some(query) = model
+            .last_search_query_text
+            .as_ref()
+            .map(|query| query.replace('\n', ""))
+            .filter(|query| !query.is_empty())
+        else {
+            return "Project Search".into();
+        };
+        let match_count = model.match_ranges.len();
+        if match_count == 0 {
+            return truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN).into();
+        }
+        let match_count = format!(" ({match_count})");
+        let max_query_len = MAX_TAB_TITLE_LEN
+            .saturating_sub(match_count.chars().count())
+            .max(5);
+        format!(
+            "{}{match_count}",
+            truncate_and_trailoff(&query, max_query_len)
+        )
+        .into()
+    }
+
+    fn toggle_pin_results(&mut self, cx: &mut ViewContext<Self>) {
+        self.results_pinned = !self.results_pinned;
+        cx.emit(ViewEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn remember_path_filters(&self, cx: &mut ViewContext<Self>) {
+        let path_filters = PathFilters {
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+        };
+        let project = self.model.read(cx).project.downgrade();
+        cx.update_global(|recent_path_filters: &mut RecentPathFilters, _| {
+            recent_path_filters.0.insert(project, path_filters);
+        });
+    }
+
+    fn search_with_query(&mut self, query: SearchQuery, cx: &mut ViewContext<Self>) {
+        if self.pending_source_location.is_none() && self.pending_review_position.is_none() {
+            self.pending_previous_match = self.active_match_location(cx);
+        }
+        // Options can also change without being toggled, e.g. when restoring a search.
+        self.remember_settings(cx);
+        let scope = self.scope.clone();
+        let include_dependencies = self.include_dependencies;
+        let symbol_kind = self
+            .symbol_kind_filter
+            .filter(|_| self.has_language_servers(cx));
+        let symbol_scope = self.symbol_scope;
+        self.model.update(cx, |model, cx| {
+            model.scope = scope;
+            model.symbol_kind = symbol_kind;
+            model.symbol_scope = symbol_scope;
+            model.include_dependencies = include_dependencies;
+            model.search_mode = SearchMode::Text;
+            model.search(query, cx)
+        });
+    }
+
+    fn active_match_location(&self, cx: &AppContext) -> Option<SourceLocation> {
+        let model = self.model.read(cx);
+        let active_match = model.match_ranges.get(self.active_match_index?)?;
+        let buffer = model
+            .excerpts
+            .read(cx)
+            .buffer(active_match.start.buffer_id?)?;
+        Some(SourceLocation {
+            buffer,
+            position: active_match.start.text_anchor,
+            search_id: model.search_id,
+        })
+    }
+
+    /// Parses the include and exclude globs, marking the ones that fail to parse as errors.
+    ///
+    /// The default excludes and the globs of the language filter are added to them.
+    fn build_path_matchers(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+    ) -> (Vec<PathMatcher>, Vec<PathMatcher>) {
+        let included_files =
+            match Self::parse_path_matches(&self.included_files_editor.read(cx).text(cx)) {
+                Ok(included_files) => {
+                    self.glob_errors.remove(&InputPanel::Include);
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+                    included_files
+                }
+                Err(error) => {
+                    self.glob_errors
+                        .insert(InputPanel::Include, error.root_cause().to_string());
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+                    vec![]
+                }
+            };
+        let excluded_files =
+            match Self::parse_path_matches(&self.excluded_files_editor.read(cx).text(cx)) {
+                Ok(excluded_files) => {
+                    self.glob_errors.remove(&InputPanel::Exclude);
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    excluded_files
+                }
+                Err(error) => {
+                    self.glob_errors
+                        .insert(InputPanel::Exclude, error.root_cause().to_string());
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+                    vec![]
+                }
+            };
+        let excluded_files = if self.default_excludes_enabled {
+            with_default_excludes(
+                excluded_files,
+                &included_files,
+                &SearchSettings::get_global(cx).default_excludes,
+            )
+        } else {
+            excluded_files
+        };
+        // The typed globs come last, so that their negations also apply to the language's files.
+        let mut included_files = included_files;
+        included_files.splice(0..0, self.language_filter_globs(cx));
+        (included_files, excluded_files)
+    }
+
+    fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
+        // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
+        let text = self.query_editor.read(cx).text(cx);
+        let (included_files, excluded_files) = self.build_path_matchers(cx);
+
+        let slow_regex_warning =
+            self.search_options.contains(SearchOptions::REGEX) && may_be_slow_regex(&text);
+        if self.slow_regex_warning != slow_regex_warning {
+            self.slow_regex_warning = slow_regex_warning;
+            cx.notify();
+        }
+        let query = if self.search_options.contains(SearchOptions::REGEX) {
+            SearchQuery::regex(
+                self.search_options.regex_pattern(&text),
+                self.search_options.contains(SearchOptions::WHOLE_WORD),
+                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            )
+        } else if self.multi_pattern {
+            SearchQuery::regex(
+                multi_pattern_regex(&text),
+                self.search_options.contains(SearchOptions::WHOLE_WORD),
+                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            )
+        } else {
+            SearchQuery::text(
+                text,
+                self.search_options.contains(SearchOptions::WHOLE_WORD),
+                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            )
+        };
+        let query = match query {
+            Ok(query) => {
+                self.query_error = None;
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                if should_unmark_error {
+                    cx.notify();
+                }
+
+                Some(query)
+            }
+            Err(error) => {
+                self.query_error = Some(error.to_string().into());
+                let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                if should_mark_error {
+                    cx.notify();
+                }
+
+                None
+            }
+        };
+        if !self.panels_with_errors.is_empty() {
+            return None;
+        }
+        if query.as_ref().is_some_and(|query| query.is_empty()) {
+            return None;
+        }
+        query.map(|query| {
+            query
+                .with_worktree(self.worktree_filter)
+                .with_inverted_matches(self.invert_match)
+        })
+    }
+
+    fn parse_path_matches(text: &str) -> anyhow::Result<Vec<PathMatcher>> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|maybe_glob_str| !maybe_glob_str.is_empty())
+            .map(|maybe_glob_str| {
+                PathMatcher::negatable(maybe_glob_str)
+                    .with_context(|| format!("parsing {maybe_glob_str} as path matcher"))
+            })
+            .collect()
+    }
+
+    fn select_match(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        self.select_nth_match(direction, 1, cx);
+    }
+
+    /// Selects the `count`th next or previous match if the results editor is focused, returning
+    /// whether it did. Meant for modal keymaps, e.g. Vim's `n` and `N`, to move through the
+    /// results while leaving the searches of the other editors alone.
+    pub fn select_match_in_results(
+        &mut self,
+        direction: Direction,
+        count: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        if !self.has_matches() || !self.results_editor.focus_handle(cx).is_focused(cx) {
+            return false;
+        }
+        self.select_nth_match(direction, count, cx);
+        true
+    }
+
+    fn select_nth_match(&mut self, direction: Direction, count: usize, cx: &mut ViewContext<Self>) {
+        let wrap_around = SearchSettings::get_global(cx).wrap_around;
+        if self.has_filename_matches(cx) {
+            let match_count = self.model.read(cx).filename_matches.len();
+            let count = count % match_count;
+            let new_index = match direction {
+                Direction::Next => (self.selected_filename_match + count) % match_count,
+                Direction::Prev => {
+                    (self.selected_filename_match + match_count - count) % match_count
+                }
+            };
+            if !wrap_around && wraps(self.selected_filename_match, new_index, direction) {
+                self.flash_end_of_results(cx);
+                return;
+            }
+            self.selected_filename_match = new_index;
+            cx.notify();
+        } else if let Some(index) = self.active_match_index {
+            let match_ranges = self.model.read(cx).match_ranges.clone();
+            let new_index = self.results_editor.update(cx, |editor, cx| {
+                editor.match_index_for_direction(&match_ranges, index, direction, count, cx)
+            });
+            if !wrap_around && wraps(index, new_index, direction) {
+                self.flash_end_of_results(cx);
+                return;
+            }
+
+            self.select_match_range(&match_ranges[new_index], cx);
+        }
+    }
+
+    fn flash_end_of_results(&mut self, cx: &mut ViewContext<Self>) {
+        self.end_of_results_flash = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(END_OF_RESULTS_FLASH).await;
+            this.update(&mut cx, |this, cx| {
+                this.end_of_results_flash = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn select_match_range(&mut self, range: &Range<Anchor>, cx: &mut ViewContext<Self>) {
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(range);
+            editor.unfold_ranges([range_to_select.clone()], false, true, cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_ranges([range_to_select])
+            });
+        });
+    }
+
+    /// Selects the match numbered `number` in the match indicator, clamped to the existing ones.
+    pub(crate) fn go_to_match(&mut self, number: usize, cx: &mut ViewContext<Self>) {
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        if match_ranges.is_empty() {
+            return;
+        }
+        let index = number.clamp(1, match_ranges.len()) - 1;
+        self.select_match_range(&match_ranges[index], cx);
+    }
+
+    fn deploy_go_to_match(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_matches() {
+            return;
+        }
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return;
+        };
+        let search_view = cx.view().downgrade();
+        let active_match_number = self.active_match_index.map(|index| index + 1);
+        let match_count = self.model.read(cx).match_ranges.len();
+        // The workspace may be the one dispatching the action that deployed the modal.
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, move |cx| {
+                    GoToMatchModal::new(search_view, active_match_number, match_count, cx)
+                });
+            });
+        });
+    }
+
+    /// Moves the active match like `select_match`, but only scrolls to it and highlights it,
+    /// leaving the selections of the results editor and the focus untouched.
+    fn peek_match(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let Some(index) = self.active_match_index else {
+            return;
+        };
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let new_index = self.results_editor.update(cx, |editor, cx| {
+            editor.match_index_for_direction(&match_ranges, index, direction, 1, cx)
+        });
+
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_peek = editor.range_for_match(&match_ranges[new_index]);
+            editor.unfold_ranges([range_to_peek.clone()], false, true, cx);
+            let snapshot = editor.snapshot(cx);
+            let row = range_to_peek.start.to_display_point(&snapshot).row() as f32;
+            let visible_line_count = editor.visible_line_count().unwrap_or(0.);
+            let scroll_x = editor.scroll_position(cx).x;
+            editor
+                .set_scroll_position(point(scroll_x, (row - visible_line_count / 2.).max(0.)), cx);
+        });
+        self.active_match_index = Some(new_index);
+        self.truncate_long_lines(cx);
+        self.highlight_active_match(cx);
+        cx.notify();
+    }
+
+    fn focus_query_editor(&mut self, cx: &mut ViewContext<Self>) {
+        self.query_editor.update(cx, |query_editor, cx| {
+            query_editor.select_all(&SelectAll, cx);
+        });
+        self.query_editor_was_focused = true;
+        let editor_handle = self.query_editor.focus_handle(cx);
+        cx.focus(&editor_handle);
+    }
+
+    fn set_query(&mut self, query: &str, cx: &mut ViewContext<Self>) {
+        self.query_editor
+            .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+    }
+
+    pub(crate) fn focus_results_editor(&mut self, cx: &mut ViewContext<Self>) {
+        self.query_editor.update(cx, |query_editor, cx| {
+            let cursor = query_editor.selections.newest_anchor().head();
+            query_editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+        });
+        self.query_editor_was_focused = false;
+        let results_handle = self.results_editor.focus_handle(cx);
+        cx.focus(&results_handle);
+    }
+
+    fn model_changed(&mut self, cx: &mut ViewContext<Self>) {
+        self.prune_bookmarks(cx);
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        self.match_summary = match_summary(&match_ranges);
+        if match_ranges.is_empty() {
+            self.active_match_index = None;
+            // Rebuilding the excerpts empties the matches too, but keeps the search and its
+            // exclusions.
+            if self.model.read(cx).search_id != self.search_id {
+                self.excluded_matches.clear();
+            }
+            self.match_history.clear();
+        } else {
+            self.active_match_index = Some(0);
+            self.update_match_index(cx);
+            let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
+            let is_new_search = self.search_id != prev_search_id;
+            // Until the search completes, the view stays where the previously active match was.
+            let keep_position = self.pending_previous_match.is_some();
+            self.results_editor.update(cx, |editor, cx| {
+                if is_new_search && !keep_position {
+                    let range_to_select = match_ranges
+                        .first()
+                        .map(|range| editor.range_for_match(range));
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.select_ranges(range_to_select)
+                    });
+                    editor.scroll(Point::default(), Some(Axis::Vertical), cx);
+                }
+                editor.highlight_background::<Self>(
+                    &match_ranges,
+                    |theme| theme.search_match_background,
+                    cx,
+                );
+            });
+            if is_new_search {
+                self.excluded_matches.clear();
+                self.match_history.clear();
+            }
+            if is_new_search && self.query_editor.focus_handle(cx).is_focused(cx) {
+                self.focus_results_editor(cx);
+            }
+        }
+        if let Some(source_location) = self.pending_source_location.take() {
+            self.select_source_location_match(source_location, &match_ranges, cx);
+        }
+        if let Some(previous_match) = self.pending_previous_match.take() {
+            self.select_nearest_previous_match(previous_match, &match_ranges, cx);
+        }
+        self.record_visited_match();
+        self.highlight_active_match(cx);
+        self.highlight_excluded_matches(cx);
+        self.update_semantic_score_blocks(&match_ranges, cx);
+        if self.model.read(cx).pending_search.is_none() {
+            if let Some(review_position) = self.pending_review_position.take() {
+                self.restore_review_position(review_position, cx);
+            }
+        }
+        self.truncate_long_lines(cx);
+
+        cx.emit(ViewEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn update_semantic_score_blocks(
+        &mut self,
+        match_ranges: &[Range<Anchor>],
+        cx: &mut ViewContext<Self>,
+    ) {
+        let model = self.model.read(cx);
+        let semantic_scores = if model.search_mode == SearchMode::Semantic {
+            model.semantic_scores.as_slice()
+        } else {
+            &[]
+        };
+        let excerpts = model.excerpts.read(cx).snapshot(cx);
+        let blocks = match_ranges
+            .iter()
+            .filter_map(|range| {
+                let (_, score) = semantic_scores
+                    .iter()
+                    .find(|(start, _)| *start == range.start.text_anchor)?;
+                Some(BlockProperties {
+                    position: excerpts
+                        .anchor_in_excerpt(range.start.excerpt_id, language::Anchor::MIN)?,
+                    height: 1,
+                    style: BlockStyle::Sticky,
+                    render: semantic_score_renderer(*score),
+                    disposition: BlockDisposition::Above,
+                })
+            })
+            .collect::<Vec<_>>();
+        let previous_blocks = mem::take(&mut self.semantic_score_blocks);
+        if previous_blocks.is_empty() && blocks.is_empty() {
+            return;
+        }
+        self.semantic_score_blocks = self.results_editor.update(cx, |editor, cx| {
+            editor.remove_blocks(previous_blocks, None, cx);
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+    }
+
+    /// Selects the match at the location the search was deployed from as soon as it streams in,
+    /// leaving the first match selected if the search completes without it.
+    fn select_source_location_match(
+        &mut self,
+        source_location: SourceLocation,
+        match_ranges: &[Range<Anchor>],
+        cx: &mut ViewContext<Self>,
+    ) {
+        let model = self.model.read(cx);
+        if model.search_id == source_location.search_id {
+            self.pending_source_location = Some(source_location);
+            return;
+        }
+        let Some(index) = source_location.match_index(match_ranges, cx) else {
+            if model.pending_search.is_some() {
+                self.pending_source_location = Some(source_location);
+            }
+            return;
+        };
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(&match_ranges[index]);
+            editor.unfold_ranges([range_to_select.clone()], false, false, cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_ranges([range_to_select])
+            });
+        });
+        self.active_match_index = Some(index);
+    }
+
+    /// Once the search completes, selects the match nearest to the one that was active before it
+    /// ran, falling back to the first match if none is left in the same file.
+    fn select_nearest_previous_match(
+        &mut self,
+        previous_match: SourceLocation,
+        match_ranges: &[Range<Anchor>],
+        cx: &mut ViewContext<Self>,
+    ) {
+        let model = self.model.read(cx);
+        if model.search_id == previous_match.search_id || model.pending_search.is_some() {
+            self.pending_previous_match = Some(previous_match);
+            return;
+        }
+        if match_ranges.is_empty() {
+            return;
+        }
+        let index = previous_match.nearest_match_index(match_ranges, cx);
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(&match_ranges[index.unwrap_or(0)]);
+            editor.unfold_ranges([range_to_select.clone()], false, false, cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_ranges([range_to_select])
+            });
+            if index.is_none() {
+                editor.scroll(Point::default(), Some(Axis::Vertical), cx);
+            }
+        });
+        self.active_match_index = Some(index.unwrap_or(0));
+    }
+
+    /// Selects the previously active match, clamped to the current match count since
+    /// the searched files may have changed, and scrolls the results back to where they were.
+    fn restore_review_position(
+        &mut self,
+        review_position: ReviewPosition,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let Some(last_index) = match_ranges.len().checked_sub(1) else {
+            return;
+        };
+        let index = review_position.active_match_index.min(last_index);
+        self.results_editor.update(cx, |editor, cx| {
+            let range_to_select = editor.range_for_match(&match_ranges[index]);
+            editor.unfold_ranges([range_to_select.clone()], false, false, cx);
+            editor.change_selections(None, cx, |s| s.select_ranges([range_to_select]));
+            editor.set_scroll_position(review_position.scroll_position, cx);
+        });
+        self.active_match_index = Some(index);
+    }
+
+    /// Folds away the parts of matched lines that exceed `search.max_excerpt_line_length`,
+    /// keeping the active match, or else the first match of every such line, centered in what
+    /// remains visible. The folds of an excerpt are recomputed once its matches change, and
+    /// restored where expanding the results or selecting a match unfolded them.
+    fn truncate_long_lines(&mut self, cx: &mut ViewContext<Self>) {
+        let max_line_length = SearchSettings::get_global(cx).max_excerpt_line_length;
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let active_match_start = self
+            .active_match_index
+            .and_then(|index| match_ranges.get(index))
+            .map(|range| range.start);
+        let active_match_start_in = |excerpt_id: ExcerptId| {
+            active_match_start.filter(|start| start.excerpt_id == excerpt_id)
+        };
+        let mut matches_by_excerpt = HashMap::<ExcerptId, Vec<Range<Anchor>>>::default();
+        if max_line_length.is_some() {
+            for range in match_ranges.iter() {
+                matches_by_excerpt
+                    .entry(range.start.excerpt_id)
+                    .or_default()
+                    .push(range.clone());
+            }
+        }
+
+        let line_truncations = &mut self.line_truncations;
+        self.results_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut stale_folds = Vec::new();
+            line_truncations.retain(|excerpt_id, truncation| {
+                let is_current = matches_by_excerpt.get(excerpt_id).map_or(false, |matches| {
+                    truncation.match_count == matches.len()
+                        && truncation.active_match_start == active_match_start_in(*excerpt_id)
+                });
+                // The folds of removed excerpts were removed along with them.
+                if !is_current && snapshot.buffer_id_for_excerpt(*excerpt_id).is_some() {
+                    stale_folds.append(&mut truncation.folds);
+                }
+                is_current
+            });
+            // Unfolding a truncation inside a fold of the user would remove that fold as well.
+            let display_snapshot = editor.snapshot(cx);
+            stale_folds.retain(|range| {
+                let offsets = range.to_offset(&snapshot);
+                display_snapshot
+                    .folds_in_range(range.clone())
+                    .all(|fold| fold.range.to_offset(&snapshot) == offsets)
+            });
+            editor.unfold_ranges(stale_folds, false, false, cx);
+            let Some(max_line_length) = max_line_length else {
+                return;
+            };
+
+            let display_snapshot = editor.snapshot(cx);
+            let mut folds_to_add = Vec::new();
+            for (excerpt_id, matches) in matches_by_excerpt {
+                let truncation = line_truncations.entry(excerpt_id).or_insert_with(|| {
+                    let active_match_start = active_match_start_in(excerpt_id);
+                    LineTruncation {
+                        match_count: matches.len(),
+                        active_match_start,
+                        folds: line_truncation_folds(
+                            &matches,
+                            active_match_start,
+                            max_line_length,
+                            &snapshot,
+                        ),
+                    }
+                });
+                folds_to_add.extend(
+                    truncation
+                        .folds
+                        .iter()
+                        .filter(|range| {
+                            let offsets = range.to_offset(&snapshot);
+                            !display_snapshot
+                                .folds_in_range((*range).clone())
+                                .any(|fold| fold.range.to_offset(&snapshot) == offsets)
+                        })
+                        .cloned(),
+                );
+            }
+            editor.fold_ranges(folds_to_add, false, cx);
+        });
+    }
+
+    fn update_match_index(&mut self, cx: &mut ViewContext<Self>) {
+        let results_editor = self.results_editor.read(cx);
+        let new_index = active_match_index(
+            &self.model.read(cx).match_ranges,
+            &results_editor.selections.newest_anchor().head(),
+            &results_editor.buffer().read(cx).snapshot(cx),
+        );
+        if self.active_match_index != new_index {
+            self.active_match_index = new_index;
+            self.record_visited_match();
+            if let Some(row_ix) = new_index.and_then(|index| self.results_list_row_index(index, cx))
+            {
+                self.results_list_scroll_handle.scroll_to_item(row_ix);
+            }
+            self.truncate_long_lines(cx);
+            self.highlight_active_match(cx);
+            cx.notify();
+        }
+    }
+
+    /// Paints the active match over the highlights shared by all matches.
+    fn highlight_active_match(&mut self, cx: &mut ViewContext<Self>) {
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let active_match = self
+            .active_match_index
+            .and_then(|index| match_ranges.get(index).cloned());
+        let active_match_index = self.active_match_index;
+        self.results_editor.update(cx, |editor, cx| {
+            match active_match {
+                Some(active_match) => editor.highlight_background::<ActiveMatchHighlight>(
+                    &[active_match],
+                    |theme| theme.search_active_match_background,
+                    cx,
+                ),
+                None => {
+                    editor.clear_background_highlights::<ActiveMatchHighlight>(cx);
+                }
+            }
+            if match_ranges.is_empty() {
+                editor.clear_scrollbar_match_markers(cx);
+            } else {
+                editor.set_scrollbar_match_markers(match_ranges.into(), active_match_index, cx);
+            }
+        });
+    }
+
+    fn highlight_excluded_matches(&mut self, cx: &mut ViewContext<Self>) {
+        let excluded_ranges = self
+            .model
+            .read(cx)
+            .match_ranges
+            .iter()
+            .filter(|range| self.excluded_matches.contains(&match_buffer_range(range)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let style = HighlightStyle {
+            color: Some(cx.theme().colors().text_disabled),
+            ..HighlightStyle::default()
+        };
+        self.results_editor.update(cx, |editor, cx| {
+            if excluded_ranges.is_empty() {
+                editor.clear_highlights::<ExcludedMatchHighlight>(cx);
+            } else {
+                editor.highlight_text::<ExcludedMatchHighlight>(excluded_ranges, style, cx);
+            }
+        });
+    }
+
+    /// Adds the active match to the match history, dropping the matches that were navigated
+    /// back from. Going back and forth through the history itself leaves it as it is.
+    fn record_visited_match(&mut self) {
+        let Some(index) = self.active_match_index else {
+            return;
+        };
+        if self.match_history.get(self.match_history_position) == Some(&index) {
+            return;
+        }
+        self.match_history
+            .truncate((self.match_history_position + 1).min(self.match_history.len()));
+        self.match_history.push(index);
+        if self.match_history.len() > MAX_MATCH_HISTORY_LEN {
+            self.match_history.remove(0);
+        }
+        self.match_history_position = self.match_history.len() - 1;
+    }
+
+    /// Selects the previously or next visited match of the match history.
+    fn navigate_match_history(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let position = match direction {
+            Direction::Prev => self.match_history_position.checked_sub(1),
+            Direction::Next => Some(self.match_history_position + 1),
+        };
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        let Some((position, range)) = position.and_then(|position| {
+            let index = *self.match_history.get(position)?;
+            Some((position, match_ranges.get(index)?))
+        }) else {
+            return;
+        };
+        self.match_history_position = position;
+        self.select_match_range(range, cx);
+    }
+
+    /// Bookmarks the line of the active match, or removes its bookmark when it has one.
+    fn toggle_match_bookmarked(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let Some(range) = self
+            .active_match_index
+            .and_then(|index| model.match_ranges.get(index))
+        else {
+            return;
+        };
+        let Some(buffer) = range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| model.excerpts.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        let anchor = range.start.text_anchor;
+        let snapshot = buffer.read(cx).snapshot();
+        let row = anchor.to_point(&snapshot).row;
+        let bookmarked_index = self.bookmarks.iter().position(|bookmark| {
+            bookmark.buffer == buffer && bookmark.anchor.to_point(&snapshot).row == row
+        });
+        if let Some(index) = bookmarked_index {
+            self.bookmarks.remove(index);
+            self.active_bookmark = None;
+        } else {
+            self.bookmarks.push(Bookmark { buffer, anchor });
+            self.active_bookmark = Some(self.bookmarks.len() - 1);
+        }
+        cx.notify();
+    }
+
+    /// Drops the bookmarks whose line was deleted.
+    fn prune_bookmarks(&mut self, cx: &AppContext) {
+        let bookmark_count = self.bookmarks.len();
+        self.bookmarks
+            .retain(|bookmark| bookmark.anchor.is_valid(bookmark.buffer.read(cx)));
+        if self.bookmarks.len() != bookmark_count {
+            self.active_bookmark = None;
+        }
+    }
+
+    fn navigate_bookmarks(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        self.prune_bookmarks(cx);
+        let bookmark_count = self.bookmarks.len();
+        if bookmark_count == 0 {
+            return;
+        }
+        let index = match (self.active_bookmark, direction) {
+            (Some(index), direction) => cycled_index(index, bookmark_count, direction),
+            (None, Direction::Next) => 0,
+            (None, Direction::Prev) => bookmark_count - 1,
+        };
+        self.go_to_bookmark(index, cx);
+    }
+
+    /// Selects the bookmarked line in the results, or opens its file when the results don't
+    /// show it anymore.
+    fn go_to_bookmark(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(bookmark) = self.bookmarks.get(index) else {
+            return;
+        };
+        self.active_bookmark = Some(index);
+        let buffer = bookmark.buffer.clone();
+        let anchor = bookmark.anchor;
+        let excerpts = self.model.read(cx).excerpts.clone();
+        let position = {
+            let snapshot = buffer.read(cx).snapshot();
+            let excerpts = excerpts.read(cx);
+            excerpts
+                .excerpts_for_buffer(&buffer, cx)
+                .into_iter()
+                .find(|(_, range)| {
+                    range.context.start.cmp(&anchor, &snapshot).is_le()
+                        && range.context.end.cmp(&anchor, &snapshot).is_ge()
+                })
+                .and_then(|(excerpt_id, _)| {
+                    excerpts.snapshot(cx).anchor_in_excerpt(excerpt_id, anchor)
+                })
+        };
+        cx.notify();
+        if let Some(position) = position {
+            self.results_editor.update(cx, |editor, cx| {
+                editor.unfold_ranges([position..position], false, true, cx);
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([position..position])
+                });
+            });
+            self.focus_results_editor(cx);
+            return;
+        }
+        let Some(workspace) = self.workspace.as_ref().and_then(WeakView::upgrade) else {
+            return;
+        };
+        cx.defer(move |_, cx| {
+            workspace.update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([anchor..anchor])
+                    });
+                });
+            });
+        });
+    }
+
+    fn render_bookmarks(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                Label::new("Bookmarks")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .children(self.bookmarks.iter().enumerate().map(|(ix, bookmark)| {
+                let buffer = bookmark.buffer.read(cx);
+                let row = bookmark.anchor.to_point(&buffer.snapshot()).row;
+                let path = buffer
+                    .file()
+                    .map(|file| file.path().to_path_buf())
+                    .unwrap_or_default();
+                let file_name = path
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "untitled".to_string());
+                let tooltip = SharedString::from(format!("{}:{}", path.display(), row + 1));
+                Button::new(
+                    ("project-search-bookmark", ix),
+                    format!("{file_name}:{}", row + 1),
+                )
+                .label_size(LabelSize::Small)
+                .selected(self.active_bookmark == Some(ix))
+                .tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+                .on_click(cx.listener(move |this, _, cx| this.go_to_bookmark(ix, cx)))
+            }))
+    }
+
+    /// Excludes the active match from the ones that replacing goes through, or includes it back.
+    fn toggle_match_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(range) = self
+            .active_match_index
+            .and_then(|index| self.model.read(cx).match_ranges.get(index))
+            .map(match_buffer_range)
+        else {
+            return;
+        };
+        if !self.excluded_matches.remove(&range) {
+            self.excluded_matches.insert(range);
+        }
+        self.highlight_excluded_matches(cx);
+        cx.notify();
+    }
+
+    /// Whether replacing skips the match at `index` in the model's `match_ranges`.
+    fn is_match_excluded(&self, index: usize, cx: &AppContext) -> bool {
+        self.model
+            .read(cx)
+            .match_ranges
+            .get(index)
+            .map_or(false, |range| {
+                self.excluded_matches.contains(&match_buffer_range(range))
+            })
+    }
+
+    pub fn has_matches(&self) -> bool {
+        self.active_match_index.is_some()
+    }
+
+    /// The matches of the displayed results, see [`ProjectSearch::structured_matches`].
+    pub fn structured_matches(&self, cx: &AppContext) -> Vec<MatchInfo> {
+        self.model.read(cx).structured_matches(cx)
+    }
+
+    fn has_results_to_filter(&self, cx: &AppContext) -> bool {
+        !self.model.read(cx).buffer_matches.is_empty()
+    }
+
+    fn landing_text_minor(&self) -> SharedString {
+        "Include/exclude specific paths with the filter option. Matching exact word and/or casing is available too.".into()
+    }
+
+    fn border_color_for(&self, panel: InputPanel, cx: &WindowContext) -> Hsla {
+        if self.panels_with_errors.contains(&panel) {
+            Color::Error.color(cx)
+        } else {
+            cx.theme().colors().border
+        }
+    }
+
+    fn move_focus_to_results(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.results_editor.focus_handle(cx).is_focused(cx)
+            && !self.model.read(cx).match_ranges.is_empty()
+        {
+            cx.stop_propagation();
+            return self.focus_results_editor(cx);
+        }
+    }
+}
+
+/// Returns the index next to `index` in `direction` among `len` items, wrapping around at both ends.
+fn cycled_index(index: usize, len: usize, direction: Direction) -> usize {
+    match direction {
+        Direction::Next => (index + 1) % len,
+        Direction::Prev => index.checked_sub(1).unwrap_or(len - 1),
+    }
+}
+
+impl ProjectSearchBar {
+    pub fn new() -> Self {
+        Self {
+            active_project_search: None,
+            subscription: None,
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                if !search_view
+                    .replacement_editor
+                    .focus_handle(cx)
+                    .is_focused(cx)
+                {
+                    cx.stop_propagation();
+                    let query = search_view.query_editor.read(cx).text(cx);
+                    let query_changed =
+                        search_view.model.read(cx).last_search_query_text.as_ref() != Some(&query);
+                    if query_changed
+                        || search_view.model.read(cx).search_mode != SearchMode::Filename
+                        || !search_view.open_selected_filename_match(cx)
+                    {
+                        search_view.search(cx);
+                    }
+                }
+            });
+        }
+    }
+
+    fn tab(&mut self, _: &editor::actions::Tab, cx: &mut ViewContext<Self>) {
+        self.cycle_field(Direction::Next, cx);
+    }
+
+    fn tab_previous(&mut self, _: &editor::actions::TabPrev, cx: &mut ViewContext<Self>) {
+        self.cycle_field(Direction::Prev, cx);
+    }
+
+    fn focus_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.query_editor.focus_handle(cx).focus(cx);
+            });
+        }
+    }
+
+    fn cycle_field(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let active_project_search = match &self.active_project_search {
+            Some(active_project_search) => active_project_search,
+
+            None => {
+                return;
+            }
+        };
+
+        active_project_search.update(cx, |project_view, cx| {
+            let mut views = vec![&project_view.query_editor];
+            if project_view.replace_enabled && project_view.can_replace(cx) {
+                views.push(&project_view.replacement_editor);
+            }
+            if project_view.filters_enabled {
+                views.extend([
+                    &project_view.included_files_editor,
+                    &project_view.excluded_files_editor,
+                ]);
+            }
+            if project_view.has_results_to_filter(cx) {
+                views.push(&project_view.filter_editor);
+            }
+            let current_index = match views
+                .iter()
+                .enumerate()
+                .find(|(_, view)| view.focus_handle(cx).is_focused(cx))
+            {
+                Some((index, _)) => index,
+                None => return,
+            };
+
+            let new_index = cycled_index(current_index, views.len(), direction);
+            let next_focus_handle = views[new_index].focus_handle(cx);
+            cx.focus(&next_focus_handle);
+            cx.stop_propagation();
+        });
+    }
+
+    fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_search_option(option, cx);
+                search_view.schedule_search(cx);
+            });
+
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn toggle_replace(&mut self, _: &ToggleReplace, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |this, cx| {
+                this.replace_enabled = !this.replace_enabled;
+                let editor_to_focus = if this.replace_enabled {
+                    this.replacement_editor.focus_handle(cx)
+                } else {
+                    this.query_editor.focus_handle(cx)
+                };
+                cx.focus(&editor_to_focus);
+                cx.notify();
+            });
+        }
+    }
+
+    fn copy_matches(&mut self, active_match_only: bool, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.copy_matches(active_match_only, cx);
+            });
+        }
+    }
+
+    fn export_results(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.export_results(cx));
+        }
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_replace_preview(cx));
+            cx.notify();
+        }
+    }
+
+    fn replace_all_in_file(&mut self, action: &ReplaceAllInFile, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.replace_all_in_file(action, cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_match_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_match_excluded(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_match_bookmarked(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_match_bookmarked(cx)
+            });
+            cx.notify();
+        }
+    }
+
+    fn navigate_bookmarks(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.navigate_bookmarks(direction, cx)
+            });
+            cx.notify();
+        }
+    }
+
+    fn navigate_match_history(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.navigate_match_history(direction, cx)
+            });
+            cx.notify();
+        }
+    }
+
+    fn swap_with_previous_query(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.swap_with_previous_query(cx)
+            });
+            cx.notify();
+        }
+    }
+
+    fn open_results_in_split(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.open_results_in_split(cx));
+        }
+    }
+
+    fn toggle_results_list_view(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_results_list_view(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_group_by_directory(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_group_by_directory(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn go_to_match(&mut self, action: &GoToMatch, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| match action.number {
+                Some(number) => search_view.go_to_match(number, cx),
+                None => search_view.deploy_go_to_match(cx),
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_pin_results(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_pin_results(cx));
+            cx.notify();
+        }
+    }
+
+    fn exclude_current_file(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.exclude_current_file(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn reveal_active_match_in_project_panel(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.reveal_active_match_in_project_panel(cx);
+            });
+        }
+    }
+
+    fn swap_include_exclude(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.swap_include_exclude(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_multi_pattern(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.multi_pattern = !search_view.multi_pattern;
+                search_view.schedule_search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_invert_match(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.invert_match = !search_view.invert_match;
+                search_view.schedule_search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn reset_search_options(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.reset_search_options(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_case_preserving_replace(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.case_preserving_replace = !search_view.case_preserving_replace;
+                cx.notify();
+            });
+            cx.notify();
+        }
+    }
+
+    fn collapse_all_groups(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.collapse_all_groups(cx));
+        }
+    }
+
+    fn expand_all_groups(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.expand_all_groups(cx));
+        }
+    }
+
+    fn cycle_scope(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.cycle_scope(cx));
+            cx.notify();
+        }
+    }
+
+    fn search_within_results(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.search_within_results(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_search_in_selection(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_search_in_selection(cx)
+            });
+            cx.notify();
+        }
+    }
+
+    fn cycle_symbol_kind_filter(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.cycle_symbol_kind_filter(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn cycle_symbol_scope(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.cycle_symbol_scope(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_semantic_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_semantic_search(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_filename_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_filename_search(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_hex_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_hex_search(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_live_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_live_search(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_treat_query_as_path(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_treat_query_as_path(cx);
+                search_view.search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_include_dependencies(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_include_dependencies(cx);
+                search_view.schedule_search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_sort_order(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_sort_order(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_default_excludes(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_default_excludes(cx);
+                search_view.schedule_search(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_multiline(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_multiline(cx));
+            cx.notify();
+        }
+    }
+
+    fn stop_search(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view
+                    .model
+                    .update(cx, |model, cx| model.stop_search(cx));
+            });
+            cx.notify();
+        }
+    }
+
+    fn change_context_lines(
+        &mut self,
+        before_delta: i32,
+        after_delta: i32,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.change_context_lines(before_delta, after_delta, cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_filters(cx);
+                search_view
+                    .included_files_editor
+                    .update(cx, |_, cx| cx.notify());
+                search_view
+                    .excluded_files_editor
+                    .update(cx, |_, cx| cx.notify());
+                cx.refresh();
+                cx.notify();
+            });
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn move_focus_to_results(&self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.move_focus_to_results(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn is_option_enabled(&self, option: SearchOptions, cx: &AppContext) -> bool {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.read(cx).search_options.contains(option)
+        } else {
+            false
+        }
+    }
+
+    fn next_history_query(&mut self, _: &NextHistoryQuery, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                let new_query = search_view.model.update(cx, |model, cx| {
+                    if let Some(new_query) = model.project.update(cx, |project, _| {
+                        project
+                            .search_history_mut()
+                            .next(&mut model.search_history_cursor)
+                            .map(str::to_string)
+                    }) {
+                        new_query
+                    } else {
+                        model.search_history_cursor.reset();
+                        String::new()
+                    }
+                });
+                search_view.set_query(&new_query, cx);
+            });
+        }
+    }
+
+    fn previous_history_query(&mut self, _: &PreviousHistoryQuery, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                if search_view.query_editor.read(cx).text(cx).is_empty() {
+                    if let Some(new_query) = search_view
+                        .model
+                        .read(cx)
+                        .project
+                        .read(cx)
+                        .search_history()
+                        .current(&search_view.model.read(cx).search_history_cursor)
+                        .map(str::to_string)
+                    {
+                        search_view.set_query(&new_query, cx);
+                        return;
+                    }
+                }
+
+                if let Some(new_query) = search_view.model.update(cx, |model, cx| {
+                    model.project.update(cx, |project, _| {
+                        project
+                            .search_history_mut()
+                            .previous(&mut model.search_history_cursor)
+                            .map(str::to_string)
+                    })
+                }) {
+                    search_view.set_query(&new_query, cx);
+                }
+            });
+        }
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.update(cx, |this, cx| {
+                this.select_match(Direction::Next, cx);
+            })
+        }
+    }
+
+    fn select_prev_match(&mut self, _: &SelectPrevMatch, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.update(cx, |this, cx| {
+                this.select_match(Direction::Prev, cx);
+            })
+        }
+    }
+
+    fn peek_match(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.active_project_search.as_ref() {
+            search.update(cx, |this, cx| {
+                this.peek_match(direction, cx);
+            })
+        }
+    }
+
+    fn render_text_input(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: if editor.read(cx).read_only(cx) {
+                cx.theme().colors().text_disabled
+            } else {
+                cx.theme().colors().text
+            },
+            font_family: settings.buffer_font.family.clone(),
+            font_features: settings.buffer_font.features,
+            font_size: rems(0.875).into(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+
+        EditorElement::new(
+            &editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for ProjectSearchBar {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(search_view) = self.active_project_search.clone() else {
+            return div();
+        };
+        let search = search_view.read(cx);
+        let recent_queries = search.recent_queries(cx);
+
+        let query_error = search.query_error.clone();
+        let query_column = h_flex()
+            .id("project-search-query")
+            .flex_1()
+            .when(!search.multiline_query, |this| this.h_8())
+            .mr_2()
+            .px_2()
+            .py_1()
+            .border_1()
+            .border_color(search.border_color_for(InputPanel::Query, cx))
+            .rounded_lg()
+            .min_w(rems(MIN_INPUT_WIDTH_REMS))
+            .max_w(rems(MAX_INPUT_WIDTH_REMS))
+            .on_action(cx.listener(|this, action, cx| this.confirm(action, cx)))
+            .on_action(cx.listener(|this, action, cx| this.previous_history_query(action, cx)))
+            .on_action(cx.listener(|this, action, cx| this.next_history_query(action, cx)))
+            .when_some(query_error, |this, query_error| {
+                this.tooltip(move |cx| Tooltip::text(query_error.clone(), cx))
+            })
+            .child(self.render_text_input(&search.query_editor, cx))
+            .when(search.slow_regex_warning, |this| {
+                this.child(
+                    div()
+                        .id("project-search-slow-regex-warning")
+                        .child(
+                            Icon::new(IconName::ExclamationTriangle)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                        .tooltip(|cx| Tooltip::text("This pattern may be slow", cx)),
+                )
+            })
+            .when_some(search.regex_preview.clone(), |this, regex_preview| {
+                this.child(
+                    Label::new(regex_preview)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                )
+            })
+            .when(!recent_queries.is_empty(), |this| {
+                let search_view = search_view.downgrade();
+                this.child(
+                    popover_menu("project-search-recent-queries")
+                        .trigger(
+                            IconButton::new(
+                                "project-search-recent-queries-trigger",
+                                IconName::ChevronDown,
+                            )
+                            .tooltip(|cx| Tooltip::text("Recent searches", cx)),
+                        )
+                        .menu(move |cx| {
+                            Some(ProjectSearchView::recent_queries_menu(
+                                search_view.clone(),
+                                recent_queries.clone(),
+                                cx,
+                            ))
+                        }),
+                )
+            })
+            .child(
+                h_flex()
+                    .child(SearchOptions::CASE_SENSITIVE.as_button(
+                        self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                        }),
+                    ))
+                    .child(SearchOptions::WHOLE_WORD.as_button(
+                        self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                        }),
+                    ))
+                    .child(SearchOptions::REGEX.as_button(
+                        self.is_option_enabled(SearchOptions::REGEX, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::REGEX, cx);
+                        }),
+                    ))
+                    .when(self.is_option_enabled(SearchOptions::REGEX, cx), |this| {
+                        this.child(SearchOptions::DOT_MATCHES_NEW_LINE.as_flag_button(
+                            self.is_option_enabled(SearchOptions::DOT_MATCHES_NEW_LINE, cx),
+                            cx.listener(|this, _, cx| {
+                                this.toggle_search_option(SearchOptions::DOT_MATCHES_NEW_LINE, cx);
+                            }),
+                        ))
+                        .child(
+                            SearchOptions::MULTI_LINE_ANCHORS.as_flag_button(
+                                self.is_option_enabled(SearchOptions::MULTI_LINE_ANCHORS, cx),
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_search_option(
+                                        SearchOptions::MULTI_LINE_ANCHORS,
+                                        cx,
+                                    );
+                                }),
+                            ),
+                        )
+                    })
+                    .child(SearchOptions::INCLUDE_IGNORED.as_button(
+                        self.is_option_enabled(SearchOptions::INCLUDE_IGNORED, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
+                        }),
+                    ))
+                    .when(!self.is_option_enabled(SearchOptions::REGEX, cx), |this| {
+                        this.child(
+                            IconButton::new("project-search-multi-pattern", IconName::Split)
+                                .selected(search.multi_pattern)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.toggle_multi_pattern(cx);
+                                }))
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Match any of the patterns separated by |",
+                                        &ToggleMultiPattern,
+                                        cx,
+                                    )
+                                }),
+                        )
+                    })
+                    .child(
+                        IconButton::new("project-search-invert-match", IconName::Dash)
+                            .selected(search.invert_match)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_invert_match(cx);
+                            }))
+                            .tooltip(|cx| {
+                                Tooltip::for_action(
+                                    "Show lines not matching the query",
+                                    &ToggleInvertMatch,
+                                    cx,
+                                )
+                            }),
+                    )
+                    .child(
+                        IconButton::new("project-search-multiline", IconName::Return)
+                            .selected(search.multiline_query)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_multiline(cx);
+                            }))
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Toggle multiline query", &ToggleMultiline, cx)
+                            }),
+                    )
+                    .child(
+                        IconButton::new("project-search-default-excludes", IconName::FolderX)
+                            .selected(search.default_excludes_enabled)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_default_excludes(cx);
+                            }))
+                            .tooltip(|cx| {
+                                Tooltip::for_action(
+                                    "Toggle default excludes",
+                                    &ToggleDefaultExcludes,
+                                    cx,
+                                )
+                            }),
+                    ),
+            );
+
+        let mode_column = v_flex().items_start().justify_start().child(
+            h_flex()
+                .child(
+                    IconButton::new("project-search-filter-button", IconName::Filter)
+                        .tooltip(|cx| Tooltip::for_action("Toggle filters", &ToggleFilters, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_filters(cx);
+                        }))
+                        .selected(
+                            self.active_project_search
+                                .as_ref()
+                                .map(|search| search.read(cx).filters_enabled)
+                                .unwrap_or_default(),
+                        ),
+                )
+                .child(
+                    IconButton::new("project-search-toggle-replace", IconName::Replace)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_replace(&ToggleReplace, cx);
+                        }))
+                        .selected(
+                            self.active_project_search
+                                .as_ref()
+                                .map(|search| search.read(cx).replace_enabled)
+                                .unwrap_or_default(),
+                        )
+                        .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
+                )
+                .child(
+                    IconButton::new("project-search-results-list", IconName::Menu)
+                        .selected(search.results_as_list)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_results_list_view(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Toggle results list view",
+                                &ToggleResultsListView,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-group-by-directory", IconName::Folder)
+                        .selected(search.group_by_directory)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_group_by_directory(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Group results by directory",
+                                &ToggleGroupByDirectory,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-open-results-in-split", IconName::Split)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.open_results_in_split(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Open results in split", &OpenResultsInSplit, cx)
+                        }),
+                )
+                .child(
+                    Button::new("project-search-pin-results", "Pin")
+                        .selected(search.results_pinned)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_pin_results(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::with_meta(
+                                "Pin results",
+                                Some(&PinResults),
+                                "Searches of a pinned tab open in a new tab",
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    Button::new("project-search-todos", "TODOs")
+                        .on_click(cx.listener(|this, _, cx| {
+                            if let Some(search) = this.active_project_search.as_ref() {
+                                search.update(cx, |search, cx| search.search_todos_in_view(cx));
+                            }
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Search for TODO comments", &SearchTodos, cx)
+                        }),
+                )
+                .child(
+                    Button::new("project-search-scope", search.scope.label())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.cycle_scope(cx);
+                        }))
+                        .disabled(search.available_scopes(cx).len() <= 1)
+                        .tooltip(|cx| Tooltip::for_action("Change search scope", &CycleScope, cx)),
+                )
+                .child(
+                    Button::new("project-search-sort-order", search.sort_order.label())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_sort_order(cx);
+                        }))
+                        .disabled(search.search_mode != SearchMode::Text)
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Change the order of the results",
+                                &ToggleSortOrder,
+                                cx,
+                            )
+                        }),
+                )
+                .when(cx.has_global::<SemanticIndex>(), |this| {
+                    this.child(
+                        Button::new("project-search-semantic", "Semantic")
+                            .selected(search.search_mode == SearchMode::Semantic)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_semantic_search(cx);
+                            }))
+                            .tooltip(|cx| {
+                                Tooltip::for_action(
+                                    "Rank results by similarity to the query",
+                                    &ToggleSemanticSearch,
+                                    cx,
+                                )
+                            }),
+                    )
+                })
+                .child(
+                    Button::new("project-search-filename", "Files")
+                        .selected(search.search_mode == SearchMode::Filename)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_filename_search(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Match the query against file paths",
+                                &ToggleFilenameSearch,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    Button::new("project-search-hex", "Hex")
+                        .selected(search.search_mode == SearchMode::Hex)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_hex_search(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Match the query as hex bytes against raw file contents",
+                                &ToggleHexSearch,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    Button::new("project-search-path", "Path")
+                        .selected(search.treat_query_as_path)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_treat_query_as_path(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Match the query as a glob against file paths",
+                                &ToggleTreatQueryAsPath,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    Button::new("project-search-live", "Live")
+                        .selected(search.live_search)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_live_search(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Search as you type", &ToggleLiveSearch, cx)
+                        }),
+                ),
+        );
+
+        let is_searching = search.model.read(cx).pending_search.is_some();
+        let match_text = search
+            .active_match_index
+            .and_then(|index| {
+                let index = index + 1;
+                let match_quantity = search.model.read(cx).total_match_count;
+                if match_quantity > 0 {
+                    debug_assert!(match_quantity >= index);
+                    Some(format!("{index}/{match_quantity}").to_string())
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_else(|| "0/0".to_string());
+
+        let limit_reached = search.model.read(cx).limit_reached;
+        let truncated_label = search.model.read(cx).truncated_label(cx);
+        let stopped_label = search.model.read(cx).stopped_label();
+        let duration_label = search.model.read(cx).duration_label();
+
+        let matches_column = h_flex()
+            .child(
+                IconButton::new("project-search-prev-match", IconName::ChevronLeft)
+                    .disabled(search.active_match_index.is_none())
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(search) = this.active_project_search.as_ref() {
+                            search.update(cx, |this, cx| {
+                                this.select_match(Direction::Prev, cx);
+                            })
+                        }
+                    }))
+                    .tooltip(|cx| {
+                        Tooltip::for_action("Go to previous match", &SelectPrevMatch, cx)
+                    }),
+            )
+            .child(
+                IconButton::new("project-search-next-match", IconName::ChevronRight)
+                    .disabled(search.active_match_index.is_none())
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(search) = this.active_project_search.as_ref() {
+                            search.update(cx, |this, cx| {
+                                this.select_match(Direction::Next, cx);
+                            })
+                        }
+                    }))
+                    .tooltip(|cx| Tooltip::for_action("Go to next match", &SelectNextMatch, cx)),
+            )
+            .child(
+                h_flex()
+                    .min_w(rems_from_px(40.))
+                    .child(
+                        div()
+                            .id("project-search-match-index")
+                            .when(search.active_match_index.is_some(), |this| {
+                                this.cursor_pointer().tooltip(|cx| {
+                                    Tooltip::text(
+                                        "Go to the first match, or the last one with shift",
+                                        cx,
+                                    )
+                                })
+                            })
+                            // Numbers past the match count are clamped to the last match.
+                            .on_click(cx.listener(|this, event: &ClickEvent, cx| {
+                                if let Some(search) = this.active_project_search.as_ref() {
+                                    let number = if event.down.modifiers.shift {
+                                        usize::MAX
+                                    } else {
+                                        1
+                                    };
+                                    search.update(cx, |search, cx| search.go_to_match(number, cx));
+                                }
+                            }))
+                            .child(Label::new(match_text).color(
+                                if search.end_of_results_flash.is_some() {
+                                    Color::Warning
+                                } else if search.active_match_index.is_some() {
+                                    Color::Default
+                                } else {
+                                    Color::Disabled
+                                },
+                            )),
+                    )
+                    .when(is_searching, |this| {
+                        this.child(
+                            Icon::new(IconName::ArrowCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Muted)
+                                .with_animation(
+                                    "project-search-in-progress",
+                                    Animation::new(Duration::from_secs(2)).repeat(),
+                                    |icon, delta| {
+                                        icon.transform(Transformation::rotate(percentage(delta)))
+                                    },
+                                ),
+                        )
+                        .child(
+                            IconButton::new("project-search-stop", IconName::XCircle)
+                                .on_click(cx.listener(|this, _, cx| this.stop_search(cx)))
+                                .tooltip(|cx| Tooltip::for_action("Stop search", &StopSearch, cx)),
+                        )
+                    }),
+            )
+            .when_some(search.match_summary.clone(), |this, match_summary| {
+                this.child(
+                    div()
+                        .child(Label::new(match_summary).color(Color::Muted))
+                        .ml_2(),
+                )
+            })
+            .when_some(duration_label, |this, duration_label| {
+                this.child(
+                    div()
+                        .child(Label::new(duration_label).color(Color::Muted))
+                        .ml_2(),
+                )
+            })
+            .when_some(stopped_label, |this, stopped_label| {
+                this.child(
+                    div()
+                        .child(Label::new(stopped_label).color(Color::Muted))
+                        .ml_2(),
+                )
+            })
+            .when(limit_reached, |this| {
+                this.child(
+                    div()
+                        .child(Label::new("Search limit reached").color(Color::Warning))
+                        .ml_2(),
+                )
+            })
+            .when_some(truncated_label, |this, truncated_label| {
+                this.child(
+                    div()
+                        .child(Label::new(truncated_label).color(Color::Warning))
+                        .ml_2(),
+                )
+            });
+
+        let search_line = h_flex()
+            .flex_1()
+            .child(query_column)
+            .child(mode_column)
+            .child(matches_column);
+
+        let replace_line = (search.replace_enabled && search.can_replace(cx)).then(|| {
+            let replace_column = h_flex()
+                .flex_1()
+                .min_w(rems(MIN_INPUT_WIDTH_REMS))
+                .max_w(rems(MAX_INPUT_WIDTH_REMS))
+                .h_8()
+                .px_2()
+                .py_1()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .rounded_lg()
+                .child(self.render_text_input(&search.replacement_editor, cx));
+            let replace_actions = h_flex().when(search.replace_enabled, |this| {
+                this.child(
+                    IconButton::new(
+                        "project-search-case-preserving-replace",
+                        IconName::CaseSensitive,
+                    )
+                    .selected(search.case_preserving_replace)
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.toggle_case_preserving_replace(cx);
+                    }))
+                    .tooltip(|cx| {
+                        Tooltip::for_action(
+                            "Preserve the case of replaced matches",
+                            &ToggleCasePreservingReplace,
+                            cx,
+                        )
+                    }),
+                )
+                .child(
+                    IconButton::new("project-search-replace-next", IconName::ReplaceNext)
+                        .on_click(cx.listener(|this, _, cx| {
+                            if let Some(search) = this.active_project_search.as_ref() {
+                                search.update(cx, |this, cx| {
+                                    this.replace_next(&ReplaceNext, cx);
+                                })
+                            }
+                        }))
+                        .tooltip(|cx| Tooltip::for_action("Replace next match", &ReplaceNext, cx)),
+                )
+                .child(
+                    IconButton::new("project-search-replace-all", IconName::ReplaceAll)
+                        .on_click(cx.listener(|this, _, cx| {
+                            if let Some(search) = this.active_project_search.as_ref() {
+                                search.update(cx, |this, cx| {
+                                    this.replace_all(&ReplaceAll, cx);
+                                })
+                            }
+                        }))
+                        .tooltip(|cx| Tooltip::for_action("Replace all matches", &ReplaceAll, cx)),
+                )
+                .child(
+                    Button::new("project-search-replace-all-in-file", "In File")
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.replace_all_in_file(&ReplaceAllInFile, cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Replace the matches in the file of the active match",
+                                &ReplaceAllInFile,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    Button::new("project-search-preview-replace", "Preview")
+                        .selected(search.replace_preview)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_replace_preview(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Preview the replacements before replacing all matches",
+                                &PreviewReplace,
+                                cx,
+                            )
+                        }),
+                )
+            });
+            h_flex()
+                .gap_2()
+                .child(replace_column)
+                .child(replace_actions)
+        });
+
+        let filter_line = search.filters_enabled.then(|| {
+            h_flex()
+                .w_full()
+                .gap_2()
+                .child(
+                    h_flex()
+                        .id("project-search-included-files")
+                        .flex_1()
+                        .min_w(rems(MIN_INPUT_WIDTH_REMS))
+                        .max_w(rems(MAX_INPUT_WIDTH_REMS))
+                        .h_8()
+                        .px_2()
+                        .py_1()
+                        .border_1()
+                        .border_color(search.border_color_for(InputPanel::Include, cx))
+                        .rounded_lg()
+                        .when_some(
+                            search.glob_errors.get(&InputPanel::Include).cloned(),
+                            |this, error| this.tooltip(move |cx| Tooltip::text(error.clone(), cx)),
+                        )
+                        .child(self.render_text_input(&search.included_files_editor, cx)),
+                )
+                .child(
+                    IconButton::new("project-search-swap-include-exclude", IconName::ArrowRight)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.swap_include_exclude(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Swap included and excluded files",
+                                &SwapIncludeExclude,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    h_flex()
+                        .id("project-search-excluded-files")
+                        .flex_1()
+                        .min_w(rems(MIN_INPUT_WIDTH_REMS))
+                        .max_w(rems(MAX_INPUT_WIDTH_REMS))
+                        .h_8()
+                        .px_2()
+                        .py_1()
+                        .border_1()
+                        .border_color(search.border_color_for(InputPanel::Exclude, cx))
+                        .rounded_lg()
+                        .when_some(
+                            search.glob_errors.get(&InputPanel::Exclude).cloned(),
+                            |this, error| this.tooltip(move |cx| Tooltip::text(error.clone(), cx)),
+                        )
+                        .child(self.render_text_input(&search.excluded_files_editor, cx)),
+                )
+                .when(
+                    search
+                        .model
+                        .read(cx)
+                        .project
+                        .read(cx)
+                        .visible_worktrees(cx)
+                        .count()
+                        > 1,
+                    |this| {
+                        let search_view = search_view.downgrade();
+                        this.child(
+                            popover_menu("project-search-worktree-filter")
+                                .trigger(Button::new(
+                                    "project-search-worktree-filter-trigger",
+                                    search.worktree_filter_label(cx),
+                                ))
+                                .menu(move |cx| {
+                                    Some(ProjectSearchView::worktree_filter_menu(
+                                        search_view.clone(),
+                                        cx,
+                                    ))
+                                }),
+                        )
+                    },
+                )
+                .child({
+                    let search_view = search_view.downgrade();
+                    popover_menu("project-search-language-filter")
+                        .trigger(Button::new(
+                            "project-search-language-filter-trigger",
+                            search
+                                .language_filter
+                                .clone()
+                                .unwrap_or_else(|| "Any Language".into()),
+                        ))
+                        .menu(move |cx| {
+                            Some(ProjectSearchView::language_filter_menu(
+                                search_view.clone(),
+                                cx,
+                            ))
+                        })
+                })
+                .child({
+                    let has_language_servers = search.has_language_servers(cx);
+                    Button::new(
+                        "project-search-symbol-kind-filter",
+                        search.symbol_kind_filter_label(),
+                    )
+                    .disabled(!has_language_servers)
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.cycle_symbol_kind_filter(cx);
+                    }))
+                    .tooltip(move |cx| {
+                        if has_language_servers {
+                            Tooltip::for_action(
+                                "Only search files defining a symbol of this kind",
+                                &CycleSymbolKindFilter,
+                                cx,
+                            )
+                        } else {
+                            Tooltip::text("Requires a running language server", cx)
+                        }
+                    })
+                })
+                .child(
+                    Button::new("project-search-symbol-scope", search.symbol_scope.label())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.cycle_symbol_scope(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Only keep the matches inside of this syntax",
+                                &CycleSymbolScope,
+                                cx,
+                            )
+                        }),
+                )
+                .child({
+                    let is_local = search.model.read(cx).project.read(cx).is_local();
+                    Button::new("project-search-include-dependencies", "Dependencies")
+                        .selected(search.include_dependencies)
+                        .disabled(!is_local)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_include_dependencies(cx);
+                        }))
+                        .tooltip(move |cx| {
+                            if is_local {
+                                Tooltip::with_meta(
+                                    "Also search the sources of Cargo dependencies",
+                                    Some(&ToggleIncludeDependencies),
+                                    "Expands the search to every downloaded dependency, opened read-only",
+                                    cx,
+                                )
+                            } else {
+                                Tooltip::text("Only available in local projects", cx)
+                            }
+                        })
+                })
+        });
+
+        let results_filter_line = search.has_results_to_filter(cx).then(|| {
+            h_flex()
+                .id("project-search-results-filter")
+                .flex_1()
+                .min_w(rems(MIN_INPUT_WIDTH_REMS))
+                .max_w(rems(MAX_INPUT_WIDTH_REMS))
+                .h_8()
+                .px_2()
+                .py_1()
+                .gap_2()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .rounded_lg()
+                .child(Icon::new(IconName::Filter).color(Color::Muted))
+                .child(self.render_text_input(&search.filter_editor, cx))
+                .tooltip(|cx| {
+                    Tooltip::text("Only show the matches on lines containing this text", cx)
+                })
+        });
+
+        v_flex()
+            .key_context("ProjectSearchBar")
+            .on_action(cx.listener(|this, _: &ToggleFocus, cx| this.move_focus_to_results(cx)))
+            .on_action(cx.listener(|this, _: &ToggleFilters, cx| {
+                this.toggle_filters(cx);
+            }))
+            .on_action(cx.listener(|this, _: &CycleScope, cx| {
+                this.cycle_scope(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleSearchInSelection, cx| {
+                this.toggle_search_in_selection(cx);
+            }))
+            .on_action(cx.listener(|this, _: &SearchWithinResults, cx| {
+                this.search_within_results(cx);
+            }))
+            .on_action(cx.listener(|this, _: &CycleSymbolKindFilter, cx| {
+                this.cycle_symbol_kind_filter(cx);
+            }))
+            .on_action(cx.listener(|this, _: &CycleSymbolScope, cx| {
+                this.cycle_symbol_scope(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleIncludeDependencies, cx| {
+                this.toggle_include_dependencies(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleSortOrder, cx| {
+                this.toggle_sort_order(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleSemanticSearch, cx| {
+                this.toggle_semantic_search(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleFilenameSearch, cx| {
+                this.toggle_filename_search(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleHexSearch, cx| {
+                this.toggle_hex_search(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleTreatQueryAsPath, cx| {
+                this.toggle_treat_query_as_path(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleLiveSearch, cx| {
+                this.toggle_live_search(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleDefaultExcludes, cx| {
+                this.toggle_default_excludes(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleMultiline, cx| {
+                this.toggle_multiline(cx);
+            }))
+            .on_action(cx.listener(|this, _: &StopSearch, cx| {
+                this.stop_search(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCasePreservingReplace, cx| {
+                this.toggle_case_preserving_replace(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleMultiPattern, cx| {
+                this.toggle_multi_pattern(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleInvertMatch, cx| {
+                this.toggle_invert_match(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ResetSearchOptions, cx| {
+                this.reset_search_options(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ExcludeCurrentFile, cx| {
+                this.exclude_current_file(cx);
+            }))
+            .on_action(
+                cx.listener(|this, _: &RevealActiveMatchInProjectPanel, cx| {
+                    this.reveal_active_match_in_project_panel(cx);
+                }),
+            )
+            .on_action(cx.listener(|this, _: &SwapIncludeExclude, cx| {
+                this.swap_include_exclude(cx);
+            }))
+            .on_action(cx.listener(|this, _: &PinResults, cx| {
+                this.toggle_pin_results(cx);
+            }))
+            .on_action(cx.listener(|this, _: &CopyMatches, cx| {
+                this.copy_matches(false, cx);
+            }))
+            .on_action(cx.listener(|this, _: &CopyActiveMatch, cx| {
+                this.copy_matches(true, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ExportResults, cx| {
+                this.export_results(cx);
+            }))
+            .on_action(cx.listener(|this, action: &GoToMatch, cx| {
+                this.go_to_match(action, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleResultsListView, cx| {
+                this.toggle_results_list_view(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleGroupByDirectory, cx| {
+                this.toggle_group_by_directory(cx);
+            }))
+            .on_action(cx.listener(|this, _: &OpenResultsInSplit, cx| {
+                this.open_results_in_split(cx);
+            }))
+            .on_action(cx.listener(|this, _: &PreviewReplace, cx| {
+                this.toggle_replace_preview(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleMatchExcluded, cx| {
+                this.toggle_match_excluded(cx);
+            }))
+            .on_action(cx.listener(|this, _: &SearchNavBack, cx| {
+                this.navigate_match_history(Direction::Prev, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SearchNavForward, cx| {
+                this.navigate_match_history(Direction::Next, cx);
+            }))
+            .on_action(cx.listener(|this, _: &BookmarkMatch, cx| {
+                this.toggle_match_bookmarked(cx);
+            }))
+            .on_action(cx.listener(|this, _: &NextBookmark, cx| {
+                this.navigate_bookmarks(Direction::Next, cx);
+            }))
+            .on_action(cx.listener(|this, _: &PrevBookmark, cx| {
+                this.navigate_bookmarks(Direction::Prev, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SwapWithPreviousQuery, cx| {
+                this.swap_with_previous_query(cx);
+            }))
+            .on_action(cx.listener(|this, action: &ReplaceAllInFile, cx| {
+                this.replace_all_in_file(action, cx);
+            }))
+            .on_action(cx.listener(|this, _: &IncreaseContext, cx| {
+                this.change_context_lines(1, 1, cx);
+            }))
+            .on_action(cx.listener(|this, _: &DecreaseContext, cx| {
+                this.change_context_lines(-1, -1, cx);
+            }))
+            .on_action(cx.listener(|this, _: &IncreaseContextBefore, cx| {
+                this.change_context_lines(1, 0, cx);
+            }))
+            .on_action(cx.listener(|this, _: &DecreaseContextBefore, cx| {
+                this.change_context_lines(-1, 0, cx);
+            }))
+            .on_action(cx.listener(|this, _: &IncreaseContextAfter, cx| {
+                this.change_context_lines(0, 1, cx);
+            }))
+            .on_action(cx.listener(|this, _: &DecreaseContextAfter, cx| {
+                this.change_context_lines(0, -1, cx);
+            }))
+            .capture_action(cx.listener(|this, action, cx| {
+                this.tab(action, cx);
+                cx.stop_propagation();
+            }))
+            .on_action(cx.listener(|this, _: &NextField, cx| {
+                this.cycle_field(Direction::Next, cx);
+            }))
+            .capture_action(cx.listener(|this, action, cx| {
+                this.tab_previous(action, cx);
+                cx.stop_propagation();
+            }))
+            .on_action(cx.listener(|this, action, cx| this.confirm(action, cx)))
+            .on_action(cx.listener(|this, action, cx| {
+                this.toggle_replace(action, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleWholeWord, cx| {
+                this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCaseSensitive, cx| {
+                this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+            }))
+            .on_action(cx.listener(|this, action, cx| {
+                if let Some(search) = this.active_project_search.as_ref() {
+                    search.update(cx, |this, cx| {
+                        this.replace_next(action, cx);
+                    })
+                }
+            }))
+            .on_action(cx.listener(|this, action, cx| {
+                if let Some(search) = this.active_project_search.as_ref() {
+                    search.update(cx, |this, cx| {
+                        this.replace_all(action, cx);
+                    })
+                }
+            }))
+            .on_action(cx.listener(|this, _: &ToggleIncludeIgnored, cx| {
+                this.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleDotMatchesNewLine, cx| {
+                this.toggle_search_option(SearchOptions::DOT_MATCHES_NEW_LINE, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleMultiLineAnchors, cx| {
+                this.toggle_search_option(SearchOptions::MULTI_LINE_ANCHORS, cx);
+            }))
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_prev_match))
+            .on_action(cx.listener(|this, _: &PeekNextMatch, cx| {
+                this.peek_match(Direction::Next, cx);
+            }))
+            .on_action(cx.listener(|this, _: &PeekPrevMatch, cx| {
+                this.peek_match(Direction::Prev, cx);
+            }))
+            .gap_2()
+            .w_full()
+            .child(search_line)
+            .children(replace_line)
+            .children(filter_line)
+            .children(results_filter_line)
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for ProjectSearchBar {}
+
+impl ToolbarItemView for ProjectSearchBar {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) -> ToolbarItemLocation {
+        cx.notify();
+        self.subscription = None;
+        self.active_project_search = None;
+        if let Some(search) = active_pane_item.and_then(|i| i.downcast::<ProjectSearchView>()) {
+            self.subscription = Some(cx.observe(&search, |_, _, cx| cx.notify()));
+            self.active_project_search = Some(search);
+            ToolbarItemLocation::PrimaryLeft {}
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn row_count(&self, cx: &WindowContext<'_>) -> usize {
+        if let Some(search) = self.active_project_search.as_ref() {
+            if search.read(cx).filters_enabled {
+                return 2;
+            }
+        }
+        1
+    }
+}
+
+/// Turns a multi-pattern query into a regex matching any of its literal patterns, which are
+/// separated by `|` and trimmed. Empty patterns are ignored.
+fn multi_pattern_regex(query: &str) -> String {
+    let patterns = query
+        .split(MULTI_PATTERN_DELIMITER)
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(regex::escape)
+        .collect::<Vec<_>>();
+    if patterns.is_empty() {
+        String::new()
+    } else {
+        format!("(?:{})", patterns.join("|"))
+    }
+}
+
+/// Whether a regex `pattern` looks like it may take long to search large projects with: when it
+/// starts with `.*` or `.+`, which makes every position of every line a potential match start,
+/// or when it repeats a group that itself contains an unbounded repetition, as in `(a+)+`.
+/// Errs on the side of not warning, since the pattern is searched for regardless.
+fn may_be_slow_regex(pattern: &str) -> bool {
+    if pattern.starts_with(".*") || pattern.starts_with(".+") {
+        return true;
+    }
+
+    // Whether each of the open groups contains an unbounded repetition so far.
+    let mut groups = vec![false];
+    let mut closed_group_repeats = false;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let repeats_unboundedly = match c {
+            '\\' => {
+                chars.next();
+                false
+            }
+            '[' => {
+                // Skip the character class, whose `]` is literal when it comes first.
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                }
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                }
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        ']' => break,
+                        _ => {}
+                    }
+                }
+                false
+            }
+            '(' => {
+                groups.push(false);
+                false
+            }
+            ')' => {
+                let Some(contains_repetition) = groups.pop().filter(|_| !groups.is_empty()) else {
+                    return false;
+                };
+                closed_group_repeats = contains_repetition;
+                if let Some(outer) = groups.last_mut() {
+                    *outer |= contains_repetition;
+                }
+                continue;
+            }
+            '*' | '+' => true,
+            '{' => {
+                let repetition = chars.clone().take_while(|c| *c != '}').collect::<String>();
+                repetition.ends_with(',')
+                    && repetition[..repetition.len() - 1]
+                        .chars()
+                        .all(|c| c.is_ascii_digit())
+            }
+            _ => false,
+        };
+        if repeats_unboundedly {
+            if closed_group_repeats {
+                return true;
+            }
+            if let Some(group) = groups.last_mut() {
+                *group = true;
+            }
+        }
+        closed_group_repeats = false;
+    }
+    false
+}
+
+/// Describes the first match of a regex `query` in `snapshot`, followed by its capture groups.
+async fn regex_preview(query: &SearchQuery, snapshot: &language::BufferSnapshot) -> Option<String> {
+    let range = query.search(snapshot, None).await.into_iter().next()?;
+    let matched_text = snapshot.text_for_range(range).collect::<String>();
+    let groups = query
+        .as_regex()
+        .and_then(|regex| regex.captures(&matched_text))
+        .map(|captures| {
+            captures
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter_map(|(ix, group)| {
+                    Some(format!("${ix}: {:?}", truncate_preview(group?.as_str())))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut preview = format!("{:?}", truncate_preview(&matched_text));
+    if !groups.is_empty() {
+        preview.push_str(&format!(" ({})", groups.join(", ")));
+    }
+    Some(preview)
+}
+
+/// Parses the bytes of a hex search query written as pairs of hex digits, e.g. `DE AD BE EF`,
+/// `\xDE\xAD\xBE\xEF` or `0xdeadbeef`.
+fn parse_hex_pattern(query: &str) -> Option<Vec<u8>> {
+    let digits = query
+        .split_whitespace()
+        .flat_map(|word| word.split("\\x"))
+        .map(|word| {
+            word.strip_prefix("0x")
+                .or_else(|| word.strip_prefix("0X"))
+                .unwrap_or(word)
+        })
+        .collect::<String>();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|ix| u8::from_str_radix(&digits[ix..ix + 2], 16).ok())
+        .collect()
+}
+
+/// Parses a command line like `rg -i --glob '!target' -e 'foo.*bar'` into the search it runs.
+/// Only `-i`, `-w`, `-F`, `-e` and `-g`/`--glob` (with grep's `--include`/`--exclude`) carry
+/// over; other flags and the paths to search are returned as ignored rather than rejected.
+/// As in ripgrep, searches are case-sensitive regex searches unless `-i` or `-F` say otherwise.
+fn parse_ripgrep_command(command: &str) -> Option<RipgrepCommand> {
+    let mut words = shlex::split(command.trim())?.into_iter();
+    let program = words.next()?;
+    if !matches!(
+        Path::new(&program)
+            .file_name()
+            .and_then(|name| name.to_str()),
+        Some("rg" | "grep")
+    ) {
+        return None;
+    }
+
+    let mut patterns = Vec::new();
+    let mut positional = Vec::new();
+    let mut ignore_case = false;
+    let mut whole_word = false;
+    let mut fixed_strings = false;
+    let mut files_to_include = Vec::new();
+    let mut files_to_exclude = Vec::new();
+    let mut ignored_arguments = Vec::new();
+    let mut add_glob = |glob: String| match glob.strip_prefix('!') {
+        Some(excluded) => files_to_exclude.push(excluded.to_string()),
+        None => files_to_include.push(glob),
+    };
+    while let Some(word) = words.next() {
+        if word == "--" {
+            positional.extend(words.by_ref());
+            break;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            let (flag, value) = match long.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (long, None),
+            };
+            match flag {
+                "ignore-case" => ignore_case = true,
+                "word-regexp" => whole_word = true,
+                "fixed-strings" => fixed_strings = true,
+                "regexp" | "glob" | "include" | "exclude" => {
+                    let Some(value) = value.or_else(|| words.next()) else {
+                        ignored_arguments.push(word.clone());
+                        continue;
+                    };
+                    match flag {
+                        "regexp" => patterns.push(value),
+                        "exclude" => add_glob(format!("!{value}")),
+                        _ => add_glob(value),
+                    }
+                }
+                _ => ignored_arguments.push(word.clone()),
+            }
+        } else if word.len() > 1 && word.starts_with('-') {
+            // Short flags can be bundled, as in `-iw`, and end with one taking a value, as in
+            // `-ie foo` or `-efoo`.
+            for (ix, flag) in word.char_indices().skip(1) {
+                match flag {
+                    'i' => ignore_case = true,
+                    'w' => whole_word = true,
+                    'F' => fixed_strings = true,
+                    'e' | 'g' => {
+                        let rest = &word[ix + flag.len_utf8()..];
+                        let value = if rest.is_empty() {
+                            words.next()
+                        } else {
+                            Some(rest.to_string())
+                        };
+                        match (flag, value) {
+                            ('e', Some(value)) => patterns.push(value),
+                            (_, Some(value)) => add_glob(value),
+                            (_, None) => ignored_arguments.push(format!("-{flag}")),
+                        }
+                        break;
+                    }
+                    _ => ignored_arguments.push(format!("-{flag}")),
+                }
+            }
+        } else {
+            positional.push(word);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    if patterns.is_empty() {
+        patterns.extend(positional.next());
+    }
+    // The remaining positional arguments are the paths to search.
+    ignored_arguments.extend(positional);
+
+    let mut search_options = SearchOptions::NONE;
+    search_options.set(SearchOptions::CASE_SENSITIVE, !ignore_case);
+    search_options.set(SearchOptions::WHOLE_WORD, whole_word);
+    let query = match patterns.as_slice() {
+        [] => return None,
+        [pattern] => {
+            search_options.set(SearchOptions::REGEX, !fixed_strings);
+            pattern.clone()
+        }
+        // Several patterns match wherever any of them does.
+        patterns => {
+            search_options.insert(SearchOptions::REGEX);
+            patterns
+                .iter()
+                .map(|pattern| {
+                    if fixed_strings {
+                        regex::escape(pattern)
+                    } else {
+                        format!("(?:{pattern})")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("|")
+        }
+    };
+    Some(RipgrepCommand {
+        query,
+        search_options,
+        files_to_include,
+        files_to_exclude,
+        ignored_arguments,
+    })
+}
+
+/// Whether moving from the match at `index` to the one at `new_index` in `direction` went past
+/// an end of the matches.
+fn wraps(index: usize, new_index: usize, direction: Direction) -> bool {
+    match direction {
+        Direction::Next => new_index < index,
+        Direction::Prev => new_index > index,
+    }
+}
+
+/// Returns the offsets of every occurrence of `pattern` in `bytes`, overlapping ones included.
+fn find_bytes<'a>(bytes: &'a [u8], pattern: &'a [u8]) -> impl 'a + Iterator<Item = usize> {
+    bytes
+        .windows(pattern.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == pattern)
+        .map(|(offset, _)| offset)
+}
+
+/// Returns up to `max_matches` occurrences of `pattern` in `file`, reading it a chunk at a time.
+/// Files larger than [`HEX_SEARCH_MAX_FILE_SIZE`] are skipped.
+fn find_hex_matches(
+    path: &ProjectPath,
+    mut file: impl io::Read,
+    pattern: &[u8],
+    max_matches: usize,
+) -> io::Result<Vec<HexMatch>> {
+    let mut matches = Vec::new();
+    let mut chunk = vec![0; HEX_SEARCH_CHUNK_SIZE];
+    // The bytes of the file from `window_start` on that are still needed, i.e. the ones the
+    // occurrences not looked for yet may start at, and the context before them.
+    let mut window = Vec::new();
+    let mut window_start = 0;
+    // The occurrences starting before this offset were looked for already.
+    let mut searched_up_to = 0;
+    loop {
+        let read_len = file.read(&mut chunk)?;
+        window.extend_from_slice(&chunk[..read_len]);
+        let window_end = window_start + window.len();
+        if window_end > HEX_SEARCH_MAX_FILE_SIZE {
+            return Ok(Vec::new());
+        }
+        // Until the file ends, the occurrences close to the end of the window wait for the
+        // context after them to be read.
+        let at_end = read_len == 0;
+        let search_end = if at_end {
+            window_end
+        } else {
+            (window_end + 1).saturating_sub(pattern.len() + HEX_CONTEXT_BYTES)
+        };
+        for index in find_bytes(&window, pattern) {
+            let offset = window_start + index;
+            if offset < searched_up_to {
+                continue;
+            }
+            if offset >= search_end {
+                break;
+            }
+            let context_start = offset.saturating_sub(HEX_CONTEXT_BYTES);
+            let context_end = (offset + pattern.len() + HEX_CONTEXT_BYTES).min(window_end);
+            matches.push(HexMatch {
+                path: path.clone(),
+                offset,
+                len: pattern.len(),
+                context_start,
+                context: window[context_start - window_start..context_end - window_start].to_vec(),
+            });
+            if matches.len() == max_matches {
+                return Ok(matches);
+            }
+        }
+        if at_end {
+            return Ok(matches);
+        }
+        searched_up_to = searched_up_to.max(search_end);
+        let keep_start = searched_up_to
+            .saturating_sub(HEX_CONTEXT_BYTES)
+            .max(window_start);
+        window.drain(..keep_start - window_start);
+        window_start = keep_start;
+    }
+}
+
+/// Formats the bytes around a hex search match as hex pairs followed by their printable ASCII
+/// characters, like hex dumps do, along with the indices of the characters of the match.
+fn hex_excerpt(hex_match: &HexMatch) -> (String, Vec<usize>) {
+    let match_start = hex_match.offset - hex_match.context_start;
+    let match_range = match_start..match_start + hex_match.len;
+    let mut excerpt = String::new();
+    let mut positions = Vec::new();
+    for (ix, byte) in hex_match.context.iter().enumerate() {
+        if ix > 0 {
+            excerpt.push(' ');
+        }
+        if match_range.contains(&ix) {
+            positions.extend([excerpt.len(), excerpt.len() + 1]);
+        }
+        excerpt.push_str(&format!("{byte:02x}"));
+    }
+    excerpt.push_str("  |");
+    for (ix, byte) in hex_match.context.iter().enumerate() {
+        if match_range.contains(&ix) {
+            positions.push(excerpt.len());
+        }
+        excerpt.push(if byte.is_ascii_graphic() || *byte == b' ' {
+            *byte as char
+        } else {
+            '.'
+        });
+    }
+    excerpt.push('|');
+    (excerpt, positions)
+}
+
+fn semantic_score_renderer(score: f32) -> RenderBlock {
+    Box::new(move |cx| {
+        h_flex()
+            .pl(cx.gutter_dimensions.width)
+            .child(
+                div()
+                    .px_1()
+                    .rounded_md()
+                    .bg(cx.theme().colors().element_background)
+                    .child(
+                        Label::new(format!("{score:.2} similarity"))
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .into_any_element()
+    })
+}
+
+fn match_summary(match_ranges: &[Range<Anchor>]) -> Option<SharedString> {
+    if match_ranges.is_empty() {
+        return None;
+    }
+    let match_count = match_ranges.len();
+    let file_count = match_ranges
+        .iter()
+        .map(|range| range.start.buffer_id)
+        .collect::<HashSet<_>>()
+        .len();
+    let matches = if match_count == 1 { "match" } else { "matches" };
+    let files = if file_count == 1 { "file" } else { "files" };
+    Some(format!("{match_count} {matches} in {file_count} {files}").into())
+}
+
+/// Groups the match ranges that were streamed by the time they are read, which come from the
+/// same batch of excerpts, so that they are inserted together.
+fn batch_match_ranges(
+    match_ranges: mpsc::Receiver<Range<Anchor>>,
+) -> impl Stream<Item = Vec<Range<Anchor>>> + Unpin {
+    futures::StreamExt::ready_chunks(match_ranges, MATCH_RANGES_BATCH_SIZE)
+}
+
+/// The range of a match in its buffer, which stays the same when the excerpts are rebuilt.
+fn match_buffer_range(range: &Range<Anchor>) -> Range<language::Anchor> {
+    range.start.text_anchor..range.end.text_anchor
+}
+
+/// The path shown in the header of the results of `file`, or `None` for the editor's default,
+/// which is relative to the project.
+fn excerpt_header_path(
+    path_display: PathDisplay,
+    file: &dyn language::File,
+    cx: &AppContext,
+) -> Option<PathBuf> {
+    match path_display {
+        PathDisplay::ProjectRelative => None,
+        // A worktree of a single file has an empty path, so its name is kept.
+        PathDisplay::WorktreeRelative if file.path().file_name().is_some() => {
+            Some(file.path().to_path_buf())
+        }
+        PathDisplay::WorktreeRelative => Some(file.full_path(cx)),
+        PathDisplay::Absolute => Some(
+            file.as_local()
+                .map_or_else(|| file.full_path(cx), |file| file.abs_path(cx)),
+        ),
+    }
+}
+
+fn truncate_preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.len() < text.len() || line.chars().count() > MAX_REGEX_PREVIEW_LEN {
+        let mut truncated = line.chars().take(MAX_REGEX_PREVIEW_LEN).collect::<String>();
+        truncated.push('…');
+        truncated
+    } else {
+        line.to_string()
+    }
+}
+
+/// Appends the `default_excludes` globs to the `excluded` ones, skipping the defaults that
+/// would exclude a path the user explicitly included.
+///
+/// The defaults go before the first negated glob, so that negations can take paths back out
+/// of them.
+fn with_default_excludes(
+    mut excluded: Vec<PathMatcher>,
+    included: &[PathMatcher],
+    default_excludes: &[String],
+) -> Vec<PathMatcher> {
+    let mut position = excluded
+        .iter()
+        .position(|glob| glob.is_negated())
+        .unwrap_or(excluded.len());
+    for glob in default_excludes {
+        let Some(default_exclude) = PathMatcher::new(glob).log_err() else {
+            continue;
+        };
+        let overridden = included.iter().any(|included| {
+            !included.is_negated() && default_exclude.is_match(included.to_string())
+        });
+        if !overridden && !excluded.contains(&default_exclude) {
+            excluded.insert(position, default_exclude);
+            position += 1;
+        }
+    }
+    excluded
+}
+
+/// Adapts `replacement` to the casing of the identifier `matched`, so that e.g. replacing
+/// `fooBar` with `baz_qux` inserts `bazQux`, and replacing `FOOBAR` inserts `BAZQUX`.
+///
+/// Replacements or matches that are not identifiers, or whose casing is mixed in some
+/// other way, are returned unchanged.
+fn with_case_of(matched: &str, replacement: &str) -> String {
+    let is_identifier =
+        |text: &str| !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !is_identifier(matched) || !is_identifier(replacement) {
+        return replacement.to_string();
+    }
+
+    let mut words = Vec::<String>::new();
+    let mut prev_is_lowercase = false;
+    for c in replacement.chars() {
+        if c == '_' {
+            words.push(String::new());
+        } else {
+            if words.is_empty() || (prev_is_lowercase && c.is_uppercase()) {
+                words.push(String::new());
+            }
+            words.last_mut().unwrap().push(c);
+        }
+        prev_is_lowercase = c.is_lowercase() || c.is_numeric();
+    }
+    words.retain(|word| !word.is_empty());
+    let capitalize = |word: &String| {
+        let mut chars = word.chars();
+        chars.next().map_or(String::new(), |first| {
+            first
+                .to_uppercase()
+                .chain(chars.flat_map(char::to_lowercase))
+                .collect()
+        })
+    };
+
+    let has_lowercase = matched.chars().any(char::is_lowercase);
+    let has_uppercase = matched.chars().any(char::is_uppercase);
+    let separator = if matched.trim_matches('_').contains('_') {
+        "_"
+    } else {
+        ""
+    };
+    match (has_lowercase, has_uppercase) {
+        (false, true) => words
+            .iter()
+            .map(|word| word.to_uppercase())
+            .collect::<Vec<_>>()
+            .join(separator),
+        (true, false) => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(separator),
+        (true, true) if separator.is_empty() => {
+            let is_pascal_case = matched.starts_with(char::is_uppercase);
+            words
+                .iter()
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 && !is_pascal_case {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect()
+        }
+        _ => replacement.to_string(),
+    }
+}
+
+/// The folds truncating the long matched lines of an excerpt.
+struct LineTruncation {
+    /// The number of matches in the excerpt when the folds were computed.
+    match_count: usize,
+    /// The start of the active match, if it was in the excerpt when the folds were computed.
+    active_match_start: Option<Anchor>,
+    folds: Vec<Range<Anchor>>,
+}
+
+/// Returns the ranges to fold so that the lines containing `matches` fit into `max_line_length`
+/// columns, centered on the match starting at `active_match_start`, or else on the line's first
+/// match.
+fn line_truncation_folds(
+    matches: &[Range<Anchor>],
+    active_match_start: Option<Anchor>,
+    max_line_length: u32,
+    snapshot: &MultiBufferSnapshot,
+) -> Vec<Range<Anchor>> {
+    let mut centered_matches = BTreeMap::<u32, Range<language::Point>>::new();
+    for range in matches {
+        let start = range.start.to_point(snapshot);
+        let end = range.end.to_point(snapshot);
+        if Some(range.start) == active_match_start || !centered_matches.contains_key(&start.row) {
+            centered_matches.insert(start.row, start..end);
+        }
+    }
+
+    let mut folds = Vec::new();
+    for (row, range) in centered_matches {
+        let line_len = snapshot.line_len(row);
+        let match_end = if range.end.row == row {
+            range.end.column
+        } else {
+            line_len
+        };
+        let Some(window) =
+            excerpt_line_window(line_len, range.start.column..match_end, max_line_length)
+        else {
+            continue;
+        };
+        if window.start > 0 {
+            let window_start =
+                snapshot.clip_point(language::Point::new(row, window.start), Bias::Left);
+            folds.push(
+                snapshot.anchor_after(language::Point::new(row, 0))
+                    ..snapshot.anchor_before(window_start),
+            );
+        }
+        if window.end < line_len {
+            let window_end =
+                snapshot.clip_point(language::Point::new(row, window.end), Bias::Right);
+            folds.push(
+                snapshot.anchor_after(window_end)
+                    ..snapshot.anchor_before(language::Point::new(row, line_len)),
+            );
+        }
+    }
+    folds
+}
+
+/// Returns the columns of a `line_len` long line that fit into `max_len` columns with the
+/// `match_columns` centered, or `None` if the whole line fits already.
+fn excerpt_line_window(
+    line_len: u32,
+    match_columns: Range<u32>,
+    max_len: u32,
+) -> Option<Range<u32>> {
+    if line_len <= max_len {
+        return None;
+    }
+    let match_len = match_columns.end.saturating_sub(match_columns.start);
+    if match_len >= max_len {
+        return Some(match_columns);
+    }
+    let padding = (max_len - match_len) / 2;
+    let start = match_columns
+        .start
+        .saturating_sub(padding)
+        .min(line_len - max_len);
+    Some(start..start + max_len)
+}
+
+fn register_workspace_action<A: Action>(
+    workspace: &mut Workspace,
+    callback: fn(&mut ProjectSearchBar, &A, &mut ViewContext<ProjectSearchBar>),
+) {
+    workspace.register_action(move |workspace, action: &A, cx| {
+        if workspace.has_active_modal(cx) {
+            cx.propagate();
+            return;
+        }
+
+        workspace.active_pane().update(cx, |pane, cx| {
+            pane.toolbar().update(cx, move |workspace, cx| {
+                if let Some(search_bar) = workspace.item_of_type::<ProjectSearchBar>() {
+                    search_bar.update(cx, move |search_bar, cx| {
+                        if search_bar.active_project_search.is_some() {
+                            callback(search_bar, action, cx);
+                            cx.notify();
+                        } else {
+                            cx.propagate();
+                        }
+                    });
+                }
+            });
+        })
+    });
+}
+
+fn register_workspace_action_for_present_search<A: Action>(
+    workspace: &mut Workspace,
+    callback: fn(&mut Workspace, &A, &mut ViewContext<Workspace>),
+) {
+    workspace.register_action(move |workspace, action: &A, cx| {
+        if workspace.has_active_modal(cx) {
+            cx.propagate();
+            return;
+        }
+
+        let should_notify = workspace
+            .active_pane()
+            .read(cx)
+            .toolbar()
+            .read(cx)
+            .item_of_type::<ProjectSearchBar>()
+            .map(|search_bar| search_bar.read(cx).active_project_search.is_some())
+            .unwrap_or(false);
+        if should_notify {
+            callback(workspace, action, cx);
+            cx.notify();
+        } else {
+            cx.propagate();
+        }
+    });
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use editor::{DisplayPoint, EditorMode};
+    use futures::{future::BoxFuture, FutureExt as _};
+    use gpui::{Action, TestAppContext, WindowHandle};
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use project::{repository::GitFileStatus, FakeFs};
+    use semantic_index::{Embedding, EmbeddingProvider, Status, TextToEmbed};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{
+        cell::RefCell,
+        future,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering::SeqCst},
+            Arc,
+        },
+    };
+    use workspace::DeploySearch;
+
+    #[gpui::test]
+    async fn test_project_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view.update(cx, |search_view, cx| {
+            assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx)),
+                "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+            );
+            let match_background_color = cx.theme().colors().search_match_background;
+            let active_match_background_color =
+                cx.theme().colors().search_active_match_background;
+            let highlights = search_view
+                .results_editor
+                .update(cx, |editor, cx| editor.all_text_background_highlights(cx));
+            assert_eq!(
+                highlights
+                    .iter()
+                    .filter(|(_, color)| *color == match_background_color)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                &[
+                    (
+                        DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
+                        match_background_color
+                    ),
+                    (
+                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
+                        match_background_color
+                    ),
+                    (
+                        DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9),
+                        match_background_color
+                    )
+                ]
+            );
+            assert_eq!(
+                highlights
+                    .iter()
+                    .filter(|(_, color)| *color == active_match_background_color)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                &[(
+                    DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
+                    active_match_background_color
+                )]
+            );
+            assert_eq!(search_view.active_match_index, Some(0));
+            assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                [DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35)]
+            );
+
+            search_view.select_match(Direction::Next, cx);
+        }).unwrap();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40)]
+                );
+                let active_match_background_color =
+                    cx.theme().colors().search_active_match_background;
+                assert!(search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx))
+                    .contains(&(
+                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
+                        active_match_background_color
+                    )));
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(2));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9)]
+                );
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35)]
+                );
+                search_view.select_match(Direction::Prev, cx);
+            })
+            .unwrap();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(2));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9)]
+                );
+                search_view.select_match(Direction::Prev, cx);
+            })
+            .unwrap();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40)]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_scrollbar_match_markers(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        let markers = |search_view: &ProjectSearchView, cx: &AppContext| {
+            search_view
+                .results_editor
+                .read(cx)
+                .scrollbar_match_markers()
+                .map(|(ranges, active_index)| (ranges.to_vec(), active_index))
+        };
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let match_ranges = search_view.model.read(cx).match_ranges.clone();
+                assert_eq!(match_ranges.len(), 3);
+                assert_eq!(markers(search_view, cx), Some((match_ranges, Some(0))));
+
+                search_view.select_match(Direction::Next, cx);
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                let match_ranges = search_view.model.read(cx).match_ranges.clone();
+                assert_eq!(markers(search_view, cx), Some((match_ranges, Some(2))));
+            })
+            .unwrap();
+
+        perform_search(search_view, "NOTHING", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.model.read(cx).match_ranges.is_empty());
+                assert_eq!(markers(search_view, cx), None);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window;
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        let active_item = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        });
+        assert!(
+            active_item.is_none(),
+            "Expected no search panel to be active"
+        );
+
+        window
+            .update(cx, move |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 1);
+                workspace.panes()[0].update(cx, move |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                });
+
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+
+        let Some(search_view) = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        }) else {
+            panic!("Search view expected to appear after new search event trigger")
+        };
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Empty search view should be focused after the toggle focus event: no results panel to focus on",
+                );
+           });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    let query_editor = &search_view.query_editor;
+                    assert!(
+                        query_editor.focus_handle(cx).is_focused(cx),
+                        "Search view should be focused after the new search view is activated",
+                    );
+                    let query_text = query_editor.read(cx).text(cx);
+                    assert!(
+                        query_text.is_empty(),
+                        "New search query should be empty but got '{query_text}'",
+                    );
+                    let results_text = search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx));
+                    assert!(
+                        results_text.is_empty(),
+                        "Empty search view should have no results but got '{results_text}'"
+                    );
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.query_editor.update(cx, |query_editor, cx| {
+                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
+                    });
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(
+                    results_text.is_empty(),
+                    "Search view for mismatching query should have no results but got '{results_text}'"
+                );
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Search view should be focused after mismatching query had been used in search",
+                );
+            });
+        }).unwrap();
+
+        cx.spawn(|mut cx| async move {
+            window.update(&mut cx, |_, cx| {
+                cx.dispatch_action(ToggleFocus.boxed_clone())
+            })
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
+                );
+            });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                    "Search view results should match the query"
+                );
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with mismatching query should be focused after search results are available",
+                );
+            });
+        }).unwrap();
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with matching query should still have its results editor focused after the toggle focus event",
+                );
+            });
+        }).unwrap();
+
+        workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_editor.read(cx).text(cx), "two", "Query should be updated to first search result after search view 2nd open in a row");
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                    "Results should be unchanged after search view 2nd open in a row"
+                );
+                assert!(
+                    search_view.query_editor.focus_handle(cx).is_focused(cx),
+                    "Focus should be moved into query editor again after search view 2nd open in a row"
+                );
+            });
+        }).unwrap();
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with matching query should switch focus to the results editor after the toggle focus event",
+                );
+            });
+        }).unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_project_search_focus(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window;
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        let active_item = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        });
+        assert!(
+            active_item.is_none(),
+            "Expected no search panel to be active"
+        );
+
+        window
+            .update(cx, move |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 1);
+                workspace.panes()[0].update(cx, move |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                });
+
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+            })
+            .unwrap();
+
+        let Some(search_view) = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        }) else {
+            panic!("Search view expected to appear after new search event trigger")
+        };
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                    assert!(
+                        search_view.query_editor.focus_handle(cx).is_focused(cx),
+                        "Empty search view should be focused after the toggle focus event: no results panel to focus on",
+                    );
+                });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    let query_editor = &search_view.query_editor;
+                    assert!(
+                        query_editor.focus_handle(cx).is_focused(cx),
+                        "Search view should be focused after the new search view is activated",
+                    );
+                    let query_text = query_editor.read(cx).text(cx);
+                    assert!(
+                        query_text.is_empty(),
+                        "New search query should be empty but got '{query_text}'",
+                    );
+                    let results_text = search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx));
+                    assert!(
+                        results_text.is_empty(),
+                        "Empty search view should have no results but got '{results_text}'"
+                    );
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.query_editor.update(cx, |query_editor, cx| {
+                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
+                    });
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    let results_text = search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx));
+                    assert!(
+                results_text.is_empty(),
+                "Search view for mismatching query should have no results but got '{results_text}'"
+            );
+                    assert!(
+                search_view.query_editor.focus_handle(cx).is_focused(cx),
+                "Search view should be focused after mismatching query had been used in search",
+            );
+                });
+            })
+            .unwrap();
+        cx.spawn(|mut cx| async move {
+            window.update(&mut cx, |_, cx| {
+                cx.dispatch_action(ToggleFocus.boxed_clone())
+            })
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                    assert!(
+                        search_view.query_editor.focus_handle(cx).is_focused(cx),
+                        "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
+                    );
+                });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx|
+        search_view.update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                    "Search view results should match the query"
+                );
+                assert!(
+                    search_view.results_editor.focus_handle(cx).is_focused(cx),
+                    "Search view with mismatching query should be focused after search results are available",
+                );
+            })).unwrap();
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                    assert!(
+                        search_view.results_editor.focus_handle(cx).is_focused(cx),
+                        "Search view with matching query should still have its results editor focused after the toggle focus event",
+                    );
+                });
+        }).unwrap();
+
+        workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        let Some(search_view_2) = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .unwrap()
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        }) else {
+            panic!("Search view expected to appear after new search event trigger")
+        };
+        assert!(
+            search_view_2 != search_view,
+            "New search view should be open after `workspace::NewSearch` event"
+        );
+
+        window.update(cx, |_, cx| {
+            search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO", "First search view should not have an updated query");
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                        "Results of the first search view should not update too"
+                    );
+                    assert!(
+                        !search_view.query_editor.focus_handle(cx).is_focused(cx),
+                        "Focus should be moved away from the first search view"
+                    );
+                });
+        }).unwrap();
+
+        window.update(cx, |_, cx| {
+            search_view_2.update(cx, |search_view_2, cx| {
+                    assert_eq!(
+                        search_view_2.query_editor.read(cx).text(cx),
+                        "two",
+                        "New search view should get the query from the text cursor was at during the event spawn (first search view's first result)"
+                    );
+                    assert_eq!(
+                        search_view_2
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "",
+                        "No search results should be in the 2nd view yet, as we did not spawn a search for it"
+                    );
+                    assert!(
+                        search_view_2.query_editor.focus_handle(cx).is_focused(cx),
+                        "Focus should be moved into query editor of the new window"
+                    );
+                });
+        }).unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view_2.update(cx, |search_view_2, cx| {
+                    search_view_2
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("FOUR", cx));
+                    search_view_2.search(cx);
+                });
+            })
+            .unwrap();
+
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view_2.update(cx, |search_view_2, cx| {
+                    assert_eq!(
+                        search_view_2
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst FOUR: usize = one::ONE + three::THREE;",
+                        "New search view with the updated query should have new search results"
+                    );
+                    assert!(
+                        search_view_2.results_editor.focus_handle(cx).is_focused(cx),
+                        "Search view with mismatching query should be focused after search results are available",
+                    );
+                });
+        }).unwrap();
+
+        cx.spawn(|mut cx| async move {
+            window
+                .update(&mut cx, |_, cx| {
+                    cx.dispatch_action(ToggleFocus.boxed_clone())
+                })
+                .unwrap();
+        })
+        .detach();
+        cx.background_executor.run_until_parked();
+        window.update(cx, |_, cx| {
+            search_view_2.update(cx, |search_view_2, cx| {
+                    assert!(
+                        search_view_2.results_editor.focus_handle(cx).is_focused(cx),
+                        "Search view with matching query should switch focus to the results editor after the toggle focus event",
+                    );
+                });}).unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_project_search_in_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = 1;",
+                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                },
+                "b": {
+                    "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                    "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        let active_item = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        });
+        assert!(
+            active_item.is_none(),
+            "Expected no search panel to be active"
+        );
+
+        window
+            .update(cx, move |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 1);
+                workspace.panes()[0].update(cx, move |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                });
+            })
+            .unwrap();
+
+        let a_dir_entry = cx.update(|cx| {
+            workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .entry_for_path(&(worktree_id, "a").into(), cx)
+                .expect("no entry for /a/ directory")
+        });
+        assert!(a_dir_entry.is_dir());
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search_in_directory(workspace, &a_dir_entry.path, cx)
+            })
+            .unwrap();
+
+        let Some(search_view) = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+        }) else {
+            panic!("Search view expected to appear after new search in directory event trigger")
+        };
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert!(
+                        search_view.query_editor.focus_handle(cx).is_focused(cx),
+                        "On new search in directory, focus should be moved into query editor"
+                    );
+                    search_view.excluded_files_editor.update(cx, |editor, cx| {
+                        assert!(
+                            editor.display_text(cx).is_empty(),
+                            "New search in directory should not have any excluded files"
+                        );
+                    });
+                    search_view.included_files_editor.update(cx, |editor, cx| {
+                        assert_eq!(
+                            editor.display_text(cx),
+                            a_dir_entry.path.to_str().unwrap(),
+                            "New search in directory should have included dir entry path"
+                        );
+                    });
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("const", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx)),
+                "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                "New search in directory should have a filter that matches a certain directory"
+            );
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_query_history(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 1);
+                    workspace.panes()[0].update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        // Add 3 search items into the history + another unsubmitted one.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("THREE", cx));
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.query_editor.update(cx, |query_editor, cx| {
+                        query_editor.set_text("JUST_TEXT_INPUT", cx)
+                    });
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        // Ensure that the latest input with search settings is active.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(
+                        search_view.query_editor.read(cx).text(cx),
+                        "JUST_TEXT_INPUT"
+                    );
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Next history query after the latest should set the query to the empty string.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // First previous query for empty current query should set the query to the latest submitted one.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Further previous items should go over the history in reverse order.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Previous items should never go behind the first history item.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // Next items should go over the history in the original order.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO_NEW", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+
+        // New search input should add another entry to history and move the selection to the end of the history.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                });
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_cycled_index() {
+        for len in 1..6 {
+            for index in 0..len {
+                let prev = cycled_index(index, len, Direction::Prev);
+                let next = cycled_index(index, len, Direction::Next);
+                assert!(prev < len && next < len);
+                assert_eq!(prev, if index == 0 { len - 1 } else { index - 1 });
+                assert_eq!(next, if index == len - 1 { 0 } else { index + 1 });
+                assert_eq!(cycled_index(prev, len, Direction::Next), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_may_be_slow_regex() {
+        for pattern in [
+            ".*foo",
+            ".+bar",
+            "(a+)+",
+            r"(\w*\s)*x",
+            "(?:x(a|b+))*",
+            "(ab*){2,}",
+        ] {
+            assert!(may_be_slow_regex(pattern), "{pattern} should be flagged");
+        }
+        for pattern in [
+            "foo.*bar",
+            "^.*foo",
+            "(ab)+",
+            "(a+)?",
+            "(a+){2}",
+            r"\(a+\)+",
+            "[(a+)]+",
+            "fn (\\w+)\\(",
+            "a)+",
+        ] {
+            assert!(
+                !may_be_slow_regex(pattern),
+                "{pattern} should not be flagged"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_pattern_regex() {
+        assert_eq!(
+            multi_pattern_regex("foo | bar.baz || qux "),
+            r"(?:foo|bar\.baz|qux)"
+        );
+        assert_eq!(multi_pattern_regex("a*"), r"(?:a\*)");
+        assert_eq!(multi_pattern_regex(" | "), "");
+    }
+
+    #[gpui::test]
+    async fn test_slow_regex_warning(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, ".*ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                // Text searches are never flagged.
+                assert!(!search_view.slow_regex_warning);
+                search_view.search_options = SearchOptions::REGEX;
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                // The warning is advisory, so the search still runs.
+                assert!(search_view.slow_regex_warning);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+        perform_search(search_view, "ONE.*", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert!(!search_view.slow_regex_warning)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_hex_pattern() {
+        let dead_beef = Some(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(parse_hex_pattern("DE AD BE EF"), dead_beef);
+        assert_eq!(parse_hex_pattern("deadbeef"), dead_beef);
+        assert_eq!(parse_hex_pattern(r"\xDE\xAD\xBE\xEF"), dead_beef);
+        assert_eq!(parse_hex_pattern("0xdead 0xBEEF"), dead_beef);
+        assert_eq!(parse_hex_pattern(""), None);
+        assert_eq!(parse_hex_pattern("DEA"), None);
+        assert_eq!(parse_hex_pattern("+f"), None);
+        assert_eq!(parse_hex_pattern("zz"), None);
+    }
+
+    #[test]
+    fn test_parse_ripgrep_command() {
+        assert_eq!(
+            parse_ripgrep_command("rg -i --glob '!target' -e 'foo.*bar'"),
+            Some(RipgrepCommand {
+                query: "foo.*bar".to_string(),
+                search_options: SearchOptions::REGEX,
+                files_to_include: Vec::new(),
+                files_to_exclude: vec!["target".to_string()],
+                ignored_arguments: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse_ripgrep_command("rg -wFn -g '*.rs' --hidden 'a.b' src/"),
+            Some(RipgrepCommand {
+                query: "a.b".to_string(),
+                search_options: SearchOptions::CASE_SENSITIVE | SearchOptions::WHOLE_WORD,
+                files_to_include: vec!["*.rs".to_string()],
+                files_to_exclude: Vec::new(),
+                ignored_arguments: vec![
+                    "-n".to_string(),
+                    "--hidden".to_string(),
+                    "src/".to_string(),
+                ],
+            })
+        );
+        assert_eq!(
+            parse_ripgrep_command("grep -F -e a.b -e c --include=*.md --exclude *.txt"),
+            Some(RipgrepCommand {
+                query: r"a\.b|c".to_string(),
+                search_options: SearchOptions::CASE_SENSITIVE | SearchOptions::REGEX,
+                files_to_include: vec!["*.md".to_string()],
+                files_to_exclude: vec!["*.txt".to_string()],
+                ignored_arguments: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse_ripgrep_command("/usr/bin/rg -- -i")
+                .map(|command| (command.query, command.search_options)),
+            Some((
+                "-i".to_string(),
+                SearchOptions::CASE_SENSITIVE | SearchOptions::REGEX
+            ))
+        );
+        assert_eq!(parse_ripgrep_command("rg -i"), None);
+        assert_eq!(parse_ripgrep_command("ag foo"), None);
+        assert_eq!(parse_ripgrep_command("rg 'foo"), None);
+    }
+
+    #[gpui::test]
+    async fn test_import_ripgrep_command(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+
+        cx.write_to_clipboard(ClipboardItem::new(
+            "rg -i --glob '!two.rs' --hidden -e 'one'".to_string(),
+        ));
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::import_ripgrep_command(workspace, &ImportRipgrepCommand, cx)
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after importing a command")
+        });
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "one");
+                    assert_eq!(search_view.search_options, SearchOptions::REGEX);
+                    assert!(search_view.filters_enabled);
+                    assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                    assert_eq!(
+                        search_view.excluded_files_editor.read(cx).text(cx),
+                        "two.rs"
+                    );
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst ONE: usize = 1;"
+                    );
+                })
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_hex_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        fs.insert_file(
+            "/dir/blob.bin",
+            vec![0x00, 0xff, 0xde, 0xad, 0xbe, 0xef, b'o', b'k', 0xde, 0xad],
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_hex_search(cx))
+            .unwrap();
+        perform_search(search_view, "not hex", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.query_error.is_some());
+                assert!(!search_view.has_hex_matches(cx));
+            })
+            .unwrap();
+
+        perform_search(search_view, r"\xDE\xAD", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_error, None);
+                assert!(search_view.has_hex_matches(cx));
+                let hex_matches = &search_view.model.read(cx).hex_matches;
+                assert_eq!(
+                    hex_matches
+                        .iter()
+                        .map(|hex_match| (hex_match.path.path.to_path_buf(), hex_match.offset))
+                        .collect::<Vec<_>>(),
+                    [
+                        (PathBuf::from("blob.bin"), 2),
+                        (PathBuf::from("blob.bin"), 8)
+                    ]
+                );
+                assert_eq!(
+                    hex_excerpt(&hex_matches[0]),
+                    (
+                        "00 ff de ad be ef 6f 6b de ad  |......ok..|".to_string(),
+                        vec![6, 7, 9, 10, 34, 35]
+                    )
+                );
+            })
+            .unwrap();
+
+        // Regular UTF-8 files are searched too.
+        perform_search(search_view, "4F 4E 45", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let hex_matches = &search_view.model.read(cx).hex_matches;
+                assert_eq!(hex_matches.len(), 1);
+                assert_eq!(hex_matches[0].path.path.as_ref(), Path::new("one.rs"));
+                assert_eq!(hex_matches[0].offset, 6);
+            })
+            .unwrap();
+
+        // The matches are capped like the ones of text searches.
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.max_matches = Some(1);
+                });
+            });
+        });
+        perform_search(search_view, r"\xDE\xAD", cx);
+        search.read_with(cx, |search, _| {
+            assert_eq!(search.hex_matches.len(), 1);
+            assert!(search.truncated);
+        });
+    }
+
+    #[test]
+    fn test_find_hex_matches() {
+        let path = ProjectPath {
+            worktree_id: WorktreeId::from_usize(0),
+            path: Path::new("blob.bin").into(),
+        };
+        // An occurrence across two chunks, with its context in both.
+        let mut bytes = vec![0; HEX_SEARCH_CHUNK_SIZE + 16];
+        let offset = HEX_SEARCH_CHUNK_SIZE - 1;
+        bytes[offset..offset + 2].copy_from_slice(&[0xde, 0xad]);
+        bytes[bytes.len() - 2..].copy_from_slice(&[0xde, 0xad]);
+        let matches = find_hex_matches(&path, bytes.as_slice(), &[0xde, 0xad], 10).unwrap();
+        assert_eq!(
+            matches
+                .iter()
+                .map(|hex_match| (hex_match.offset, hex_match.context_start))
+                .collect::<Vec<_>>(),
+            [
+                (offset, offset - HEX_CONTEXT_BYTES),
+                (bytes.len() - 2, bytes.len() - 2 - HEX_CONTEXT_BYTES)
+            ]
+        );
+        assert_eq!(
+            matches[0].context,
+            &bytes[offset - HEX_CONTEXT_BYTES..offset + 2 + HEX_CONTEXT_BYTES]
+        );
+        assert_eq!(
+            matches[1].context,
+            &bytes[bytes.len() - 2 - HEX_CONTEXT_BYTES..]
+        );
+
+        let matches = find_hex_matches(&path, bytes.as_slice(), &[0xde, 0xad], 1).unwrap();
+        assert_eq!(matches.len(), 1);
+
+        let bytes = vec![0xde; HEX_SEARCH_MAX_FILE_SIZE + 1];
+        let matches = find_hex_matches(&path, bytes.as_slice(), &[0xde], 10).unwrap();
+        assert!(matches.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_open_results_in_split(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), cx);
+                search_view
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.set_query("TWO", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| search_view.open_results_in_split(cx));
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let results_editor = window
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 2);
+                let editor = workspace
+                    .active_pane()
+                    .read(cx)
+                    .active_item()
+                    .and_then(|item| item.downcast::<Editor>())
+                    .expect("The results should be opened in an editor in the new pane");
+                assert_eq!(
+                    editor.read(cx).buffer(),
+                    &search_view.read(cx).model.read(cx).excerpts,
+                    "The split editor should show the live results of the search"
+                );
+                editor
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                let selection = |editor: &View<Editor>, cx: &mut WindowContext| {
+                    editor.update(cx, |editor, cx| {
+                        editor.selections.newest::<usize>(cx).range()
+                    })
+                };
+                assert_eq!(
+                    selection(&results_editor, cx),
+                    selection(&search_view.read(cx).results_editor.clone(), cx),
+                    "The active match should be selected in the split editor"
+                );
+            })
+            .unwrap();
+
+        // Rerunning the search updates the results shown in the split.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.set_query("THREE", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                assert_eq!(
+                    results_editor.update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_breadcrumbs_show_active_match_location(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "// Two\n\nconst TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let breadcrumbs = |search_view: &ProjectSearchView,
+                           cx: &mut ViewContext<ProjectSearchView>| {
+            search_view
+                .breadcrumbs(cx.theme(), cx)
+                .unwrap()
+                .into_iter()
+                .map(|breadcrumb| breadcrumb.text)
+                .collect::<Vec<_>>()
+        };
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(breadcrumbs(search_view, cx), ["one.rs:1"]);
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                assert_eq!(breadcrumbs(search_view, cx), ["two.rs:3"]);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::REGEX;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("${1}_VALUE: u32", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, r"(\w+): usize", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_replace_preview(cx);
+                assert!(search_view.replace_preview);
+                let query = search_view.replace_preview_query(cx).unwrap();
+                let rows = (0..3)
+                    .filter_map(|ix| search_view.replace_preview_row(ix, &query, cx))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    rows,
+                    [
+                        (
+                            "one.rs:1".to_string(),
+                            "const ONE: usize = 1;".to_string(),
+                            "const ONE_VALUE: u32 = 1;".to_string(),
+                        ),
+                        (
+                            "three.rs:1".to_string(),
+                            "const THREE: usize = one::ONE + two::TWO;".to_string(),
+                            "const THREE_VALUE: u32 = one::ONE + two::TWO;".to_string(),
+                        ),
+                        (
+                            "two.rs:1".to_string(),
+                            "const TWO: usize = one::ONE + one::ONE;".to_string(),
+                            "const TWO_VALUE: u32 = one::ONE + one::ONE;".to_string(),
+                        ),
+                    ]
+                );
+                assert!(
+                    !search_view
+                        .results_editor
+                        .read(cx)
+                        .text(cx)
+                        .contains("VALUE"),
+                    "Previewing the replacements should not edit the buffers"
+                );
+
+                search_view.confirm_replace_preview(cx);
+                assert!(!search_view.replace_preview);
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const ONE_VALUE: u32 = 1;"));
+                assert!(text.contains("const TWO_VALUE: u32 = one::ONE + one::ONE;"));
+                assert!(text.contains("const THREE_VALUE: u32 = one::ONE + two::TWO;"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_all_in_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("uno", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        // Matches are in one.rs (1), three.rs (1), then two.rs (2).
+        search_view
+            .update(cx, |search_view, cx| search_view.go_to_match(3, cx))
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(2));
+                search_view.replace_all_in_file(&ReplaceAllInFile, cx);
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const ONE: usize = 1;"));
+                assert!(text.contains("const THREE: usize = one::ONE + two::TWO;"));
+                assert!(text.contains("const TWO: usize = one::uno + one::uno;"));
+                // Only the matches of the other files are left.
+                let model = search_view.model.read(cx);
+                assert_eq!(model.match_ranges.len(), 2);
+                assert_eq!(model.total_match_count, 2);
+
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.undo(&editor::actions::Undo, cx));
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const TWO: usize = one::ONE + one::ONE;"));
+                let model = search_view.model.read(cx);
+                assert_eq!(model.match_ranges.len(), 4);
+                assert_eq!(model.total_match_count, 4);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_exclude_matches_from_replace_all(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("u32", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, "usize", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                search_view.toggle_match_excluded(cx);
+                assert!(search_view.is_match_excluded(1, cx));
+                assert_eq!(search_view.excluded_matches.len(), 1);
+                search_view.toggle_match_excluded(cx);
+                assert!(search_view.excluded_matches.is_empty());
+                search_view.toggle_match_excluded(cx);
+
+                search_view.replace_all(&ReplaceAll, cx);
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const ONE: u32 = 1;"));
+                assert!(text.contains("const THREE: usize = one::ONE + two::TWO;"));
+                assert!(text.contains("const TWO: u32 = one::ONE + one::ONE;"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_excluded_matches_survive_rebuilding_excerpts(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("u32", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, "usize", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                search_view.toggle_match_excluded(cx);
+                search_view.model.update(cx, |model, cx| {
+                    model.set_context_lines(0, 0, cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                // The excerpts were rebuilt under the same search, with new match ranges.
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                assert_eq!(
+                    (0..3)
+                        .filter(|ix| search_view.is_match_excluded(*ix, cx))
+                        .count(),
+                    1
+                );
+
+                search_view.replace_all(&ReplaceAll, cx);
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const ONE: u32 = 1;"));
+                assert!(text.contains("const THREE: usize = one::ONE + two::TWO;"));
+                assert!(text.contains("const TWO: u32 = one::ONE + one::ONE;"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_undo_replace_all_restores_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("u32", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, "usize", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.select_match(Direction::Next, cx)
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                search_view.toggle_match_excluded(cx);
+                search_view.replace_all(&ReplaceAll, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                // Only the excluded match is left after replacing the others.
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                assert!(search_view.is_match_excluded(0, cx));
+                let match_background_color = cx.theme().colors().search_match_background;
+                let highlights = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx));
+                assert_eq!(
+                    highlights
+                        .iter()
+                        .filter(|(_, color)| *color == match_background_color)
+                        .count(),
+                    1
+                );
+
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.undo(&editor::actions::Undo, cx));
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                let text = search_view.results_editor.read(cx).text(cx);
+                assert!(text.contains("const ONE: usize = 1;"));
+                assert!(text.contains("const TWO: usize = one::ONE + one::ONE;"));
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                assert!(search_view.is_match_excluded(1, cx));
+                assert_eq!(search_view.excluded_matches.len(), 1);
+                let match_background_color = cx.theme().colors().search_match_background;
+                let highlights = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx));
+                assert_eq!(
+                    highlights
+                        .iter()
+                        .filter(|(_, color)| *color == match_background_color)
+                        .count(),
+                    3
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_cycle_fields(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.panes()[0].update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        let focused_field = |cx: &mut WindowContext| {
+            let search_view = search_view.read(cx);
+            [
+                ("query", &search_view.query_editor),
+                ("replacement", &search_view.replacement_editor),
+                ("included", &search_view.included_files_editor),
+                ("excluded", &search_view.excluded_files_editor),
+            ]
+            .into_iter()
+            .find(|(_, editor)| editor.focus_handle(cx).is_focused(cx))
+            .map(|(name, _)| name)
+        };
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.replace_enabled = true;
+                    search_view.filters_enabled = true;
+                    search_view.query_editor.focus_handle(cx).focus(cx);
+                });
+
+                let mut fields = Vec::new();
+                for _ in 0..4 {
+                    search_bar.update(cx, |search_bar, cx| {
+                        search_bar.cycle_field(Direction::Next, cx)
+                    });
+                    fields.push(focused_field(cx));
+                }
+                assert_eq!(
+                    fields,
+                    [
+                        Some("replacement"),
+                        Some("included"),
+                        Some("excluded"),
+                        Some("query")
+                    ]
+                );
+
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.cycle_field(Direction::Prev, cx)
+                });
+                assert_eq!(
+                    focused_field(cx),
+                    Some("excluded"),
+                    "Going back from the first field should wrap around to the last one"
+                );
+
+                search_view.update(cx, |search_view, cx| {
+                    search_view.replace_enabled = false;
+                    search_view.query_editor.focus_handle(cx).focus(cx);
+                });
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.cycle_field(Direction::Next, cx)
+                });
+                assert_eq!(
+                    focused_field(cx),
+                    Some("included"),
+                    "The replacement editor should be skipped while replacing is disabled"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let panes: Vec<_> = window
+            .update(cx, |this, _| this.panes().to_owned())
+            .unwrap();
+
+        let search_bar_1 = window.build_view(cx, |_| ProjectSearchBar::new());
+        let search_bar_2 = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        assert_eq!(panes.len(), 1);
+        let first_pane = panes.get(0).cloned().unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
+        window
+            .update(cx, |workspace, cx| {
+                workspace.open_path(
+                    (worktree_id, "one.rs"),
+                    Some(first_pane.downgrade()),
+                    true,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+
+        // Add a project search item to the first pane
+        window
+            .update(cx, {
+                let search_bar = search_bar_1.clone();
+                let pane = first_pane.clone();
+                move |workspace, cx| {
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view_1 = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        let second_pane = window
+            .update(cx, |workspace, cx| {
+                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
+
+        // Add a project search item to the second pane
+        window
+            .update(cx, {
+                let search_bar = search_bar_2.clone();
+                let pane = second_pane.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+
+        let search_view_2 = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        cx.run_until_parked();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
+
+        let update_search_view =
+            |search_view: &View<ProjectSearchView>, query: &str, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_view.update(cx, |search_view, cx| {
+                            search_view
+                                .query_editor
+                                .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+                            search_view.search(cx);
+                        });
+                    })
+                    .unwrap();
+            };
+
+        let active_query =
+            |search_view: &View<ProjectSearchView>, cx: &mut TestAppContext| -> String {
+                window
+                    .update(cx, |_, cx| {
+                        search_view.update(cx, |search_view, cx| {
+                            search_view.query_editor.read(cx).text(cx).to_string()
+                        })
+                    })
+                    .unwrap()
+            };
+
+        let select_prev_history_item =
+            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_bar.update(cx, |search_bar, cx| {
+                            search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                        })
+                    })
+                    .unwrap();
+            };
+
+        let select_next_history_item =
+            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
+                window
+                    .update(cx, |_, cx| {
+                        search_bar.update(cx, |search_bar, cx| {
+                            search_bar.next_history_query(&NextHistoryQuery, cx);
+                        })
+                    })
+                    .unwrap();
+            };
+
+        update_search_view(&search_view_1, "ONE", cx);
+        cx.background_executor.run_until_parked();
+
+        update_search_view(&search_view_2, "TWO", cx);
+        cx.background_executor.run_until_parked();
+
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
+
+        // Selecting previous history item should select the query from search view 1.
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Selecting the previous history item should not change the query as it is already the first item.
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Changing the query in search view 2 should not affect the history of search view 1.
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+
+        // Deploying a new search in search view 2
+        update_search_view(&search_view_2, "THREE", cx);
+        cx.background_executor.run_until_parked();
+
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "THREE");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        select_prev_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "ONE");
+
+        // Search view 1 should now see the query from search view 2.
+        assert_eq!(active_query(&search_view_1, cx), "ONE");
+
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "TWO");
+
+        // Here is the new query from search view 2
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "THREE");
+
+        select_next_history_item(&search_bar_2, cx);
+        assert_eq!(active_query(&search_view_2, cx), "");
+
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "TWO");
+
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "THREE");
+
+        select_next_history_item(&search_bar_1, cx);
+        assert_eq!(active_query(&search_view_1, cx), "");
+    }
+
+    #[gpui::test]
+    async fn test_recent_queries(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        perform_search(search_view, "TWO", cx);
+        perform_search(search_view, "TWO", cx);
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.recent_queries(cx), ["ONE", "TWO"]);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_match_ranges_stay_sorted(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "const A: usize = ONE;",
+                "b.rs": "const B: usize = ONE + ONE;",
+                "c.rs": "const C: usize = ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search.update(cx, |search, cx| {
+            let snapshot = search.excerpts.read(cx).snapshot(cx);
+            let starts = |match_ranges: &[Range<Anchor>]| {
+                match_ranges
+                    .iter()
+                    .map(|range| range.start.to_point(&snapshot))
+                    .collect::<Vec<_>>()
+            };
+            let sorted_starts = starts(&search.match_ranges);
+            assert_eq!(sorted_starts.len(), 4);
+            assert!(sorted_starts.windows(2).all(|pair| pair[0] < pair[1]));
+
+            let match_ranges = mem::take(&mut search.match_ranges);
+            for ix in [2, 0, 3, 1] {
+                search.insert_match_range(match_ranges[ix].clone(), &snapshot);
+            }
+            assert_eq!(starts(&search.match_ranges), sorted_starts);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_total_match_count(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1; const TWO: usize = ONE + ONE;",
+                "two.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search.update(cx, |search, _| {
+            assert!(search.pending_search.is_none());
+            assert_eq!(search.total_match_count, 4);
+            assert_eq!(search.total_match_count, search.match_ranges.len());
+        });
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(
+                    search_view.match_summary,
+                    Some("4 matches in 2 files".into())
+                );
+            })
+            .unwrap();
+
+        perform_search(search_view, "THREE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.match_summary, Some("1 match in 1 file".into()));
+            })
+            .unwrap();
+
+        perform_search(search_view, "NOTHING", cx);
+        search.update(cx, |search, _| {
+            assert_eq!(search.total_match_count, 0);
+        });
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.match_summary, None);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_invert_match(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;\n\nconst TWO: usize = 2;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, _| search_view.invert_match = true)
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert!(model.active_query.as_ref().unwrap().inverted());
+                assert_eq!(model.total_match_count, 1);
+                assert_eq!(model.match_ranges.len(), 1);
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(results_text.contains("const TWO: usize = 2;"));
+                assert!(!results_text.contains("one::ONE"));
+                assert_eq!(search_view.active_match_index, Some(0));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_replace_disabled_while_inverted(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;\n\nconst TWO: usize = 2;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.invert_match = true;
+                search_view.replace_enabled = true;
+                search_view.replacement_editor.update(cx, |editor, cx| {
+                    editor.set_text("THREE", cx);
+                });
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.can_replace(cx));
+                let results_text = search_view.results_editor.read(cx).text(cx);
+                search_view.replace_next(&ReplaceNext, cx);
+                search_view.replace_all_in_file(&ReplaceAllInFile, cx);
+                search_view.replace_all(&ReplaceAll, cx);
+                search_view.toggle_replace_preview(cx);
+                assert!(!search_view.replace_preview);
+                assert_eq!(search_view.results_editor.read(cx).text(cx), results_text);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+
+                search_view.invert_match = false;
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.can_replace(cx));
+                search_view.replace_all(&ReplaceAll, cx);
+                let results_text = search_view.results_editor.read(cx).text(cx);
+                assert!(results_text.contains("const THREE: usize = 1;"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_multi_pattern(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = crate::ONE;",
+                "three.rs": "let three = 3;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, _| search_view.multi_pattern = true)
+            .unwrap();
+        perform_search(search_view, "ONE | three | 3.", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert!(model.active_query.as_ref().unwrap().is_regex());
+                assert_eq!(model.match_ranges.len(), 3);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_peek_match(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.focus_query_editor(cx);
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.peek_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                assert!(search_view.query_editor.focus_handle(cx).is_focused(cx));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35)]
+                );
+                assert!(search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx))
+                    .contains(&(
+                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
+                        cx.theme().colors().search_active_match_background
+                    )));
+
+                search_view.peek_match(Direction::Next, cx);
+                assert_eq!(search_view.active_match_index, Some(2));
+                search_view.select_match(Direction::Prev, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(1));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
+                    [DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40)]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_select_match_in_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.focus_query_editor(cx);
+                assert!(!search_view.select_match_in_results(Direction::Next, 1, cx));
+                assert_eq!(search_view.active_match_index, Some(0));
+
+                search_view.focus_results_editor(cx);
+                assert!(search_view.select_match_in_results(Direction::Next, 2, cx));
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(2));
+                assert!(search_view.select_match_in_results(Direction::Prev, 1, cx));
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(1));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_go_to_match(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        for (number, expected_index) in [(3, 2), (2, 1), (0, 0), (99, 2), (usize::MAX, 2)] {
+            search_view
+                .update(cx, |search_view, cx| search_view.go_to_match(number, cx))
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    assert_eq!(
+                        search_view.active_match_index,
+                        Some(expected_index),
+                        "going to match {number}"
+                    );
+                })
+                .unwrap();
+        }
+    }
+
+    #[gpui::test]
+    async fn test_select_match_without_wrap_around(cx: &mut TestAppContext) {
+        init_test(cx);
+        let set_wrap_around = |wrap_around, cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|settings, cx| {
+                    settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                        settings.wrap_around = Some(wrap_around);
+                    });
+                });
+            });
+        };
+        set_wrap_around(false, cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        // Returns the active match index after the selection, and whether the index flashes.
+        let select_match = |direction, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.select_match(direction, cx)
+                })
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    (
+                        search_view.active_match_index,
+                        search_view.end_of_results_flash.is_some(),
+                    )
+                })
+                .unwrap()
+        };
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(select_match(Direction::Prev, cx), (Some(0), true));
+        cx.executor().advance_clock(END_OF_RESULTS_FLASH);
+        cx.background_executor.run_until_parked();
+        assert_eq!(select_match(Direction::Next, cx), (Some(1), false));
+        assert_eq!(select_match(Direction::Next, cx), (Some(2), false));
+        assert_eq!(select_match(Direction::Next, cx), (Some(2), true));
+        cx.executor().advance_clock(END_OF_RESULTS_FLASH);
+        cx.background_executor.run_until_parked();
+
+        set_wrap_around(true, cx);
+        assert_eq!(select_match(Direction::Next, cx), (Some(0), false));
+        assert_eq!(select_match(Direction::Prev, cx), (Some(2), false));
+    }
+
+    #[gpui::test]
+    async fn test_match_navigation_history(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let active_match_index = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, _| search_view.active_match_index)
+                .unwrap()
+        };
+        let go_to_match = |number, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| search_view.go_to_match(number, cx))
+                .unwrap();
+            active_match_index(cx)
+        };
+        let navigate = |direction, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.navigate_match_history(direction, cx)
+                })
+                .unwrap();
+            active_match_index(cx)
+        };
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(active_match_index(cx), Some(0));
+        assert_eq!(go_to_match(3, cx), Some(2));
+        assert_eq!(go_to_match(2, cx), Some(1));
+
+        assert_eq!(navigate(Direction::Prev, cx), Some(2));
+        assert_eq!(navigate(Direction::Prev, cx), Some(0));
+        assert_eq!(navigate(Direction::Prev, cx), Some(0));
+        assert_eq!(navigate(Direction::Next, cx), Some(2));
+
+        // Visiting another match drops the matches that were navigated back from.
+        assert_eq!(go_to_match(1, cx), Some(0));
+        assert_eq!(navigate(Direction::Next, cx), Some(0));
+        assert_eq!(navigate(Direction::Prev, cx), Some(2));
+        assert_eq!(navigate(Direction::Prev, cx), Some(0));
+
+        // A new search starts a new history.
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.match_history, vec![0]);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_bookmark_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let go_to_match = |number, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| search_view.go_to_match(number, cx))
+                .unwrap();
+        };
+        let bookmark_match = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.toggle_match_bookmarked(cx);
+                    search_view.bookmarks.len()
+                })
+                .unwrap()
+        };
+        let navigate = |direction, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.navigate_bookmarks(direction, cx)
+                })
+                .unwrap();
+            search_view
+                .update(cx, |search_view, _| {
+                    (search_view.active_bookmark, search_view.active_match_index)
+                })
+                .unwrap()
+        };
+
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE;
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        go_to_match(1, cx);
+        assert_eq!(bookmark_match(cx), 1);
+        go_to_match(3, cx);
+        assert_eq!(bookmark_match(cx), 2);
+        // A match on a bookmarked line doesn't add another bookmark.
+        go_to_match(4, cx);
+        assert_eq!(bookmark_match(cx), 1);
+        assert_eq!(bookmark_match(cx), 2);
+
+        assert_eq!(navigate(Direction::Next, cx), (Some(0), Some(0)));
+        assert_eq!(navigate(Direction::Next, cx), (Some(1), Some(3)));
+        assert_eq!(navigate(Direction::Prev, cx), (Some(0), Some(0)));
+
+        // The bookmarks are kept when searching for something else.
+        perform_search(search_view, "usize", cx);
+        assert_eq!(navigate(Direction::Prev, cx), (Some(1), Some(2)));
+        assert_eq!(navigate(Direction::Prev, cx), (Some(0), Some(0)));
+        assert_eq!(bookmark_match(cx), 1);
+    }
+
+    #[gpui::test]
+    async fn test_swap_with_previous_query(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let swap_with_previous_query = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.swap_with_previous_query(cx)
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+            search_view
+                .update(cx, |search_view, cx| {
+                    (
+                        search_view.query_editor.read(cx).text(cx),
+                        search_view.model.read(cx).match_ranges.len(),
+                    )
+                })
+                .unwrap()
+        };
+
+        assert_eq!(swap_with_previous_query(cx), (String::new(), 0));
+        perform_search(search_view, "ONE", cx);
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(swap_with_previous_query(cx), ("ONE".to_string(), 4));
+        assert_eq!(swap_with_previous_query(cx), ("TWO".to_string(), 2));
+        assert_eq!(swap_with_previous_query(cx), ("ONE".to_string(), 4));
+    }
+
+    #[gpui::test]
+    async fn test_reset_search_options(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.search_options = SearchOptions::CASE_SENSITIVE | SearchOptions::REGEX;
+                search_view.multi_pattern = true;
+                search_view.symbol_scope = SymbolScope::Identifiers;
+                search_view.symbol_kind_filter = Some(lsp::SymbolKind::STRUCT);
+                search_view.include_dependencies = true;
+                search_view.case_preserving_replace = true;
+            })
+            .unwrap();
+        perform_search(search_view, "one", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                search_view.reset_search_options(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.search_options, SearchOptions::NONE);
+                assert!(!search_view.multi_pattern);
+                assert!(!search_view.invert_match);
+                assert!(matches!(search_view.scope, SearchScope::Project));
+                assert_eq!(search_view.symbol_scope, SymbolScope::All);
+                assert_eq!(search_view.symbol_kind_filter, None);
+                assert!(!search_view.include_dependencies);
+                assert!(!search_view.case_preserving_replace);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 7);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_tab_title_match_count(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let tab_title = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| search_view.tab_title(cx).to_string())
+                .unwrap()
+        };
+
+        assert_eq!(tab_title(cx), "Project Search");
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(tab_title(cx), "TWO (3)");
+        perform_search(search_view, "FOUR", cx);
+        assert_eq!(tab_title(cx), "FOUR");
+        // The query is truncated so that the count still fits.
+        perform_search(search_view, "const TWO: usize = one::ONE", cx);
+        assert_eq!(tab_title(cx), "const TWO: usize = o… (1)");
+    }
+
+    #[gpui::test]
+    async fn test_research_keeps_nearest_match_selected(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let selected_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view.results_editor.update(cx, |editor, cx| {
+                        let range = editor.selections.newest::<usize>(cx).range();
+                        editor
+                            .buffer()
+                            .read(cx)
+                            .snapshot(cx)
+                            .text_for_range(range)
+                            .collect::<String>()
+                    })
+                })
+                .unwrap()
+        };
+
+        // Select the second `ONE` of `two.rs`, the last match of the project.
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| search_view.go_to_match(7, cx))
+            .unwrap();
+
+        // Both `one::ONE` of `two.rs` are left, the second of which is the nearest.
+        perform_search(search_view, "one::ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                assert_eq!(search_view.active_match_index, Some(2));
+                assert!(search_view.pending_previous_match.is_none());
+            })
+            .unwrap();
+        assert_eq!(selected_text(cx), "one::ONE");
+
+        // Without matches left in `one.rs`, the first match is selected.
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| search_view.go_to_match(1, cx))
+            .unwrap();
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(0));
+            })
+            .unwrap();
+        assert_eq!(selected_text(cx), "two");
+    }
+
+    #[gpui::test]
+    async fn test_collapse_all_groups(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        let folded_matches = |search_view: &mut ProjectSearchView, cx: &mut ViewContext<_>| {
+            let match_ranges = search_view.model.read(cx).match_ranges.clone();
+            let snapshot = search_view
+                .results_editor
+                .update(cx, |editor, cx| editor.snapshot(cx));
+            match_ranges
+                .iter()
+                .map(|range| snapshot.intersects_fold(range.start))
+                .collect::<Vec<_>>()
+        };
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.collapse_all_groups(cx);
+                assert_eq!(
+                    folded_matches(search_view, cx),
+                    [false, true, true, true, true, true, true]
+                );
+                assert_eq!(search_view.active_match_index, Some(0));
+
+                search_view.expand_all_groups(cx);
+                assert_eq!(folded_matches(search_view, cx), [false; 7]);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_truncate_long_lines(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.max_excerpt_line_length = Some(16);
+                });
+            });
+        });
+
+        let line = format!(
+            "{}needle{}needle{}",
+            "x".repeat(30),
+            "y".repeat(30),
+            "z".repeat(30)
+        );
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "long.txt": line })).await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "needle", cx);
+        let results_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+        assert!(
+            results_text(cx).contains("⋯xxxxxneedleyyyyy⋯"),
+            "the first match is centered: {:?}",
+            results_text(cx)
+        );
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.select_match(Direction::Next, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert!(
+            results_text(cx).contains("⋯yyyyyneedlezzzzz⋯"),
+            "the active match is centered: {:?}",
+            results_text(cx)
+        );
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.collapse_all_groups(cx);
+                search_view.expand_all_groups(cx);
+            })
+            .unwrap();
+        assert!(
+            results_text(cx).contains("⋯yyyyyneedlezzzzz⋯"),
+            "expanding the results keeps the line truncated: {:?}",
+            results_text(cx)
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+
+        let query = SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let matches = search
+            .update(cx, |search, cx| search.find_matches(query, cx))
+            .await;
+        let mut matches = cx.read(|cx| {
+            matches
+                .into_iter()
+                .map(|(buffer, ranges)| {
+                    let buffer = buffer.read(cx);
+                    (
+                        buffer.file().unwrap().path().to_string_lossy().to_string(),
+                        ranges
+                            .iter()
+                            .map(|range| buffer.text_for_range(range.clone()).collect::<String>())
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        matches.sort();
+        assert_eq!(
+            matches,
+            [
+                ("three.rs".to_string(), vec!["TWO".to_string()]),
+                ("two.rs".to_string(), vec!["TWO".to_string()]),
+            ]
+        );
+        search.read_with(cx, |search, cx| {
+            assert!(search.match_ranges.is_empty());
+            assert!(search.excerpts.read(cx).excerpt_ids().is_empty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_structured_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+
+        let query = SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap();
+        search.update(cx, |search, cx| search.search(query, cx));
+        cx.background_executor.run_until_parked();
+        search.read_with(cx, |search, cx| {
+            assert_eq!(
+                search.structured_matches(cx),
+                [
+                    MatchInfo {
+                        path: PathBuf::from("dir/three.rs"),
+                        line: 1,
+                        column: 38,
+                        text: "TWO".to_string(),
+                    },
+                    MatchInfo {
+                        path: PathBuf::from("dir/two.rs"),
+                        line: 1,
+                        column: 7,
+                        text: "TWO".to_string(),
+                    },
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_search_cache(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = 3;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        // Returns whether the project was searched, which is the only way matches get streamed,
+        // along with the number of matches shown.
+        let search_for_one = |cx: &mut TestAppContext| {
+            let query =
+                SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+            let searched_project = search.update(cx, |search, cx| {
+                search.search(query, cx);
+                search.match_stream.is_some()
+            });
+            cx.background_executor.run_until_parked();
+            let match_count = search.read_with(cx, |search, _| search.match_ranges.len());
+            (searched_project, match_count)
+        };
+
+        assert_eq!(search_for_one(cx), (true, 3));
+        search.read_with(cx, |search, _| assert_eq!(search.search_cache.len(), 1));
+
+        // The project is not searched again, so the match written to disk is not found until
+        // the worktree reports the change.
+        fs.pause_events();
+        fs.insert_file("/dir/three.rs", b"const THREE: usize = one::ONE;".to_vec())
+            .await;
+        assert_eq!(search_for_one(cx), (false, 3));
+        fs.flush_events(fs.buffered_event_count());
+        cx.background_executor.run_until_parked();
+        assert_eq!(search_for_one(cx), (true, 4));
+        assert_eq!(search_for_one(cx), (false, 4));
+
+        let buffer = search.read_with(cx, |search, _| search.buffer_matches[0].0.clone());
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "ONE ")], None, cx));
+        assert_eq!(search_for_one(cx), (true, 5));
+        search.read_with(cx, |search, _| assert_eq!(search.search_cache.len(), 1));
+    }
+
+    #[gpui::test]
+    async fn test_search_from_ranges(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let project_path = |path: &str| ProjectPath {
+            worktree_id,
+            path: Path::new(path).into(),
+        };
+        let spans = vec![
+            (project_path("two.rs"), 6..9),
+            (project_path("one.rs"), 0..5),
+            (project_path("two.rs"), 0..5),
+        ];
+        let search = cx.new_model(|cx| ProjectSearch::from_ranges(project, spans, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        cx.background_executor.run_until_parked();
+
+        search.read_with(cx, |search, cx| {
+            assert!(search.active_query.is_none());
+            assert_eq!(
+                search.structured_matches(cx),
+                [
+                    MatchInfo {
+                        path: PathBuf::from("dir/two.rs"),
+                        line: 1,
+                        column: 1,
+                        text: "const".to_string(),
+                    },
+                    MatchInfo {
+                        path: PathBuf::from("dir/two.rs"),
+                        line: 1,
+                        column: 7,
+                        text: "TWO".to_string(),
+                    },
+                    MatchInfo {
+                        path: PathBuf::from("dir/one.rs"),
+                        line: 1,
+                        column: 1,
+                        text: "const".to_string(),
+                    },
+                ]
+            );
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.select_match(Direction::Next, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(1));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_keep_previous_results_on_empty(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.keep_previous_results_on_empty = Some(true);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let excerpt_count = |cx: &mut TestAppContext| {
+            search.read_with(cx, |search, cx| {
+                search.excerpts.read(cx).excerpt_ids().len()
+            })
+        };
+
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(excerpt_count(cx), 2);
+
+        perform_search(search_view, "THREE", cx);
+        assert_eq!(excerpt_count(cx), 2);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.has_matches());
+                assert!(search_view.model.read(cx).showing_previous_results);
+                assert!(search_view.model.read(cx).match_ranges.is_empty());
+            })
+            .unwrap();
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(excerpt_count(cx), 1);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.has_matches());
+                assert!(!search_view.model.read(cx).showing_previous_results);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_sort_by_relevance(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "let someone = 1;",
+                "b.rs": "let one = 1;",
+                "one.rs": "let two = 2; // someone",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let result_paths = |cx: &mut TestAppContext| {
+            search.update(cx, |search, cx| {
+                search
+                    .excerpts
+                    .read(cx)
+                    .snapshot(cx)
+                    .excerpts()
+                    .map(|(_, buffer, _)| {
+                        buffer.file().unwrap().path().to_string_lossy().to_string()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        perform_search(search_view, "one", cx);
+        assert_eq!(result_paths(cx), ["a.rs", "b.rs", "one.rs"]);
+
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_sort_order(cx))
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(result_paths(cx), ["one.rs", "b.rs", "a.rs"]);
+        search.read_with(cx, |search, _| assert_eq!(search.match_ranges.len(), 5));
+
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_sort_order(cx))
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(result_paths(cx), ["a.rs", "b.rs", "one.rs"]);
+    }
+
+    #[gpui::test]
+    async fn test_search_completed_event(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        let completed_searches = Rc::new(RefCell::new(Vec::new()));
+        let view = search_view.root(cx).unwrap();
+        let _subscription = cx.update(|cx| {
+            let completed_searches = completed_searches.clone();
+            cx.subscribe(&view, move |_, event, _| {
+                if let ViewEvent::SearchCompleted { match_count } = event {
+                    completed_searches.borrow_mut().push(*match_count);
+                }
+            })
+        });
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("ONE", cx);
+                search_view.search(cx);
+            })
+            .unwrap();
+        assert!(completed_searches.borrow().is_empty());
+        cx.background_executor.run_until_parked();
+        assert_eq!(*completed_searches.borrow(), [5]);
+
+        perform_search(search_view, "NOTHING", cx);
+        assert_eq!(*completed_searches.borrow(), [5, 0]);
+    }
+
+    #[gpui::test]
+    async fn test_default_search_options(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.case_sensitive = Some(true);
+                    settings.whole_word = Some(true);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.search_options,
+                    SearchOptions::CASE_SENSITIVE | SearchOptions::WHOLE_WORD
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+            })
+            .unwrap();
+
+        // Views restored from an existing search take the options of its query.
+        search.update(cx, |search, cx| {
+            let query =
+                SearchQuery::text("one", false, false, false, Vec::new(), Vec::new()).unwrap();
+            search.search(query, cx);
+        });
+        cx.background_executor.run_until_parked();
+        let restored_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        restored_view
+            .update(cx, |restored_view, _| {
+                assert_eq!(restored_view.search_options, SearchOptions::NONE);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_options_remembered_per_project(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/one", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        fs.insert_tree("/two", json!({ "two.rs": "const TWO: usize = 2;" }))
+            .await;
+        let project_one = Project::test(fs.clone(), ["/one".as_ref()], cx).await;
+        let project_two = Project::test(fs.clone(), ["/two".as_ref()], cx).await;
+        let new_search_view = |project: &Model<Project>, cx: &mut TestAppContext| {
+            let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+            cx.add_window(|cx| ProjectSearchView::new(search, cx, None))
+        };
+
+        let search_view = new_search_view(&project_one, cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.search_options, SearchOptions::NONE);
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+            })
+            .unwrap();
+
+        // Without a query to take them from, new searches start with the project's last options.
+        new_search_view(&project_one, cx)
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.search_options, SearchOptions::REGEX);
+            })
+            .unwrap();
+        new_search_view(&project_two, cx)
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.search_options, SearchOptions::NONE);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_streaming_notifications_are_throttled(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.max_matches = Some(10_000);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        let files = (0..100)
+            .map(|ix| (format!("file_{ix}.rs"), json!("one\n".repeat(100))))
+            .collect::<serde_json::Map<_, _>>();
+        fs.insert_tree("/dir", serde_json::Value::Object(files))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let notification_count = Rc::new(RefCell::new(0));
+        let _subscription = cx.update({
+            let notification_count = notification_count.clone();
+            |cx| {
+                cx.observe(&search, move |_, _| {
+                    *notification_count.borrow_mut() += 1;
+                })
+            }
+        });
+
+        perform_search(search_view, "one", cx);
+        search.read_with(cx, |search, _| {
+            assert!(search.pending_search.is_none());
+            assert_eq!(search.match_ranges.len(), 10_000);
+        });
+        // The 100 files of results are streamed without advancing the clock, so only the first
+        // notification of the stream goes out before the search completes.
+        assert!(
+            *notification_count.borrow() < 10,
+            "{} notifications while streaming 100 files",
+            notification_count.borrow()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_max_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.max_matches = Some(2);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1; const TWO: usize = ONE + ONE;",
+                "two.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search.update(cx, |search, cx| {
+            assert!(search.pending_search.is_none());
+            assert!(search.truncated);
+            assert_eq!(search.total_match_count, 2);
+            assert_eq!(search.match_ranges.len(), 2);
+            assert_eq!(
+                search.truncated_label(cx).as_deref(),
+                Some("Showing first 2 matches (truncated)")
+            );
+        });
+
+        perform_search(search_view, "THREE", cx);
+        search.update(cx, |search, cx| {
+            assert!(!search.truncated);
+            assert_eq!(search.total_match_count, 1);
+            assert_eq!(search.truncated_label(cx), None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_open_buffers_and_current_file_scopes(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        for path in ["one.rs", "two.rs"] {
+            window
+                .update(cx, |workspace, cx| {
+                    workspace.open_path((worktree_id, path), None, true, cx)
+                })
+                .unwrap()
+                .await
+                .unwrap();
+        }
+
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        let search_in_next_scope = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        search_view.cycle_scope(cx);
+                        search_view
+                            .query_editor
+                            .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                        search_view.search(cx);
+                    })
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+            cx.read(|cx| {
+                let search_view = search_view.read(cx);
+                (
+                    search_view.scope.label(),
+                    search_view.model.read(cx).match_ranges.len(),
+                )
+            })
+        };
+        assert_eq!(search_in_next_scope(cx), ("Open buffers", 3));
+        assert_eq!(search_in_next_scope(cx), ("Current file", 2));
+        let (mut scope, mut match_count) = search_in_next_scope(cx);
+        while scope != "Project" {
+            (scope, match_count) = search_in_next_scope(cx);
+        }
+        assert_eq!(match_count, 4);
+    }
+
+    #[gpui::test]
+    async fn test_search_changed_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/.git"),
+            &[
+                (Path::new("two.rs"), GitFileStatus::Modified),
+                (Path::new("three.rs"), GitFileStatus::Added),
+                (Path::new("deleted.rs"), GitFileStatus::Modified),
+            ],
+        );
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        cx.background_executor.run_until_parked();
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .available_scopes(cx)
+                        .iter()
+                        .map(|scope| scope.label())
+                        .collect::<Vec<_>>(),
+                    ["Project", "Changed files"]
+                );
+                search_view.cycle_scope(cx);
+                assert_eq!(search_view.scope.label(), "Changed files");
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_within_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_within_results(cx);
+                assert_eq!(search_view.scope.label(), "Previous results");
+                assert!(search_view.query_editor.focus_handle(cx).is_focused(cx));
+            })
+            .unwrap();
+        perform_search(search_view, "usize", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_search_in_selection(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let a = ONE;\nlet b = ONE;\nlet c = ONE + ONE;\nlet d = ONE;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let editor = window
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |s| {
+                        s.select_ranges([language::Point::new(1, 4)..language::Point::new(2, 12)])
+                    });
+                })
+            })
+            .unwrap();
+
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        let toggle_search_in_selection = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        search_view
+                            .query_editor
+                            .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                        search_view.toggle_search_in_selection(cx);
+                    })
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+            cx.read(|cx| {
+                let search_view = search_view.read(cx);
+                (
+                    search_view.scope.label(),
+                    search_view.model.read(cx).match_ranges.len(),
+                )
+            })
+        };
+        assert_eq!(toggle_search_in_selection(cx), ("Selection", 2));
+        assert_eq!(toggle_search_in_selection(cx), ("Project", 6));
+    }
+
+    #[gpui::test]
+    async fn test_visible_area_scope(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let a = ONE;\nlet b = ONE;\nlet c = ONE + ONE;\nlet d = ONE;",
+                "two.rs": "const TWO: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                let visible_area = BufferRange {
+                    buffer: buffer.clone(),
+                    range: snapshot.anchor_before(language::Point::new(1, 0))
+                        ..snapshot.anchor_after(language::Point::new(2, 17)),
+                };
+                search_view.visible_area = Some(visible_area.clone());
+                search_view.scope = SearchScope::VisibleArea(visible_area);
+                assert_eq!(search_view.scope.buffers(), Some(vec![buffer.clone()]));
+            })
+            .unwrap();
+
+        let scope_and_match_count = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    (
+                        search_view.scope.label(),
+                        search_view.model.read(cx).match_ranges.len(),
+                    )
+                })
+                .unwrap()
+        };
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(scope_and_match_count(cx), ("Visible area", 3));
+
+        // Changing the scope searches again, without searching explicitly.
+        search_view
+            .update(cx, |search_view, cx| search_view.cycle_scope(cx))
+            .unwrap();
+        cx.executor().advance_clock(OPTION_TOGGLE_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        assert_eq!(scope_and_match_count(cx), ("Project", 6));
+    }
+
+    #[gpui::test]
+    async fn test_change_context_lines(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "// a\n// b\nconst ONE: usize = 1;\n// c\n// d",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        let search_id = search.read_with(cx, |search, _| search.search_id);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n// a\n// b\nconst ONE: usize = 1;\n// c\n// d"
+                );
+                search_view.change_context_lines(-1, -1, cx);
+                search_view.change_context_lines(-1, -1, cx);
+                search_view.change_context_lines(-1, -1, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    (search_view.context_before, search_view.context_after),
+                    (0, 0)
+                );
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = 1;"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                assert_eq!(search_view.model.read(cx).search_id, search_id);
+
+                search_view.change_context_lines(1, 0, cx);
+                search_view.change_context_lines(0, 2, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    (search_view.context_before, search_view.context_after),
+                    (1, 2)
+                );
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n// b\nconst ONE: usize = 1;\n// c\n// d"
+                );
+                assert_eq!(search_view.model.read(cx).search_id, search_id);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_results_filter(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let one = ONE;\n\n\n\n\n\nassert_eq!(ONE, 1);",
+                "two.rs": "let two = ONE + ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        let set_filter = |filter: &str, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .filter_editor
+                        .update(cx, |editor, cx| editor.set_text(filter, cx));
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+        };
+
+        set_filter("let", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert_eq!(model.match_ranges.len(), 3);
+                assert_eq!(model.total_match_count, 3);
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(results_text.contains("let two = ONE + ONE;"));
+                assert!(!results_text.contains("assert_eq!"));
+            })
+            .unwrap();
+
+        set_filter("assert", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(results_text.contains("assert_eq!(ONE, 1);"));
+                assert!(!results_text.contains("let"));
+            })
+            .unwrap();
+
+        set_filter("", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert_eq!(model.match_ranges.len(), 4);
+                assert_eq!(model.total_match_count, 4);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_pinned_results_search_in_new_tab(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let pinned_view = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_in_workspace(workspace, Some(query), cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |_, cx| {
+                pinned_view.update(cx, |search_view, cx| {
+                    search_view.toggle_pin_results(cx);
+                    search_view.set_query("TWO", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let new_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .expect("no project search view")
+        });
+        assert_ne!(new_view, pinned_view);
+        cx.read(|cx| {
+            let pinned_view = pinned_view.read(cx);
+            assert_eq!(pinned_view.query_editor.read(cx).text(cx), "ONE");
+            assert_eq!(pinned_view.model.read(cx).match_ranges.len(), 3);
+            let new_view = new_view.read(cx);
+            assert!(!new_view.results_pinned);
+            assert_eq!(new_view.query_editor.read(cx).text(cx), "TWO");
+            assert_eq!(new_view.model.read(cx).match_ranges.len(), 1);
+        });
+
+        window
+            .update(cx, |_, cx| {
+                pinned_view.update(cx, |search_view, cx| {
+                    search_view.toggle_pin_results(cx);
+                    search_view.set_query("TWO", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(
+                workspace.read(cx).active_item_as::<ProjectSearchView>(cx),
+                Some(new_view.clone())
+            );
+            assert_eq!(pinned_view.read(cx).model.read(cx).match_ranges.len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_filename_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "src": {
+                    "search_bar.rs": "const ONE: usize = 1;",
+                    "buffer.rs": "const BAR: usize = 2;",
+                },
+                "README.md": "search bar",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), cx);
+                search_view
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.toggle_filename_search(cx);
+                    search_view.set_query("srchbar", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            let search_view = search_view.read(cx);
+            assert!(search_view.has_filename_matches(cx));
+            assert!(!search_view.has_matches());
+            let paths = search_view
+                .model
+                .read(cx)
+                .filename_matches
+                .iter()
+                .map(|(_, path_match)| path_match.string.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(paths, ["src/search_bar.rs"]);
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert!(search_view.open_selected_filename_match(cx));
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            let active_editor = workspace
+                .read(cx)
+                .active_item_as::<Editor>(cx)
+                .expect("no active editor");
+            assert_eq!(active_editor.read(cx).text(cx), "const ONE: usize = 1;");
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.toggle_filename_search(cx);
+                    assert_eq!(search_view.search_mode, SearchMode::Text);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            let search_view = search_view.read(cx);
+            assert!(!search_view.has_filename_matches(cx));
+            assert!(search_view.model.read(cx).filename_matches.is_empty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_treat_query_as_path(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "src": {
+                    "search_bar.rs": "const ONE: usize = 1;",
+                    "buffer.rs": "const BAR: usize = 2;",
+                },
+                "README.md": "src/*.rs",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let matched_paths = |cx: &mut TestAppContext| {
+            search.read_with(cx, |search, _| {
+                search
+                    .filename_matches
+                    .iter()
+                    .map(|(_, path_match)| path_match.string.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_treat_query_as_path(cx);
+                search_view.set_query("src/*.rs", cx);
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(matched_paths(cx), ["src/buffer.rs", "src/search_bar.rs"]);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.search_mode, SearchMode::Text);
+                assert!(search_view.has_filename_matches(cx));
+                assert!(!search_view.has_matches());
+            })
+            .unwrap();
+
+        // Pasted fragments match the end of the paths.
+        perform_search(search_view, "search_bar.rs", cx);
+        assert_eq!(matched_paths(cx), ["src/search_bar.rs"]);
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_treat_query_as_path(cx);
+                search_view.set_query("src/*.rs", cx);
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(!search_view.has_filename_matches(cx));
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nsrc/*.rs"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_results_list_view(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "    const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), cx);
+                search_view
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.set_query("TWO", cx);
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.toggle_results_list_view(cx);
+                    assert!(search_view.results_as_list);
+                    let rows = (0..3)
+                        .filter_map(|ix| search_view.results_list_row(ix, cx))
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        rows,
+                        [
+                            (
+                                "three.rs:1".to_string(),
+                                "const THREE: usize = one::ONE + two::TWO;".to_string()
+                            ),
+                            (
+                                "three.rs:1".to_string(),
+                                "const THREE: usize = one::ONE + two::TWO;".to_string()
+                            ),
+                            (
+                                "two.rs:1".to_string(),
+                                "const TWO: usize = one::ONE + one::ONE;".to_string()
+                            ),
+                        ]
+                    );
+
+                    // Navigating the matches moves the highlighted row.
+                    search_view.select_match(Direction::Next, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.active_match_index, Some(1));
+                    search_view.open_match(2, cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(search_view.read(cx).active_match_index, Some(2));
+        });
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            let active_editor = workspace
+                .read(cx)
+                .active_item_as::<Editor>(cx)
+                .expect("no active editor");
+            let active_editor = active_editor.read(cx);
+            assert_eq!(
+                active_editor.text(cx),
+                "const TWO: usize = one::ONE + one::ONE;"
+            );
+            assert_eq!(active_editor.selections.newest::<usize>(cx).range(), 6..9);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_group_results_by_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = TWO - 1;",
+                    "two.rs": "const TWO: usize = 2;",
+                },
+                "b": {
+                    "three.rs": "const THREE: usize = TWO + TWO - 1;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let directory = |path: &str, match_count, collapsed| ResultsListRow::Directory {
+            path: PathBuf::from(path),
+            match_count,
+            collapsed,
+        };
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_group_by_directory(cx);
+                assert!(search_view.results_as_list);
+                assert_eq!(
+                    search_view.results_list_rows(cx),
+                    [
+                        directory("a", 2, false),
+                        ResultsListRow::Match(0),
+                        ResultsListRow::Match(1),
+                        directory("b", 2, false),
+                        ResultsListRow::Match(2),
+                        ResultsListRow::Match(3),
+                    ]
+                );
+
+                search_view.toggle_directory_collapsed(PathBuf::from("a"), cx);
+                assert_eq!(
+                    search_view.results_list_rows(cx),
+                    [
+                        directory("a", 2, true),
+                        directory("b", 2, false),
+                        ResultsListRow::Match(2),
+                        ResultsListRow::Match(3),
+                    ]
+                );
+                assert_eq!(search_view.results_list_row_index(0, cx), None);
+                assert_eq!(search_view.results_list_row_index(3, cx), Some(3));
+
+                search_view.toggle_group_by_directory(cx);
+                assert_eq!(
+                    search_view.results_list_rows(cx),
+                    (0..4).map(ResultsListRow::Match).collect::<Vec<_>>()
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_results_minimap(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let entries = search_view.minimap_entries(cx);
+                assert_eq!(
+                    entries,
+                    [
+                        MinimapEntry {
+                            path: PathBuf::from("dir/one.rs"),
+                            match_count: 1,
+                            first_match_index: 0,
+                        },
+                        MinimapEntry {
+                            path: PathBuf::from("dir/two.rs"),
+                            match_count: 2,
+                            first_match_index: 1,
+                        },
+                    ]
+                );
+                assert_eq!(search_view.viewed_minimap_entry(&entries, cx), Some(0));
+                search_view.select_file_matches(entries[1].first_match_index, cx);
+            })
+            .unwrap();
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(1));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_excerpt_header_path(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "src": {
+                    "one.rs": "const ONE: usize = 1;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/src/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, cx| {
+            let file = buffer.file().unwrap().as_ref();
+            assert_eq!(
+                excerpt_header_path(PathDisplay::ProjectRelative, file, cx),
+                None
+            );
+            assert_eq!(
+                excerpt_header_path(PathDisplay::WorktreeRelative, file, cx),
+                Some(PathBuf::from("src/one.rs"))
+            );
+            assert_eq!(
+                excerpt_header_path(PathDisplay::Absolute, file, cx),
+                Some(PathBuf::from("/dir/src/one.rs"))
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_copy_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.copy_matches(false, cx);
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().to_owned()),
+            Some(
+                "one.rs:1: const ONE: usize = 1;\ntwo.rs:1: const TWO: usize = one::ONE + one::ONE;"
+                    .to_string()
+            )
+        );
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.active_match_index = Some(0);
+                search_view.copy_matches(true, cx);
+            })
+            .unwrap();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().to_owned()),
+            Some("one.rs:1: const ONE: usize = 1;".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_regex_flags(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "first line\nsecond line\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let match_count =
+            |cx: &mut TestAppContext| search.read_with(cx, |search, _| search.match_ranges.len());
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+            })
+            .unwrap();
+
+        perform_search(search_view, "first.*second", cx);
+        assert_eq!(match_count(cx), 0);
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::DOT_MATCHES_NEW_LINE, cx);
+            })
+            .unwrap();
+        perform_search(search_view, "first.*second", cx);
+        assert_eq!(match_count(cx), 1);
+        search_view
+            .update(cx, |search_view, cx| {
+                let active_query = search_view.model.read(cx).active_query.clone().unwrap();
+                assert_eq!(active_query.as_str(), "(?s)first.*second");
+                assert_eq!(typed_query(&active_query), "first.*second");
+                assert_eq!(
+                    SearchOptions::from_query(&active_query),
+                    SearchOptions::REGEX | SearchOptions::DOT_MATCHES_NEW_LINE
+                );
+            })
+            .unwrap();
+
+        // Once matches can span lines, `^` only matches at the start of the file.
+        perform_search(search_view, "^second", cx);
+        assert_eq!(match_count(cx), 0);
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::MULTI_LINE_ANCHORS, cx);
+            })
+            .unwrap();
+        perform_search(search_view, "^second", cx);
+        assert_eq!(match_count(cx), 1);
+    }
+
+    #[gpui::test]
+    async fn test_save_results_as_report(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.save_as(project, PathBuf::from("/matches.txt"), cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load(Path::new("/matches.txt")).await.unwrap(),
+            "one.rs:1:7: const ONE: usize = 1;\n\
+             two.rs:1:20: const TWO: usize = one::ONE + one::ONE;\n\
+             two.rs:1:25: const TWO: usize = one::ONE + one::ONE;\n\
+             two.rs:1:31: const TWO: usize = one::ONE + one::ONE;\n\
+             two.rs:1:36: const TWO: usize = one::ONE + one::ONE;\n"
+        );
+        assert_eq!(
+            fs.load(Path::new("/dir/one.rs")).await.unwrap(),
+            "const ONE: usize = 1;"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_stop_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                search_view.search(cx);
+                search_view
+                    .model
+                    .update(cx, |model, cx| model.stop_search(cx));
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search.read_with(cx, |search, _| {
+            assert!(search.pending_search.is_none());
+            assert!(search.match_stream.is_none());
+            assert!(search.match_ranges.is_empty());
+            assert_eq!(
+                search.stopped_label().as_deref(),
+                Some("Stopped (0 results)")
+            );
+        });
+
+        // Dropping the streaming task stops the project search from producing any more results,
+        // while keeping it streams them all.
+        let query = SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new()).unwrap();
+        let (mut dropped_matches, match_stream) =
+            search.update(cx, |search, cx| search.stream_matches(query.clone(), cx));
+        drop(match_stream);
+        let (kept_matches, match_stream) =
+            search.update(cx, |search, cx| search.stream_matches(query, cx));
+        cx.background_executor.run_until_parked();
+        assert!(dropped_matches.next().await.is_none());
+        drop(match_stream);
+        assert_eq!(kept_matches.count().await, 2);
+
+        perform_search(search_view, "TWO", cx);
+        search.read_with(cx, |search, _| {
+            assert!(!search.stopped);
+            assert_eq!(search.stopped_label(), None);
+            assert_eq!(search.match_ranges.len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_search_duration(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("ONE", cx);
+                search_view.search(cx);
+                assert_eq!(search_view.model.read(cx).duration_label(), None);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search.update(cx, |search, _| {
+            // The test clock only moves when advanced, so searches take no time at all.
+            assert_eq!(search.search_duration, Some(Duration::ZERO));
+            assert_eq!(
+                search.duration_label().as_deref(),
+                Some("5 results in 0.0s")
+            );
+
+            search.search_duration = Some(Duration::from_millis(812));
+            assert_eq!(
+                search.duration_label().as_deref(),
+                Some("5 results in 0.8s")
+            );
+        });
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("TWO", cx);
+                search_view.search(cx);
+                search_view
+                    .model
+                    .update(cx, |model, cx| model.stop_search(cx));
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search.read_with(cx, |search, _| {
+            assert_eq!(
+                search.duration_label(),
+                None,
+                "Stopped searches should not report how long they took"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_live_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        let type_query = |query: &str, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+        };
+        let active_query = |cx: &mut TestAppContext| {
+            search.read_with(cx, |search, _| {
+                search
+                    .active_query
+                    .as_ref()
+                    .map(|query| query.as_str().to_string())
+            })
+        };
+
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_live_search(cx))
+            .unwrap();
+        type_query("ONE", cx);
+        assert_eq!(
+            active_query(cx),
+            None,
+            "Live searches should wait for the query to stop changing"
+        );
+        cx.executor().advance_clock(LIVE_SEARCH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        assert_eq!(active_query(cx), Some("ONE".to_string()));
+        search.read_with(cx, |search, _| assert_eq!(search.match_ranges.len(), 7));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::REGEX, cx)
+            })
+            .unwrap();
+        type_query("(ONE", cx);
+        cx.executor().advance_clock(LIVE_SEARCH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            active_query(cx),
+            Some("ONE".to_string()),
+            "Regexes that do not compile should not be searched for"
+        );
+        type_query("(ONE)", cx);
+        cx.executor().advance_clock(LIVE_SEARCH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        assert_eq!(active_query(cx), Some("(ONE)".to_string()));
+
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_live_search(cx))
+            .unwrap();
+        type_query("TWO", cx);
+        cx.executor().advance_clock(LIVE_SEARCH_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        assert_eq!(active_query(cx), Some("(ONE)".to_string()));
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(active_query(cx), Some("TWO".to_string()));
+    }
+
+    #[gpui::test]
+    async fn test_regex_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        let project = Project::test(fs.clone(), [], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        let buffer = cx.new_model(|cx| Buffer::local("let one_two = 1;\nlet x = 2;", cx));
+
+        let set_query = |query: &str, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+                })
+                .unwrap();
+            cx.executor().advance_clock(REGEX_PREVIEW_DEBOUNCE);
+            cx.background_executor.run_until_parked();
+        };
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.current_buffer = Some(buffer);
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+            })
+            .unwrap();
+
+        set_query("(\\w+)_(\\w+)", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.query_error, None);
+                assert_eq!(
+                    search_view.regex_preview,
+                    Some(r#""one_two" ($1: "one", $2: "two")"#.into())
+                );
+            })
+            .unwrap();
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use editor::DisplayPoint;
-    use gpui::{Action, TestAppContext, WindowHandle};
-    use project::FakeFs;
-    use serde_json::json;
-    use settings::SettingsStore;
-    use std::sync::Arc;
-    use workspace::DeploySearch;
+        set_query("(\\w+", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert!(search_view.query_error.is_some());
+                assert!(search_view.panels_with_errors.contains(&InputPanel::Query));
+                assert_eq!(search_view.regex_preview, None);
+            })
+            .unwrap();
+
+        set_query("x", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.query_error, None);
+                assert_eq!(search_view.regex_preview, Some(r#""x""#.into()));
+            })
+            .unwrap();
+    }
 
     #[gpui::test]
-    async fn test_project_search(cx: &mut TestAppContext) {
+    async fn test_new_search_prefills_recent_path_filters(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
@@ -1713,122 +13894,114 @@ pub mod tests {
             "/dir",
             json!({
                 "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+                "one.ts": "const ONE = 1;",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let other_project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
         let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-        perform_search(search_view, "TWO", cx);
-        search_view.update(cx, |search_view, cx| {
-            assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx)),
-                "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
-            );
-            let match_background_color = cx.theme().colors().search_match_background;
-            assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx)),
-                &[
-                    (
-                        DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35),
-                        match_background_color
-                    ),
-                    (
-                        DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40),
-                        match_background_color
-                    ),
-                    (
-                        DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9),
-                        match_background_color
-                    )
-                ]
-            );
-            assert_eq!(search_view.active_match_index, Some(0));
-            assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                [DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35)]
-            );
-
-            search_view.select_match(Direction::Next, cx);
-        }).unwrap();
-
         search_view
             .update(cx, |search_view, cx| {
-                assert_eq!(search_view.active_match_index, Some(1));
-                assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                    [DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40)]
-                );
-                search_view.select_match(Direction::Next, cx);
+                search_view.included_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.rs", cx);
+                });
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("target/**", cx);
+                });
             })
             .unwrap();
+        perform_search(search_view, "ONE", cx);
 
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search, cx, None));
         search_view
             .update(cx, |search_view, cx| {
-                assert_eq!(search_view.active_match_index, Some(2));
+                assert!(search_view.filters_enabled);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "*.rs");
                 assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                    [DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9)]
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "target/**"
                 );
-                search_view.select_match(Direction::Next, cx);
             })
             .unwrap();
 
+        let search = cx.new_model(|cx| ProjectSearch::new(other_project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search, cx, None));
         search_view
             .update(cx, |search_view, cx| {
-                assert_eq!(search_view.active_match_index, Some(0));
-                assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                    [DisplayPoint::new(2, 32)..DisplayPoint::new(2, 35)]
-                );
-                search_view.select_match(Direction::Prev, cx);
+                assert!(!search_view.filters_enabled);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                assert_eq!(search_view.excluded_files_editor.read(cx).text(cx), "");
             })
             .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_exclude_current_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
+        perform_search(search_view, "ONE", cx);
         search_view
             .update(cx, |search_view, cx| {
-                assert_eq!(search_view.active_match_index, Some(2));
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("one.rs,", cx);
+                });
+                search_view.exclude_current_file(cx);
+                assert!(search_view.filters_enabled);
                 assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                    [DisplayPoint::new(5, 6)..DisplayPoint::new(5, 9)]
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "one.rs,"
                 );
-                search_view.select_match(Direction::Prev, cx);
             })
             .unwrap();
-
+        cx.background_executor.run_until_parked();
         search_view
             .update(cx, |search_view, cx| {
-                assert_eq!(search_view.active_match_index, Some(1));
                 assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.selections.display_ranges(cx)),
-                    [DisplayPoint::new(2, 37)..DisplayPoint::new(2, 40)]
+                    search_view.model.read(cx).matched_project_paths(cx).len(),
+                    2
+                );
+                search_view.exclude_current_file(cx);
+                assert_eq!(
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "one.rs, three.rs"
                 );
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                let paths = search_view
+                    .model
+                    .read(cx)
+                    .matched_project_paths(cx)
+                    .into_iter()
+                    .map(|project_path| project_path.path)
+                    .collect::<Vec<_>>();
+                assert_eq!(paths, [Arc::from(Path::new("two.rs"))]);
+            })
+            .unwrap();
     }
 
     #[gpui::test]
-    async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
+    async fn test_reveal_active_match_in_project_panel(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
@@ -1836,673 +14009,902 @@ pub mod tests {
             "/dir",
             json!({
                 "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+                "src": {
+                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                },
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window;
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        let active_item = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
+        let revealed_entries = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let revealed_entries = revealed_entries.clone();
+            cx.subscribe(&project, move |_, event, _| {
+                if let project::Event::RevealInProjectPanel(entry_id) = event {
+                    revealed_entries.borrow_mut().push(*entry_id);
+                }
+            })
         });
-        assert!(
-            active_item.is_none(),
-            "Expected no search panel to be active"
-        );
-
-        window
-            .update(cx, move |workspace, cx| {
-                assert_eq!(workspace.panes().len(), 1);
-                workspace.panes()[0].update(cx, move |pane, cx| {
-                    pane.toolbar()
-                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                });
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.reveal_active_match_in_project_panel(cx)
             })
             .unwrap();
-
-        let Some(search_view) = cx.read(|cx| {
-            workspace
-                .read(cx)
+        let entry_id = project.read_with(cx, |project, cx| {
+            let worktree_id = project.worktrees().next().unwrap().read(cx).id();
+            project
+                .entry_for_path(
+                    &ProjectPath {
+                        worktree_id,
+                        path: Path::new("src/two.rs").into(),
+                    },
+                    cx,
+                )
                 .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        }) else {
-            panic!("Search view expected to appear after new search event trigger")
-        };
+                .id
+        });
+        assert_eq!(*revealed_entries.borrow(), [entry_id]);
+    }
 
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Empty search view should be focused after the toggle focus event: no results panel to focus on",
+    #[gpui::test]
+    async fn test_search_include_ignored(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                ".gitignore": "ignored.rs\n",
+                "one.rs": "const ONE: usize = 1;",
+                "ignored.rs": "const IGNORED: usize = one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = 1;"
                 );
-           });
-        }).unwrap();
+                search_view.toggle_search_option(SearchOptions::INCLUDE_IGNORED, cx);
+            })
+            .unwrap();
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    let query_editor = &search_view.query_editor;
-                    assert!(
-                        query_editor.focus_handle(cx).is_focused(cx),
-                        "Search view should be focused after the new search view is activated",
-                    );
-                    let query_text = query_editor.read(cx).text(cx);
-                    assert!(
-                        query_text.is_empty(),
-                        "New search query should be empty but got '{query_text}'",
-                    );
-                    let results_text = search_view
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
                         .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx));
-                    assert!(
-                        results_text.is_empty(),
-                        "Empty search view should have no results but got '{results_text}'"
-                    );
-                });
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = 1;\n\n\nconst IGNORED: usize = one::ONE;"
+                );
             })
             .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_swap_include_exclude(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "one.md": "ONE",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.query_editor.update(cx, |query_editor, cx| {
-                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
-                    });
-                    search_view.search(cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.included_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.md, crates/**/*.{toml", cx);
+                });
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.rs", cx);
                 });
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                let results_text = search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx));
-                assert!(
-                    results_text.is_empty(),
-                    "Search view for mismatching query should have no results but got '{results_text}'"
-                );
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Search view should be focused after mismatching query had been used in search",
-                );
-            });
-        }).unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view
+                    .panels_with_errors
+                    .contains(&InputPanel::Include));
+                assert!(search_view.glob_errors.contains_key(&InputPanel::Include));
 
-        cx.spawn(|mut cx| async move {
-            window.update(&mut cx, |_, cx| {
-                cx.dispatch_action(ToggleFocus.boxed_clone())
-            })
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
+                search_view.swap_include_exclude(cx);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "*.rs");
+                assert_eq!(
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "*.md, crates/**/*.{toml"
                 );
-            });
-        }).unwrap();
-
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
-                    search_view.search(cx);
+                assert!(!search_view
+                    .panels_with_errors
+                    .contains(&InputPanel::Include));
+                assert!(search_view
+                    .panels_with_errors
+                    .contains(&InputPanel::Exclude));
+                assert!(!search_view.glob_errors.contains_key(&InputPanel::Include));
+                assert!(search_view.glob_errors.contains_key(&InputPanel::Exclude));
+
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.md", cx);
                 });
+                search_view.swap_include_exclude(cx);
             })
             .unwrap();
         cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.panels_with_errors.is_empty());
+                assert!(search_view.glob_errors.is_empty());
                 assert_eq!(
                     search_view
                         .results_editor
                         .update(cx, |editor, cx| editor.display_text(cx)),
-                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                    "Search view results should match the query"
-                );
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with mismatching query should be focused after search results are available",
-                );
-            });
-        }).unwrap();
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with matching query should still have its results editor focused after the toggle focus event",
+                    "\n\nONE"
                 );
-            });
-        }).unwrap();
+            })
+            .unwrap();
+    }
 
-        workspace
-            .update(cx, |workspace, cx| {
-                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+    #[gpui::test]
+    async fn test_glob_errors(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        let project = Project::test(fs.clone(), [], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.query_editor.update(cx, |editor, cx| {
+                    editor.set_text("ONE", cx);
+                });
+                search_view.included_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.rs, crates/**/*.{toml", cx);
+                });
+                assert!(search_view.build_search_query(cx).is_none());
+                let error = &search_view.glob_errors[&InputPanel::Include];
+                assert!(error.contains("crates/**/*.{toml"), "{error}");
+                assert!(!search_view.glob_errors.contains_key(&InputPanel::Exclude));
+
+                search_view.included_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.rs, crates/**/*.toml", cx);
+                });
+                assert!(search_view.build_search_query(cx).is_some());
+                assert!(search_view.glob_errors.is_empty());
             })
             .unwrap();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert_eq!(search_view.query_editor.read(cx).text(cx), "two", "Query should be updated to first search result after search view 2nd open in a row");
-                assert_eq!(
-                    search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx)),
-                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                    "Results should be unchanged after search view 2nd open in a row"
-                );
-                assert!(
-                    search_view.query_editor.focus_handle(cx).is_focused(cx),
-                    "Focus should be moved into query editor again after search view 2nd open in a row"
-                );
-            });
-        }).unwrap();
+    }
 
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
+    #[gpui::test]
+    async fn test_symbol_scope(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "// one\nfn one() -> &'static str {\n    \"one\"\n}\n",
+                "notes.txt": "one",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )));
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        for (symbol_scope, expected_lines) in [
+            (
+                SymbolScope::All,
+                vec![
+                    ("dir/notes.txt", 1),
+                    ("dir/one.rs", 1),
+                    ("dir/one.rs", 2),
+                    ("dir/one.rs", 3),
+                ],
+            ),
+            (SymbolScope::Comments, vec![("dir/one.rs", 1)]),
+            (SymbolScope::Strings, vec![("dir/one.rs", 3)]),
+            (SymbolScope::Identifiers, vec![("dir/one.rs", 2)]),
+        ] {
+            search_view
+                .update(cx, |search_view, _| search_view.symbol_scope = symbol_scope)
                 .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with matching query should switch focus to the results editor after the toggle focus event",
-                );
+            perform_search(search_view, "one", cx);
+            let lines = search.read_with(cx, |search, cx| {
+                search
+                    .structured_matches(cx)
+                    .into_iter()
+                    .map(|info| (info.path.to_string_lossy().to_string(), info.line))
+                    .collect::<Vec<_>>()
             });
-        }).unwrap();
+            let expected_lines = expected_lines
+                .into_iter()
+                .map(|(path, line)| (path.to_string(), line))
+                .collect::<Vec<_>>();
+            assert_eq!(lines, expected_lines, "searching in {symbol_scope:?}");
+        }
     }
 
     #[gpui::test]
-    async fn test_new_project_search_focus(cx: &mut TestAppContext) {
+    async fn test_symbol_kind_filter(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+                "consts.rs": "const CIRCLE_AREA: usize = 3;",
+                "notes.txt": "circle",
+                "shapes.rs": "mod shapes { struct Circle; }",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window;
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        let active_item = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        });
-        assert!(
-            active_item.is_none(),
-            "Expected no search panel to be active"
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::language()),
+        )));
+        let mut fake_servers = language_registry.register_fake_lsp_adapter(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
         );
-
-        window
-            .update(cx, move |workspace, cx| {
-                assert_eq!(workspace.panes().len(), 1);
-                workspace.panes()[0].update(cx, move |pane, cx| {
-                    pane.toolbar()
-                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                });
-
-                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/shapes.rs", cx)
             })
+            .await
             .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        #[allow(deprecated)]
+        fn symbol(name: &str, kind: lsp::SymbolKind, path: &str) -> lsp::SymbolInformation {
+            lsp::SymbolInformation {
+                name: name.to_string(),
+                kind,
+                tags: None,
+                deprecated: None,
+                location: lsp::Location::new(
+                    lsp::Url::from_file_path(path).unwrap(),
+                    lsp::Range::default(),
+                ),
+                container_name: None,
+            }
+        }
+        let symbol_requests = Arc::new(AtomicUsize::new(0));
+        fake_server.handle_request::<lsp::request::WorkspaceSymbolRequest, _, _>({
+            let symbol_requests = symbol_requests.clone();
+            move |_, _| {
+                symbol_requests.fetch_add(1, SeqCst);
+                future::ready(Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![
+                    symbol("shapes", lsp::SymbolKind::MODULE, "/dir/shapes.rs"),
+                    symbol("Circle", lsp::SymbolKind::STRUCT, "/dir/shapes.rs"),
+                    symbol("CIRCLE_AREA", lsp::SymbolKind::CONSTANT, "/dir/consts.rs"),
+                ]))))
+            }
+        });
 
-        let Some(search_view) = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        }) else {
-            panic!("Search view expected to appear after new search event trigger")
-        };
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.symbol_kind_filter = Some(lsp::SymbolKind::STRUCT);
+            })
+            .unwrap();
+        perform_search(search_view, "circle", cx);
+        // The workspace symbols are listed once for the whole search. `shapes.rs` defines a
+        // struct, while `consts.rs` only defines a constant. No language server lists the
+        // symbols of `notes.txt`, so it isn't filtered.
+        assert_eq!(symbol_requests.load(SeqCst), 1);
+        let paths = search.read_with(cx, |search, cx| {
+            search
+                .structured_matches(cx)
+                .into_iter()
+                .map(|info| info.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(paths, ["dir/notes.txt", "dir/shapes.rs"]);
+    }
 
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
+    #[gpui::test]
+    async fn test_language_filter(cx: &mut TestAppContext) {
+        init_test(cx);
 
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                    assert!(
-                        search_view.query_editor.focus_handle(cx).is_focused(cx),
-                        "Empty search view should be focused after the toggle focus event: no results panel to focus on",
-                    );
-                });
-        }).unwrap();
+        let fs = FakeFs::new(cx.background_executor.clone());
+        let project = Project::test(fs.clone(), [], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: "TypeScript".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["ts".to_string(), "tsx".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                None,
+            )));
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    let query_editor = &search_view.query_editor;
-                    assert!(
-                        query_editor.focus_handle(cx).is_focused(cx),
-                        "Search view should be focused after the new search view is activated",
-                    );
-                    let query_text = query_editor.read(cx).text(cx);
-                    assert!(
-                        query_text.is_empty(),
-                        "New search query should be empty but got '{query_text}'",
-                    );
-                    let results_text = search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx));
-                    assert!(
-                        results_text.is_empty(),
-                        "Empty search view should have no results but got '{results_text}'"
-                    );
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("ONE", cx);
+                search_view.set_language_filter(Some("TypeScript".into()), cx);
+                let query = search_view.build_search_query(cx).unwrap();
+                assert!(query.file_matches(Some(Path::new("dir/one.ts"))));
+                assert!(query.file_matches(Some(Path::new("dir/src/two.tsx"))));
+                assert!(!query.file_matches(Some(Path::new("dir/three.rs"))));
+
+                search_view.included_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.rs", cx);
                 });
+                let query = search_view.build_search_query(cx).unwrap();
+                assert!(query.file_matches(Some(Path::new("dir/one.ts"))));
+                assert!(query.file_matches(Some(Path::new("dir/three.rs"))));
+                assert!(!query.file_matches(Some(Path::new("dir/four.md"))));
+
+                search_view.set_language_filter(None, cx);
+                let query = search_view.build_search_query(cx).unwrap();
+                assert!(!query.file_matches(Some(Path::new("dir/one.ts"))));
+                assert!(query.file_matches(Some(Path::new("dir/three.rs"))));
             })
             .unwrap();
+    }
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.query_editor.update(cx, |query_editor, cx| {
-                        query_editor.set_text("sOMETHINGtHATsURELYdOESnOTeXIST", cx)
-                    });
-                    search_view.search(cx);
-                });
+    #[gpui::test]
+    async fn test_worktree_filter(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/a",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/b",
+            json!({
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/a".as_ref(), "/b".as_ref()], cx).await;
+        let worktree_b = project.read_with(cx, |project, cx| {
+            project.worktrees().nth(1).unwrap().read(cx).id()
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.worktree_filter_label(cx), "All worktrees");
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 7);
+                search_view.set_worktree_filter(Some(worktree_b), cx);
+                assert_eq!(search_view.worktree_filter_label(cx), "b");
             })
             .unwrap();
 
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    let results_text = search_view
-                        .results_editor
-                        .update(cx, |editor, cx| editor.display_text(cx));
-                    assert!(
-                results_text.is_empty(),
-                "Search view for mismatching query should have no results but got '{results_text}'"
-            );
-                    assert!(
-                search_view.query_editor.focus_handle(cx).is_focused(cx),
-                "Search view should be focused after mismatching query had been used in search",
-            );
-                });
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
             })
             .unwrap();
-        cx.spawn(|mut cx| async move {
-            window.update(&mut cx, |_, cx| {
-                cx.dispatch_action(ToggleFocus.boxed_clone())
-            })
-        })
-        .detach();
+
+        project.update(cx, |project, cx| project.remove_worktree(worktree_b, cx));
         cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                    assert!(
-                        search_view.query_editor.focus_handle(cx).is_focused(cx),
-                        "Search view with mismatching query should be focused after the toggle focus event: still no results panel to focus on",
-                    );
-                });
-        }).unwrap();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.worktree_filter, None);
+                assert_eq!(search_view.worktree_filter_label(cx), "All worktrees");
+            })
+            .unwrap();
+    }
 
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
-                    search_view.search(cx);
-                })
+    #[gpui::test]
+    async fn test_results_detect_missing_languages(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.txt": "ONE",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
             })
+            .await
             .unwrap();
+        buffer.read_with(cx, |buffer, _| assert!(buffer.language().is_none()));
+
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                None,
+            )));
+        });
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search.read_with(cx, |search, cx| {
+            let languages = search
+                .buffer_matches
+                .iter()
+                .map(|(buffer, _)| {
+                    let buffer = buffer.read(cx);
+                    (
+                        buffer.file().unwrap().path().to_string_lossy().to_string(),
+                        buffer.language().map(|language| language.name()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                languages,
+                [
+                    ("one.rs".to_string(), Some(Arc::from("Rust"))),
+                    ("two.txt".to_string(), None),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_results_follow_file_changes(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search.read_with(cx, |search, _| assert_eq!(search.match_ranges.len(), 5));
+
+        fs.save(
+            "/dir/two.rs".as_ref(),
+            &"const TWO: usize = 2;\nconst ONE_MORE: usize = one::ONE;".into(),
+            language::LineEnding::Unix,
+        )
+        .await
+        .unwrap();
         cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx|
-        search_view.update(cx, |search_view, cx| {
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 4);
                 assert_eq!(
                     search_view
                         .results_editor
                         .update(cx, |editor, cx| editor.display_text(cx)),
-                    "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                    "Search view results should match the query"
-                );
-                assert!(
-                    search_view.results_editor.focus_handle(cx).is_focused(cx),
-                    "Search view with mismatching query should be focused after search results are available",
+                    "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = 2;\nconst ONE_MORE: usize = one::ONE;"
                 );
-            })).unwrap();
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                    assert!(
-                        search_view.results_editor.focus_handle(cx).is_focused(cx),
-                        "Search view with matching query should still have its results editor focused after the toggle focus event",
-                    );
-                });
-        }).unwrap();
-
-        workspace
-            .update(cx, |workspace, cx| {
-                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
-        let Some(search_view_2) = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .unwrap()
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        }) else {
-            panic!("Search view expected to appear after new search event trigger")
-        };
-        assert!(
-            search_view_2 != search_view,
-            "New search view should be open after `workspace::NewSearch` event"
-        );
-
-        window.update(cx, |_, cx| {
-            search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO", "First search view should not have an updated query");
-                    assert_eq!(
-                        search_view
-                            .results_editor
-                            .update(cx, |editor, cx| editor.display_text(cx)),
-                        "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                        "Results of the first search view should not update too"
-                    );
-                    assert!(
-                        !search_view.query_editor.focus_handle(cx).is_focused(cx),
-                        "Focus should be moved away from the first search view"
-                    );
-                });
-        }).unwrap();
-
-        window.update(cx, |_, cx| {
-            search_view_2.update(cx, |search_view_2, cx| {
-                    assert_eq!(
-                        search_view_2.query_editor.read(cx).text(cx),
-                        "two",
-                        "New search view should get the query from the text cursor was at during the event spawn (first search view's first result)"
-                    );
-                    assert_eq!(
-                        search_view_2
-                            .results_editor
-                            .update(cx, |editor, cx| editor.display_text(cx)),
-                        "",
-                        "No search results should be in the 2nd view yet, as we did not spawn a search for it"
-                    );
-                    assert!(
-                        search_view_2.query_editor.focus_handle(cx).is_focused(cx),
-                        "Focus should be moved into query editor of the new window"
-                    );
-                });
-        }).unwrap();
 
-        window
-            .update(cx, |_, cx| {
-                search_view_2.update(cx, |search_view_2, cx| {
-                    search_view_2
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("FOUR", cx));
-                    search_view_2.search(cx);
-                });
-            })
+        for path in ["/dir/one.rs", "/dir/two.rs"] {
+            fs.save(
+                path.as_ref(),
+                &"const TWO: usize = 2;".into(),
+                language::LineEnding::Unix,
+            )
+            .await
             .unwrap();
-
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view_2.update(cx, |search_view_2, cx| {
-                    assert_eq!(
-                        search_view_2
-                            .results_editor
-                            .update(cx, |editor, cx| editor.display_text(cx)),
-                        "\n\nconst FOUR: usize = one::ONE + three::THREE;",
-                        "New search view with the updated query should have new search results"
-                    );
-                    assert!(
-                        search_view_2.results_editor.focus_handle(cx).is_focused(cx),
-                        "Search view with mismatching query should be focused after search results are available",
-                    );
-                });
-        }).unwrap();
-
-        cx.spawn(|mut cx| async move {
-            window
-                .update(&mut cx, |_, cx| {
-                    cx.dispatch_action(ToggleFocus.boxed_clone())
-                })
-                .unwrap();
-        })
-        .detach();
-        cx.background_executor.run_until_parked();
-        window.update(cx, |_, cx| {
-            search_view_2.update(cx, |search_view_2, cx| {
-                    assert!(
-                        search_view_2.results_editor.focus_handle(cx).is_focused(cx),
-                        "Search view with matching query should switch focus to the results editor after the toggle focus event",
-                    );
-                });}).unwrap();
+        }
+        cx.background_executor.run_until_parked();
+        search.read_with(cx, |search, cx| {
+            assert!(search.match_ranges.is_empty());
+            assert!(search.buffer_matches.is_empty());
+            assert!(search.excerpts.read(cx).excerpt_ids().is_empty());
+            assert_eq!(search.no_results, Some(true));
+        });
     }
 
     #[gpui::test]
-    async fn test_new_project_search_in_directory(cx: &mut TestAppContext) {
+    async fn test_open_all_matches(cx: &mut TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "a": {
-                    "one.rs": "const ONE: usize = 1;",
-                    "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                },
-                "b": {
-                    "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                    "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
-                },
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = two::TWO;",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let worktree_id = project.read_with(cx, |project, cx| {
-            project.worktrees().next().unwrap().read(cx).id()
-        });
         let window = cx.add_window(|cx| Workspace::test_new(project, cx));
         let workspace = window.root(cx).unwrap();
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        let active_item = cx.read(|cx| {
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
             workspace
                 .read(cx)
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .active_item_as::<ProjectSearchView>(cx)
+                .expect("no project search view")
         });
-        assert!(
-            active_item.is_none(),
-            "Expected no search panel to be active"
-        );
-
         window
-            .update(cx, move |workspace, cx| {
-                assert_eq!(workspace.panes().len(), 1);
-                workspace.panes()[0].update(cx, move |pane, cx| {
-                    pane.toolbar()
-                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                });
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                })
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
 
-        let a_dir_entry = cx.update(|cx| {
-            workspace
-                .read(cx)
-                .project()
-                .read(cx)
-                .entry_for_path(&(worktree_id, "a").into(), cx)
-                .expect("no entry for /a/ directory")
-        });
-        assert!(a_dir_entry.is_dir());
         window
             .update(cx, |workspace, cx| {
-                ProjectSearchView::new_search_in_directory(workspace, &a_dir_entry.path, cx)
+                ProjectSearchView::open_all_matches(workspace, &OpenAllMatches, cx)
             })
             .unwrap();
-
-        let Some(search_view) = cx.read(|cx| {
+        cx.background_executor.run_until_parked();
+        let mut opened_paths = cx.read(|cx| {
             workspace
                 .read(cx)
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-        }) else {
-            panic!("Search view expected to appear after new search in directory event trigger")
-        };
-        cx.background_executor.run_until_parked();
+                .items_of_type::<Editor>(cx)
+                .filter_map(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+                .filter_map(|buffer| Some(buffer.read(cx).file()?.path().to_path_buf()))
+                .collect::<Vec<_>>()
+        });
+        opened_paths.sort();
+        assert_eq!(
+            opened_paths,
+            [PathBuf::from("one.rs"), PathBuf::from("two.rs")]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_option_toggles_search_once(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1; const one: usize = ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert!(
-                        search_view.query_editor.focus_handle(cx).is_focused(cx),
-                        "On new search in directory, focus should be moved into query editor"
-                    );
-                    search_view.excluded_files_editor.update(cx, |editor, cx| {
-                        assert!(
-                            editor.display_text(cx).is_empty(),
-                            "New search in directory should not have any excluded files"
-                        );
-                    });
-                    search_view.included_files_editor.update(cx, |editor, cx| {
-                        assert_eq!(
-                            editor.display_text(cx),
-                            a_dir_entry.path.to_str().unwrap(),
-                            "New search in directory should have included dir entry path"
-                        );
-                    });
+            .update(cx, |workspace, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.toolbar()
+                        .update(cx, |toolbar, cx| toolbar.add_item(search_bar.clone(), cx))
                 });
+                ProjectSearchView::deploy_search(workspace, &workspace::DeploySearch::find(), cx)
             })
             .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .expect("no project search view")
+        });
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
                     search_view
                         .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("const", cx));
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
                     search_view.search(cx);
-                });
+                })
             })
             .unwrap();
         cx.background_executor.run_until_parked();
+        let search_id = cx.read(|cx| search_view.read(cx).model.read(cx).search_id);
+
         window
             .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(
-                search_view
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                    search_bar.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                    search_bar.toggle_search_option(SearchOptions::REGEX, cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            assert_eq!(search_view.read(cx).model.read(cx).search_id, search_id);
+        });
+
+        cx.executor().advance_clock(OPTION_TOGGLE_DEBOUNCE);
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| {
+            let model = search_view.read(cx).model.read(cx);
+            assert_eq!(model.search_id, search_id + 1);
+            assert_eq!(model.match_ranges.len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_multiline_query(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "fn one() {\n    two();\n}\nfn two() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.set_query("{", cx);
+                search_view.toggle_multiline(cx);
+                assert!(search_view.multiline_query);
+                assert_eq!(
+                    search_view.query_editor.read(cx).mode(),
+                    EditorMode::AutoHeight {
+                        max_lines: MAX_MULTILINE_QUERY_ROWS
+                    }
+                );
+                assert_eq!(search_view.query_editor.read(cx).text(cx), "{");
+            })
+            .unwrap();
+
+        perform_search(search_view, "{\n    two", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let match_background_color = cx.theme().colors().search_match_background;
+                assert!(search_view
                     .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx)),
-                "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                "New search in directory should have a filter that matches a certain directory"
+                    .update(cx, |editor, cx| editor.all_text_background_highlights(cx))
+                    .contains(&(
+                        DisplayPoint::new(2, 9)..DisplayPoint::new(3, 7),
+                        match_background_color
+                    )));
+
+                search_view.toggle_multiline(cx);
+                assert_eq!(
+                    search_view.query_editor.read(cx).mode(),
+                    EditorMode::SingleLine
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_new_in_workspace(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let query = SearchQuery::text(
+            "ONE",
+            false,
+            true,
+            false,
+            vec![PathMatcher::new("two.rs").unwrap()],
+            Vec::new(),
+        )
+        .unwrap();
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_in_workspace(workspace, Some(query), cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        cx.read(|cx| {
+            assert_eq!(
+                workspace.read(cx).active_item_as::<ProjectSearchView>(cx),
+                Some(search_view.clone())
             );
-                })
+            let search_view = search_view.read(cx);
+            assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+            assert!(search_view
+                .search_options
+                .contains(SearchOptions::CASE_SENSITIVE));
+            assert!(search_view.filters_enabled);
+            assert_eq!(
+                search_view.included_files_editor.read(cx).text(cx),
+                "two.rs"
+            );
+            assert_eq!(search_view.excluded_files_editor.read(cx).text(cx), "");
+            assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+            assert_eq!(
+                workspace
+                    .read(cx)
+                    .project()
+                    .read(cx)
+                    .search_history()
+                    .queries()
+                    .collect::<Vec<_>>(),
+                ["ONE"]
+            );
+        });
+
+        // Deploying a new search opens its tab the same way, with empty inputs.
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+            })
+            .unwrap();
+        cx.read(|cx| {
+            let new_search_view = workspace
+                .read(cx)
+                .active_item_as::<ProjectSearchView>(cx)
+                .unwrap();
+            assert_ne!(new_search_view, search_view);
+            let new_search_view = new_search_view.read(cx);
+            assert_eq!(new_search_view.query_editor.read(cx).text(cx), "");
+            assert!(new_search_view.model.read(cx).active_query.is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_deploy_search_with_multiple_panes(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        // Setup 2 panes, both with a file open and one with a project search.
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |this, cx| {
+            this.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let panes: Vec<_> = window
+            .update(cx, |this, _| this.panes().to_owned())
+            .unwrap();
+        assert_eq!(panes.len(), 1);
+        let first_pane = panes.get(0).cloned().unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
+        window
+            .update(cx, |workspace, cx| {
+                workspace.open_path(
+                    (worktree_id, "one.rs"),
+                    Some(first_pane.downgrade()),
+                    true,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+        let second_pane = window
+            .update(cx, |workspace, cx| {
+                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
+        assert!(window
+            .update(cx, |_, cx| second_pane
+                .focus_handle(cx)
+                .contains_focused(cx))
+            .unwrap());
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                let pane = first_pane.clone();
+                move |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                }
             })
             .unwrap();
-    }
-
-    #[gpui::test]
-    async fn test_search_query_history(cx: &mut TestAppContext) {
-        init_test(cx);
-
-        let fs = FakeFs::new(cx.background_executor.clone());
-        fs.insert_tree(
-            "/dir",
-            json!({
-                "one.rs": "const ONE: usize = 1;",
-                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
-                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
-                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
-            }),
-        )
-        .await;
-        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window.root(cx).unwrap();
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
 
+        // Add a project search item to the second pane
         window
             .update(cx, {
                 let search_bar = search_bar.clone();
+                let pane = second_pane.clone();
                 move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 1);
-                    workspace.panes()[0].update(cx, move |pane, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    pane.update(cx, move |pane, cx| {
                         pane.toolbar()
                             .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
                     });
@@ -2512,724 +14914,921 @@ pub mod tests {
             })
             .unwrap();
 
-        let search_view = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .active_pane()
-                .read(cx)
-                .active_item()
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-                .expect("Search view expected to appear after new search event trigger")
-        });
-
-        // Add 3 search items into the history + another unsubmitted one.
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
-                    search_view.search(cx);
-                });
-            })
-            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
+        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
 
-        cx.background_executor.run_until_parked();
+        // Focus the first pane
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
-                    search_view.search(cx);
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &second_pane);
+                second_pane.update(cx, |this, cx| {
+                    assert_eq!(this.active_item_index(), 1);
+                    this.activate_prev_item(false, cx);
+                    assert_eq!(this.active_item_index(), 0);
                 });
+                workspace.activate_pane_in_direction(workspace::SplitDirection::Left, cx);
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("THREE", cx));
-                    search_view.search(cx);
-                })
-            })
-            .unwrap();
-        cx.background_executor.run_until_parked();
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view.query_editor.update(cx, |query_editor, cx| {
-                        query_editor.set_text("JUST_TEXT_INPUT", cx)
-                    });
-                })
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &first_pane);
+                assert_eq!(first_pane.read(cx).items_len(), 1);
+                assert_eq!(second_pane.read(cx).items_len(), 2);
             })
             .unwrap();
-        cx.background_executor.run_until_parked();
 
-        // Ensure that the latest input with search settings is active.
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(
-                        search_view.query_editor.read(cx).text(cx),
-                        "JUST_TEXT_INPUT"
-                    );
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+        // Deploy a new search
+        cx.dispatch_action(window.into(), DeploySearch::find());
 
-        // Next history query after the latest should set the query to the empty string.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                })
-            })
-            .unwrap();
+        // Both panes should now have a project search in them
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.active_pane(), &first_pane);
+                first_pane.update(cx, |this, _| {
+                    assert_eq!(this.active_item_index(), 1);
+                    assert_eq!(this.items_len(), 2);
+                });
+                second_pane.update(cx, |this, cx| {
+                    assert!(!cx.focus_handle().contains_focused(cx));
+                    assert_eq!(this.items_len(), 2);
                 });
             })
             .unwrap();
+
+        // Focus the second pane's non-search item
         window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                })
+            .update(cx, |_workspace, cx| {
+                second_pane.update(cx, |pane, cx| pane.activate_next_item(true, cx));
             })
             .unwrap();
+
+        // Deploy a new search
+        cx.dispatch_action(window.into(), DeploySearch::find());
+
+        // The project search view should now be focused in the second pane
+        // And the number of items should be unchanged.
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+            .update(cx, |_workspace, cx| {
+                second_pane.update(cx, |pane, _cx| {
+                    assert!(pane
+                        .active_item()
+                        .unwrap()
+                        .downcast::<ProjectSearchView>()
+                        .is_some());
+
+                    assert_eq!(pane.items_len(), 2);
                 });
             })
             .unwrap();
+    }
 
-        // First previous query for empty current query should set the query to the latest submitted one.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+    #[gpui::test]
+    async fn test_scroll_search_results_to_top(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        // We need many lines in the search results to be able to scroll the window
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "1.txt": "\n\n\n\n\n A \n\n\n\n\n",
+                "2.txt": "\n\n\n\n\n A \n\n\n\n\n",
+                "3.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "4.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "5.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "6.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "7.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "8.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "9.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "a.rs": "\n\n\n\n\n A \n\n\n\n\n",
+                "b.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "c.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "d.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "e.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "f.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "g.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "h.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "i.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "j.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "k.rs": "\n\n\n\n\n B \n\n\n\n\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        // First search
+        perform_search(search_view, "A", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.results_editor.update(cx, |results_editor, cx| {
+                    // Results are correct and scrolled to the top
+                    assert_eq!(
+                        results_editor.display_text(cx).match_indices(" A ").count(),
+                        10
+                    );
+                    assert_eq!(results_editor.scroll_position(cx), Point::default());
+
+                    // Scroll results all the way down
+                    results_editor.scroll(Point::new(0., f32::MAX), Some(Axis::Vertical), cx);
                 });
             })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+            .expect("unable to update search view");
+
+        // Second search
+        perform_search(search_view, "B", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.results_editor.update(cx, |results_editor, cx| {
+                    // Results are correct...
+                    assert_eq!(
+                        results_editor.display_text(cx).match_indices(" B ").count(),
+                        10
+                    );
+                    // ...and scrolled back to the top
+                    assert_eq!(results_editor.scroll_position(cx), Point::default());
                 });
             })
-            .unwrap();
+            .expect("unable to update search view");
+    }
+
+    #[gpui::test]
+    async fn test_rapid_searches_resolve_only_the_last_query(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-        // Further previous items should go over the history in reverse order.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
+        search_view
+            .update(cx, |search_view, cx| {
+                for query in ["ONE", "usize", "TWO"] {
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
+                    search_view.search(cx);
+                }
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst TWO: usize = one::ONE + one::ONE;"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                assert!(search_view.model.read(cx).pending_search.is_none());
             })
             .unwrap();
+    }
 
-        // Previous items should never go behind the first history item.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
-            })
+    #[gpui::test]
+    async fn test_search_todos(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "// TODO: one\nconst TODOS: usize = 1;",
+                "two.rs": "// FIXME two\n// XXX: three",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| search_view.search_todos_in_view(cx))
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert!(search_view.search_options.contains(SearchOptions::REGEX));
+                assert_eq!(
+                    search_view.query_editor.read(cx).text(cx),
+                    r"\b(TODO|FIXME|HACK|XXX)\b"
+                );
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
             })
             .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_repeat_last_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+
         window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                });
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
             })
             .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
         window
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+                    search_view.set_query("ONE", cx);
+                    search_view.search(cx);
+                })
             })
             .unwrap();
+        cx.background_executor.run_until_parked();
+        cx.read(|cx| assert_eq!(search_view.read(cx).model.read(cx).match_ranges.len(), 3));
 
-        // Next items should go over the history in the original order.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
+        // Edit a file from an editor, whose selection must not replace the query.
+        let editor = window
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
             })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
             .unwrap();
         window
             .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+                editor.update(cx, |editor, cx| {
+                    editor.set_text("const ONE: usize = ONE;", cx);
+                    editor.select_all(&SelectAll, cx);
+                })
             })
             .unwrap();
 
         window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    search_view
-                        .query_editor
-                        .update(cx, |query_editor, cx| query_editor.set_text("TWO_NEW", cx));
-                    search_view.search(cx);
-                });
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::repeat_last_search(workspace, &RepeatLastProjectSearch, cx)
             })
             .unwrap();
         cx.background_executor.run_until_parked();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
-            })
-            .unwrap();
+        cx.read(|cx| {
+            let active_item = workspace.read(cx).active_item(cx).unwrap();
+            assert_eq!(active_item.item_id(), search_view.entity_id());
+            let search_view = search_view.read(cx);
+            assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+            assert_eq!(search_view.model.read(cx).match_ranges.len(), 4);
+        });
+    }
 
-        // New search input should add another entry to history and move the selection to the end of the history.
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+    #[gpui::test]
+    async fn test_restore_review_position(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.pending_review_position = Some(ReviewPosition {
+                    active_match_index: 2,
+                    scroll_position: Point::default(),
                 });
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(2));
+                assert_eq!(search_view.pending_review_position, None);
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+
+        // When fewer matches are found on restore, the last one is selected.
+        search_view
+            .update(cx, |search_view, _| {
+                search_view.pending_review_position = Some(ReviewPosition {
+                    active_match_index: 4,
+                    scroll_position: Point::default(),
                 });
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(2));
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
-            })
+    }
+
+    struct TestEmbeddingProvider;
+
+    impl EmbeddingProvider for TestEmbeddingProvider {
+        fn embed<'a>(
+            &'a self,
+            texts: &'a [TextToEmbed<'a>],
+        ) -> BoxFuture<'a, anyhow::Result<Vec<Embedding>>> {
+            let embeddings = texts
+                .iter()
+                .map(|text| {
+                    if text.text.contains("garbage") {
+                        Embedding::new(vec![0.9, 0.1])
+                    } else {
+                        Embedding::new(vec![0.1, 0.9])
+                    }
+                })
+                .collect();
+            future::ready(Ok(embeddings)).boxed()
+        }
+
+        fn batch_size(&self) -> usize {
+            16
+        }
+    }
+
+    #[gpui::test]
+    async fn test_semantic_search(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "haystack.txt": "hay and more hay",
+                "needle.txt": "garbage in, garbage out",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_index = cx
+            .update(|cx| SemanticIndex::new(temp_dir.path(), Arc::new(TestEmbeddingProvider), cx))
+            .await
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "THREE");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+        cx.update(|cx| cx.set_global(semantic_index));
+        let project_index = cx.update(|cx| {
+            cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+                semantic_index.project_index(project.clone(), cx)
             })
-            .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
+        });
+        let (status_tx, mut status_rx) = futures::channel::mpsc::unbounded();
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&project_index, move |_, status, _| {
+                status_tx.unbounded_send(*status).ok();
             })
+        });
+        let mut scanned = false;
+        while let Some(status) = status_rx.next().await {
+            match status {
+                Status::Scanning => scanned = true,
+                Status::Idle if scanned => break,
+                Status::Idle => {}
+            }
+        }
+
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_semantic_search(cx))
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO_NEW");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+        perform_search(search_view, "garbage in, garbage out", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+                assert_eq!(search_view.active_match_index, Some(0));
+
+                // The most similar result comes first, regardless of its path.
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                let needle_ix = results_text.find("garbage in, garbage out").unwrap();
+                let haystack_ix = results_text.find("hay and more hay").unwrap();
+                assert!(needle_ix < haystack_ix);
+                assert_eq!(search_view.semantic_score_blocks.len(), 2);
+
+                // The results are scoped by the globs, like the ones of a text search.
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("haystack.txt", cx);
                 });
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_bar.update(cx, |search_bar, cx| {
-                    search_bar.next_history_query(&NextHistoryQuery, cx);
-                });
+        perform_search(search_view, "garbage in, garbage out", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(results_text.contains("garbage in, garbage out"));
+                assert!(!results_text.contains("hay and more hay"));
             })
             .unwrap();
-        window
-            .update(cx, |_, cx| {
-                search_view.update(cx, |search_view, cx| {
-                    assert_eq!(search_view.query_editor.read(cx).text(cx), "");
-                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
-                });
+    }
+
+    #[gpui::test]
+    async fn test_semantic_search_without_index(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_error, None);
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                search_view.search_mode = SearchMode::Semantic;
+            })
+            .unwrap();
+
+        // The previous results stay while the query tells why nothing was searched.
+        perform_search(search_view, "a constant", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view.query_error.as_deref(),
+                    Some("Semantic search is unavailable without a semantic index")
+                );
+                assert!(search_view.panels_with_errors.contains(&InputPanel::Query));
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
             })
             .unwrap();
     }
 
     #[gpui::test]
-    async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
+    async fn test_rapid_semantic_searches_resolve_only_the_last_query(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
         init_test(cx);
 
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "one.rs": "const ONE: usize = 1;",
+                "haystack.txt": "hay and more hay",
+                "needle.txt": "garbage in, garbage out",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let worktree_id = project.update(cx, |this, cx| {
-            this.worktrees().next().unwrap().read(cx).id()
-        });
 
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let workspace = window.root(cx).unwrap();
-
-        let panes: Vec<_> = window
-            .update(cx, |this, _| this.panes().to_owned())
-            .unwrap();
-
-        let search_bar_1 = window.build_view(cx, |_| ProjectSearchBar::new());
-        let search_bar_2 = window.build_view(cx, |_| ProjectSearchBar::new());
-
-        assert_eq!(panes.len(), 1);
-        let first_pane = panes.get(0).cloned().unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
-        window
-            .update(cx, |workspace, cx| {
-                workspace.open_path(
-                    (worktree_id, "one.rs"),
-                    Some(first_pane.downgrade()),
-                    true,
-                    cx,
-                )
-            })
-            .unwrap()
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_index = cx
+            .update(|cx| SemanticIndex::new(temp_dir.path(), Arc::new(TestEmbeddingProvider), cx))
             .await
             .unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
-
-        // Add a project search item to the first pane
-        window
-            .update(cx, {
-                let search_bar = search_bar_1.clone();
-                let pane = first_pane.clone();
-                move |workspace, cx| {
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
-
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+        cx.update(|cx| cx.set_global(semantic_index));
+        let project_index = cx.update(|cx| {
+            cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+                semantic_index.project_index(project.clone(), cx)
+            })
+        });
+        let (status_tx, mut status_rx) = futures::channel::mpsc::unbounded();
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&project_index, move |_, status, _| {
+                status_tx.unbounded_send(*status).ok();
             })
-            .unwrap();
-        let search_view_1 = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .active_item(cx)
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-                .expect("Search view expected to appear after new search event trigger")
         });
+        let mut scanned = false;
+        while let Some(status) = status_rx.next().await {
+            match status {
+                Status::Scanning => scanned = true,
+                Status::Idle if scanned => break,
+                Status::Idle => {}
+            }
+        }
 
-        let second_pane = window
-            .update(cx, |workspace, cx| {
-                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.live_search = true;
+                search_view.toggle_semantic_search(cx);
             })
-            .unwrap()
             .unwrap();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
-
-        // Add a project search item to the second pane
-        window
-            .update(cx, {
-                let search_bar = search_bar_2.clone();
-                let pane = second_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
 
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+        // A semantic search that is running when the query changes is cancelled right away.
+        perform_search(search_view, "hay", cx);
+        let search_id = search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |editor, cx| editor.set_text("garbage", cx));
+                search_view.search(cx);
+                assert!(search.read(cx).pending_search.is_some());
+                search_view
+                    .query_editor
+                    .update(cx, |editor, cx| editor.set_text("garbage in", cx));
+                search_view.schedule_live_search(cx);
+                assert!(search.read(cx).pending_search.is_none());
+                search.read(cx).search_id
             })
             .unwrap();
 
-        let search_view_2 = cx.read(|cx| {
-            workspace
-                .read(cx)
-                .active_item(cx)
-                .and_then(|item| item.downcast::<ProjectSearchView>())
-                .expect("Search view expected to appear after new search event trigger")
-        });
-
-        cx.run_until_parked();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 2);
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
-
-        let update_search_view =
-            |search_view: &View<ProjectSearchView>, query: &str, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_view.update(cx, |search_view, cx| {
-                            search_view
-                                .query_editor
-                                .update(cx, |query_editor, cx| query_editor.set_text(query, cx));
-                            search_view.search(cx);
-                        });
-                    })
-                    .unwrap();
-            };
-
-        let active_query =
-            |search_view: &View<ProjectSearchView>, cx: &mut TestAppContext| -> String {
-                window
-                    .update(cx, |_, cx| {
-                        search_view.update(cx, |search_view, cx| {
-                            search_view.query_editor.read(cx).text(cx).to_string()
-                        })
-                    })
-                    .unwrap()
-            };
-
-        let select_prev_history_item =
-            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_bar.update(cx, |search_bar, cx| {
-                            search_bar.previous_history_query(&PreviousHistoryQuery, cx);
-                        })
-                    })
-                    .unwrap();
-            };
-
-        let select_next_history_item =
-            |search_bar: &View<ProjectSearchBar>, cx: &mut TestAppContext| {
-                window
-                    .update(cx, |_, cx| {
-                        search_bar.update(cx, |search_bar, cx| {
-                            search_bar.next_history_query(&NextHistoryQuery, cx);
-                        })
-                    })
-                    .unwrap();
-            };
-
-        update_search_view(&search_view_1, "ONE", cx);
-        cx.background_executor.run_until_parked();
-
-        update_search_view(&search_view_2, "TWO", cx);
+        for query in ["garbage in,", "garbage in, garbage out"] {
+            search_view
+                .update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |editor, cx| editor.set_text(query, cx));
+                    search_view.schedule_live_search(cx);
+                })
+                .unwrap();
+        }
+        // Semantic searches wait longer than text ones for the query to stop changing.
+        cx.executor().advance_clock(LIVE_SEARCH_DEBOUNCE);
         cx.background_executor.run_until_parked();
-
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        // Selecting previous history item should select the query from search view 1.
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        // Selecting the previous history item should not change the query as it is already the first item.
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        // Changing the query in search view 2 should not affect the history of search view 1.
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
-
-        // Deploying a new search in search view 2
-        update_search_view(&search_view_2, "THREE", cx);
+        search_view
+            .update(cx, |_, cx| assert_eq!(search.read(cx).search_id, search_id))
+            .unwrap();
+        cx.executor().advance_clock(SEMANTIC_SEARCH_DEBOUNCE);
         cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |_, cx| {
+                let search = search.read(cx);
+                assert_eq!(search.search_id, search_id + 1);
+                assert_eq!(
+                    search.last_search_query_text.as_deref(),
+                    Some("garbage in, garbage out")
+                );
+                assert!(search.pending_search.is_none());
+                assert_eq!(search.match_ranges.len(), 2);
+            })
+            .unwrap();
+    }
 
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "THREE");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        select_prev_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "ONE");
-
-        // Search view 1 should now see the query from search view 2.
-        assert_eq!(active_query(&search_view_1, cx), "ONE");
-
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "TWO");
-
-        // Here is the new query from search view 2
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "THREE");
-
-        select_next_history_item(&search_bar_2, cx);
-        assert_eq!(active_query(&search_view_2, cx), "");
+    #[gpui::test]
+    async fn test_semantic_search_threshold(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<SearchSettings>(cx, |settings| {
+                    settings.semantic_search_threshold = Some(0.5);
+                });
+            });
+        });
 
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "TWO");
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "haystack.txt": "hay and more hay",
+                "needle.txt": "garbage in, garbage out",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
 
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "THREE");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_index = cx
+            .update(|cx| SemanticIndex::new(temp_dir.path(), Arc::new(TestEmbeddingProvider), cx))
+            .await
+            .unwrap();
+        cx.update(|cx| cx.set_global(semantic_index));
+        let project_index = cx.update(|cx| {
+            cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+                semantic_index.project_index(project.clone(), cx)
+            })
+        });
+        let (status_tx, mut status_rx) = futures::channel::mpsc::unbounded();
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&project_index, move |_, status, _| {
+                status_tx.unbounded_send(*status).ok();
+            })
+        });
+        let mut scanned = false;
+        while let Some(status) = status_rx.next().await {
+            match status {
+                Status::Scanning => scanned = true,
+                Status::Idle if scanned => break,
+                Status::Idle => {}
+            }
+        }
 
-        select_next_history_item(&search_bar_1, cx);
-        assert_eq!(active_query(&search_view_1, cx), "");
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| search_view.toggle_semantic_search(cx))
+            .unwrap();
+        perform_search(search_view, "garbage in, garbage out", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                assert_eq!(model.match_ranges.len(), 1);
+                assert_eq!(model.semantic_scores.len(), 1);
+                assert!(model.semantic_scores[0].1 >= 0.5);
+                assert_eq!(search_view.semantic_score_blocks.len(), 1);
+                let results_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(results_text.contains("garbage in, garbage out"));
+                assert!(!results_text.contains("hay and more hay"));
+            })
+            .unwrap();
     }
 
     #[gpui::test]
-    async fn test_deploy_search_with_multiple_panes(cx: &mut TestAppContext) {
+    async fn test_semantic_search_waits_for_indexing(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
         init_test(cx);
 
-        // Setup 2 panes, both with a file open and one with a project search.
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "one.rs": "const ONE: usize = 1;",
+                "haystack.txt": "hay and more hay",
+                "needle.txt": "garbage in, garbage out",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-        let worktree_id = project.update(cx, |this, cx| {
-            this.worktrees().next().unwrap().read(cx).id()
-        });
-        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
-        let panes: Vec<_> = window
-            .update(cx, |this, _| this.panes().to_owned())
-            .unwrap();
-        assert_eq!(panes.len(), 1);
-        let first_pane = panes.get(0).cloned().unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 0);
-        window
-            .update(cx, |workspace, cx| {
-                workspace.open_path(
-                    (worktree_id, "one.rs"),
-                    Some(first_pane.downgrade()),
-                    true,
-                    cx,
-                )
-            })
-            .unwrap()
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_index = cx
+            .update(|cx| SemanticIndex::new(temp_dir.path(), Arc::new(TestEmbeddingProvider), cx))
             .await
             .unwrap();
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
-        let second_pane = window
-            .update(cx, |workspace, cx| {
-                workspace.split_and_clone(first_pane.clone(), workspace::SplitDirection::Right, cx)
+        cx.update(|cx| cx.set_global(semantic_index));
+        let project_index = cx.update(|cx| {
+            cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+                semantic_index.project_index(project.clone(), cx)
             })
-            .unwrap()
-            .unwrap();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 1);
-        assert!(window
-            .update(cx, |_, cx| second_pane
-                .focus_handle(cx)
-                .contains_focused(cx))
-            .unwrap());
-        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
-        window
-            .update(cx, {
-                let search_bar = search_bar.clone();
-                let pane = first_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
-                }
+        });
+        let (status_tx, mut status_rx) = futures::channel::mpsc::unbounded();
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&project_index, move |_, status, _| {
+                status_tx.unbounded_send(*status).ok();
             })
-            .unwrap();
-
-        // Add a project search item to the second pane
-        window
-            .update(cx, {
-                let search_bar = search_bar.clone();
-                let pane = second_pane.clone();
-                move |workspace, cx| {
-                    assert_eq!(workspace.panes().len(), 2);
-                    pane.update(cx, move |pane, cx| {
-                        pane.toolbar()
-                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
-                    });
+        });
+        while let Some(status) = status_rx.next().await {
+            if status == Status::Scanning {
+                break;
+            }
+        }
 
-                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
-                }
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_semantic_search(cx);
+                search_view.set_query("garbage in, garbage out", cx);
+                search_view.search(cx);
             })
             .unwrap();
+        search.update(cx, |search, _| {
+            assert_eq!(search.indexing, Some(IndexingState::Indexing));
+            assert!(search.pending_search.is_some());
+        });
 
+        while let Some(status) = status_rx.next().await {
+            if status == Status::Idle {
+                break;
+            }
+        }
         cx.run_until_parked();
-        assert_eq!(cx.update(|cx| second_pane.read(cx).items_len()), 2);
-        assert_eq!(cx.update(|cx| first_pane.read(cx).items_len()), 1);
+        search.update(cx, |search, _| {
+            assert_eq!(search.indexing, Some(IndexingState::Complete));
+            assert!(search.pending_search.is_none());
+            assert_eq!(search.match_ranges.len(), 2);
+        });
+    }
 
-        // Focus the first pane
-        window
-            .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &second_pane);
-                second_pane.update(cx, |this, cx| {
-                    assert_eq!(this.active_item_index(), 1);
-                    this.activate_prev_item(false, cx);
-                    assert_eq!(this.active_item_index(), 0);
-                });
-                workspace.activate_pane_in_direction(workspace::SplitDirection::Left, cx);
-            })
-            .unwrap();
-        window
-            .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &first_pane);
-                assert_eq!(first_pane.read(cx).items_len(), 1);
-                assert_eq!(second_pane.read(cx).items_len(), 2);
-            })
-            .unwrap();
+    #[gpui::test]
+    async fn test_restore_serialized_search(cx: &mut TestAppContext) {
+        init_test(cx);
 
-        // Deploy a new search
-        cx.dispatch_action(window.into(), DeploySearch::find());
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
 
-        // Both panes should now have a project search in them
-        window
-            .update(cx, |workspace, cx| {
-                assert_eq!(workspace.active_pane(), &first_pane);
-                first_pane.update(cx, |this, _| {
-                    assert_eq!(this.active_item_index(), 1);
-                    assert_eq!(this.items_len(), 2);
-                });
-                second_pane.update(cx, |this, cx| {
-                    assert!(!cx.focus_handle().contains_focused(cx));
-                    assert_eq!(this.items_len(), 2);
-                });
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.restore(
+                    "ONE".into(),
+                    SearchOptions::CASE_SENSITIVE,
+                    "*.rs".into(),
+                    String::new(),
+                    Some(ReviewPosition {
+                        active_match_index: 1,
+                        scroll_position: Point::default(),
+                    }),
+                    cx,
+                );
             })
             .unwrap();
-
-        // Focus the second pane's non-search item
-        window
-            .update(cx, |_workspace, cx| {
-                second_pane.update(cx, |pane, cx| pane.activate_next_item(true, cx));
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                assert!(search_view.filters_enabled);
+                assert_eq!(search_view.included_files_editor.read(cx).text(cx), "*.rs");
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 3);
+                assert_eq!(search_view.active_match_index, Some(1));
             })
             .unwrap();
 
-        // Deploy a new search
-        cx.dispatch_action(window.into(), DeploySearch::find());
-
-        // The project search view should now be focused in the second pane
-        // And the number of items should be unchanged.
-        window
-            .update(cx, |_workspace, cx| {
-                second_pane.update(cx, |pane, _cx| {
-                    assert!(pane
-                        .active_item()
-                        .unwrap()
-                        .downcast::<ProjectSearchView>()
-                        .is_some());
-
-                    assert_eq!(pane.items_len(), 2);
-                });
+        // A regex that no longer compiles is restored, but not searched for.
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.restore(
+                    "ONE(".into(),
+                    SearchOptions::REGEX,
+                    String::new(),
+                    String::new(),
+                    None,
+                    cx,
+                );
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE(");
+                assert!(search_view.panels_with_errors.contains(&InputPanel::Query));
+                assert!(search_view.model.read(cx).active_query.is_none());
             })
             .unwrap();
     }
 
     #[gpui::test]
-    async fn test_scroll_search_results_to_top(cx: &mut TestAppContext) {
+    async fn test_select_source_location_match(cx: &mut TestAppContext) {
         init_test(cx);
 
-        // We need many lines in the search results to be able to scroll the window
         let fs = FakeFs::new(cx.background_executor.clone());
         fs.insert_tree(
             "/dir",
             json!({
-                "1.txt": "\n\n\n\n\n A \n\n\n\n\n",
-                "2.txt": "\n\n\n\n\n A \n\n\n\n\n",
-                "3.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "4.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "5.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "6.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "7.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "8.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "9.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "a.rs": "\n\n\n\n\n A \n\n\n\n\n",
-                "b.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "c.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "d.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "e.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "f.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "g.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "h.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "i.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "j.rs": "\n\n\n\n\n B \n\n\n\n\n",
-                "k.rs": "\n\n\n\n\n B \n\n\n\n\n",
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
             }),
         )
         .await;
         let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/two.rs", cx)
+            })
+            .await
+            .unwrap();
         let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
         let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
 
-        // First search
-        perform_search(search_view, "A", cx);
+        // The cursor was in the second `ONE` of `two.rs`, the last match of the project.
         search_view
             .update(cx, |search_view, cx| {
-                search_view.results_editor.update(cx, |results_editor, cx| {
-                    // Results are correct and scrolled to the top
-                    assert_eq!(
-                        results_editor.display_text(cx).match_indices(" A ").count(),
-                        10
-                    );
-                    assert_eq!(results_editor.scroll_position(cx), Point::default());
-
-                    // Scroll results all the way down
-                    results_editor.scroll(Point::new(0., f32::MAX), Some(Axis::Vertical), cx);
+                let position = buffer.read(cx).anchor_before(36);
+                search_view.pending_source_location = Some(SourceLocation {
+                    buffer: buffer.clone(),
+                    position,
+                    search_id: search_view.model.read(cx).search_id,
                 });
             })
-            .expect("unable to update search view");
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(3));
+                assert!(search_view.pending_source_location.is_none());
+            })
+            .unwrap();
 
-        // Second search
-        perform_search(search_view, "B", cx);
+        // Without a match at the source location, the first match stays selected.
         search_view
             .update(cx, |search_view, cx| {
-                search_view.results_editor.update(cx, |results_editor, cx| {
-                    // Results are correct...
-                    assert_eq!(
-                        results_editor.display_text(cx).match_indices(" B ").count(),
-                        10
-                    );
-                    // ...and scrolled back to the top
-                    assert_eq!(results_editor.scroll_position(cx), Point::default());
+                let position = buffer.read(cx).anchor_before(0);
+                search_view.pending_source_location = Some(SourceLocation {
+                    buffer: buffer.clone(),
+                    position,
+                    search_id: search_view.model.read(cx).search_id,
                 });
             })
-            .expect("unable to update search view");
+            .unwrap();
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, _| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                assert!(search_view.pending_source_location.is_none());
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_with_default_excludes() {
+        let matchers = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| PathMatcher::negatable(glob).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let default_excludes =
+            ["**/target/**", "**/node_modules/**", "*.min.js", "["].map(String::from);
+
+        assert_eq!(
+            with_default_excludes(matchers(&["*.snap"]), &[], &default_excludes),
+            matchers(&["*.snap", "**/target/**", "**/node_modules/**", "*.min.js"]),
+        );
+        assert_eq!(
+            with_default_excludes(
+                matchers(&["*.min.js"]),
+                &matchers(&["target/debug/build/**", "src/**"]),
+                &default_excludes,
+            ),
+            matchers(&["*.min.js", "**/node_modules/**"]),
+        );
+        assert_eq!(
+            with_default_excludes(
+                matchers(&["*.snap", "!**/target/doc/**"]),
+                &matchers(&["!src/node_modules/**"]),
+                &default_excludes,
+            ),
+            matchers(&[
+                "*.snap",
+                "**/target/**",
+                "**/node_modules/**",
+                "*.min.js",
+                "!**/target/doc/**",
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_with_case_of() {
+        assert_eq!(with_case_of("fooBar", "bazQux"), "bazQux");
+        assert_eq!(with_case_of("FooBar", "bazQux"), "BazQux");
+        assert_eq!(with_case_of("FOOBAR", "bazQux"), "BAZQUX");
+        assert_eq!(with_case_of("foobar", "bazQux"), "bazqux");
+        assert_eq!(with_case_of("FOO_BAR", "bazQux"), "BAZ_QUX");
+        assert_eq!(with_case_of("foo_bar", "BazQux"), "baz_qux");
+        assert_eq!(with_case_of("fooBar", "BAZ_QUX"), "bazQux");
+        assert_eq!(with_case_of("Foo_Bar", "bazQux"), "bazQux");
+        assert_eq!(with_case_of("foo bar", "bazQux"), "bazQux");
+        assert_eq!(with_case_of("fooBar", "baz qux"), "baz qux");
+    }
+
+    #[test]
+    fn test_excerpt_line_window() {
+        assert_eq!(excerpt_line_window(10, 2..4, 20), None);
+        assert_eq!(excerpt_line_window(100, 50..54, 20), Some(42..62));
+        assert_eq!(excerpt_line_window(100, 2..4, 20), Some(0..20));
+        assert_eq!(excerpt_line_window(100, 95..98, 20), Some(80..100));
+        assert_eq!(excerpt_line_window(100, 10..40, 20), Some(10..40));
     }
 
     fn init_test(cx: &mut TestAppContext) {
@@ -3,15 +3,22 @@ pub use buffer_search::BufferSearchBar;
 use gpui::{actions, Action, AppContext, IntoElement};
 use project::search::SearchQuery;
 pub use project_search::ProjectSearchView;
+pub use search_settings::{PathDisplay, SearchSettings};
+use settings::Settings;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton};
 
 pub mod buffer_search;
+mod dependency_sources;
+mod go_to_match;
+mod persistence;
 pub mod project_search;
 pub(crate) mod search_bar;
+mod search_settings;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
+    SearchSettings::register(cx);
     buffer_search::init(cx);
     project_search::init(cx);
 }
@@ -24,6 +31,8 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleDotMatchesNewLine,
+        ToggleMultiLineAnchors,
         ToggleReplace,
         SelectNextMatch,
         SelectPrevMatch,
@@ -43,6 +52,11 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        /// Lets `.` match newlines in regex queries, like the `s` inline flag.
+        const DOT_MATCHES_NEW_LINE = 0b10000;
+        /// Makes `^` and `$` match at the start and end of every line in regex queries,
+        /// like the `m` inline flag.
+        const MULTI_LINE_ANCHORS = 0b100000;
     }
 }
 
@@ -53,6 +67,8 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "match case",
             SearchOptions::INCLUDE_IGNORED => "include Ignored",
             SearchOptions::REGEX => "regular expression",
+            SearchOptions::DOT_MATCHES_NEW_LINE => "dot matches newline",
+            SearchOptions::MULTI_LINE_ANCHORS => "line anchors",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -73,10 +89,51 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::DOT_MATCHES_NEW_LINE => Box::new(ToggleDotMatchesNewLine),
+            SearchOptions::MULTI_LINE_ANCHORS => Box::new(ToggleMultiLineAnchors),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
 
+    /// The regex inline flag this option stands for.
+    pub fn inline_flag(&self) -> char {
+        match *self {
+            SearchOptions::DOT_MATCHES_NEW_LINE => 's',
+            SearchOptions::MULTI_LINE_ANCHORS => 'm',
+            _ => panic!("{:?} is not a regex flag SearchOption", self),
+        }
+    }
+
+    /// The inline flags of the enabled regex flag options, e.g. `sm`.
+    pub fn regex_flags(&self) -> String {
+        [
+            SearchOptions::DOT_MATCHES_NEW_LINE,
+            SearchOptions::MULTI_LINE_ANCHORS,
+        ]
+        .into_iter()
+        .filter(|option| self.contains(*option))
+        .map(|option| option.inline_flag())
+        .collect()
+    }
+
+    /// The regex flag options enabled by the inline `flags`, ignoring the unknown ones.
+    pub fn from_regex_flags(flags: &str) -> SearchOptions {
+        let mut options = SearchOptions::NONE;
+        options.set(SearchOptions::DOT_MATCHES_NEW_LINE, flags.contains('s'));
+        options.set(SearchOptions::MULTI_LINE_ANCHORS, flags.contains('m'));
+        options
+    }
+
+    /// Prefixes the regex `pattern` with the inline flags of the enabled regex flag options.
+    pub fn regex_pattern(&self, pattern: &str) -> String {
+        let flags = self.regex_flags();
+        if flags.is_empty() {
+            pattern.to_string()
+        } else {
+            format!("(?{flags}){pattern}")
+        }
+    }
+
     pub fn none() -> SearchOptions {
         SearchOptions::NONE
     }
@@ -87,6 +144,9 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        if query.is_regex() {
+            options |= split_regex_flags(query.as_str()).0;
+        }
         options
     }
 
@@ -105,4 +165,44 @@ impl SearchOptions {
                 move |cx| Tooltip::for_action(label.clone(), &*action, cx)
             })
     }
+
+    /// Renders a regex flag option as a button labeled with its inline flag.
+    pub fn as_flag_button(
+        &self,
+        active: bool,
+        action: impl Fn(&gpui::ClickEvent, &mut WindowContext) + 'static,
+    ) -> impl IntoElement {
+        Button::new(self.label(), self.inline_flag().to_string())
+            .on_click(action)
+            .style(ButtonStyle::Subtle)
+            .selected(active)
+            .tooltip({
+                let action = self.to_toggle_action();
+                let label: SharedString = format!("Toggle {}", self.label()).into();
+                move |cx| Tooltip::for_action(label.clone(), &*action, cx)
+            })
+    }
+}
+
+/// Splits the inline flags that [`SearchOptions::regex_pattern`] adds off the regex `pattern`,
+/// returning the options they stand for and the pattern as it was typed.
+pub(crate) fn split_regex_flags(pattern: &str) -> (SearchOptions, &str) {
+    if let Some((flags, typed_pattern)) = pattern
+        .strip_prefix("(?")
+        .and_then(|pattern| pattern.split_once(')'))
+    {
+        if !flags.is_empty() && flags.chars().all(|flag| flag == 's' || flag == 'm') {
+            return (SearchOptions::from_regex_flags(flags), typed_pattern);
+        }
+    }
+    (SearchOptions::NONE, pattern)
+}
+
+/// The text of `query` as it was typed in the query editor.
+pub(crate) fn typed_query(query: &SearchQuery) -> &str {
+    if query.is_regex() {
+        split_regex_flags(query.as_str()).1
+    } else {
+        query.as_str()
+    }
 }
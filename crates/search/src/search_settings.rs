@@ -0,0 +1,140 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::SearchOptions;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchSettings {
+    pub max_excerpt_line_length: Option<u32>,
+    pub todo_tokens: Vec<String>,
+    pub default_excludes: Vec<String>,
+    pub context_lines: u32,
+    pub context_lines_before: Option<u32>,
+    pub context_lines_after: Option<u32>,
+    pub max_matches: usize,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub semantic_search_threshold: f32,
+    pub wrap_around: bool,
+    pub keep_previous_results_on_empty: bool,
+    pub path_display: PathDisplay,
+}
+
+/// How the paths of the files in the project search results are shown in their headers.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDisplay {
+    /// Relative to the project, starting with the name of the worktree when the project has
+    /// several of them.
+    #[default]
+    ProjectRelative,
+    /// Relative to the worktree of the file, without its name.
+    WorktreeRelative,
+    /// The absolute path of the file.
+    Absolute,
+}
+
+/// Project search configuration.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SearchSettingsContent {
+    /// The maximum number of characters of a matched line to display in the
+    /// project search results. Longer lines are truncated with an ellipsis
+    /// around the match; `null` disables truncation.
+    ///
+    /// Default: null
+    pub max_excerpt_line_length: Option<u32>,
+    /// The comment markers that the TODOs search looks for.
+    ///
+    /// Default: ["TODO", "FIXME", "HACK", "XXX"]
+    pub todo_tokens: Option<Vec<String>>,
+    /// Globs of the files that project search skips unless the default excludes are
+    /// toggled off. A default is ignored when it matches an explicitly included glob.
+    ///
+    /// Default: ["**/target/**", "**/node_modules/**", "**/*.min.js"]
+    pub default_excludes: Option<Vec<String>>,
+    /// The number of lines shown around every match in the project search results,
+    /// up to 10.
+    ///
+    /// Default: 2
+    pub context_lines: Option<u32>,
+    /// The number of lines shown before every match in the project search results,
+    /// overriding `context_lines`.
+    ///
+    /// Default: null
+    pub context_lines_before: Option<u32>,
+    /// The number of lines shown after every match in the project search results,
+    /// overriding `context_lines`.
+    ///
+    /// Default: null
+    pub context_lines_after: Option<u32>,
+    /// The number of matches after which project search stops looking for more,
+    /// showing the results found so far as truncated.
+    ///
+    /// Default: 5000
+    pub max_matches: Option<usize>,
+    /// Whether new project searches match the query as a regular expression.
+    ///
+    /// Default: false
+    pub regex: Option<bool>,
+    /// Whether new project searches match the case of the query.
+    ///
+    /// Default: false
+    pub case_sensitive: Option<bool>,
+    /// Whether new project searches only match whole words.
+    ///
+    /// Default: false
+    pub whole_word: Option<bool>,
+    /// The similarity to the query, between -1 and 1, below which semantic search
+    /// results are omitted. Raising it trades recall for precision.
+    ///
+    /// Default: 0.0
+    pub semantic_search_threshold: Option<f32>,
+    /// Whether going to the next match from the last one of the project search
+    /// results selects the first one, and vice versa.
+    ///
+    /// Default: true
+    pub wrap_around: Option<bool>,
+    /// Whether the results of the previous project search stay visible, greyed out, while
+    /// a new search has not found any match, instead of showing the "No results" placeholder.
+    ///
+    /// Default: false
+    pub keep_previous_results_on_empty: Option<bool>,
+    /// How the paths of the files are shown in the headers of the project search results:
+    /// relative to the project, relative to their worktree, or absolute.
+    ///
+    /// Default: project_relative
+    pub path_display: Option<PathDisplay>,
+}
+
+impl SearchSettings {
+    /// The options that new project searches start with.
+    pub fn default_search_options(&self) -> SearchOptions {
+        let mut options = SearchOptions::NONE;
+        options.set(SearchOptions::REGEX, self.regex);
+        options.set(SearchOptions::CASE_SENSITIVE, self.case_sensitive);
+        options.set(SearchOptions::WHOLE_WORD, self.whole_word);
+        options
+    }
+
+    /// The numbers of lines shown before and after every match in the project search results.
+    pub fn context_lines_around_matches(&self) -> (u32, u32) {
+        (
+            self.context_lines_before.unwrap_or(self.context_lines),
+            self.context_lines_after.unwrap_or(self.context_lines),
+        )
+    }
+}
+
+impl Settings for SearchSettings {
+    const KEY: Option<&'static str> = Some("search");
+
+    type FileContent = SearchSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
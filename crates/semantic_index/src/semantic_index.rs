@@ -223,6 +223,10 @@ impl ProjectIndex {
         }
     }
 
+    pub fn status(&self) -> Status {
+        self.last_status
+    }
+
     pub fn search(&self, query: &str, limit: usize, cx: &AppContext) -> Task<Vec<SearchResult>> {
         let mut worktree_searches = Vec::new();
         for worktree_index in self.worktree_indices.values() {
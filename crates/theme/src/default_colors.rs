@@ -55,6 +55,7 @@ impl ThemeColors {
             tab_inactive_background: neutral().light().step_2(),
             tab_active_background: neutral().light().step_1(),
             search_match_background: neutral().light().step_5(),
+            search_active_match_background: blue().light().step_5(),
             panel_background: neutral().light().step_2(),
             panel_focused_border: blue().light().step_5(),
             pane_focused_border: blue().light().step_5(),
@@ -150,6 +151,7 @@ impl ThemeColors {
             tab_inactive_background: neutral().dark().step_2(),
             tab_active_background: neutral().dark().step_1(),
             search_match_background: neutral().dark().step_5(),
+            search_active_match_background: blue().dark().step_5(),
             panel_background: neutral().dark().step_2(),
             panel_focused_border: blue().dark().step_5(),
             pane_focused_border: blue().dark().step_5(),
@@ -80,6 +80,7 @@ pub(crate) fn one_dark() -> Theme {
                 tab_inactive_background: bg,
                 tab_active_background: editor,
                 search_match_background: bg,
+                search_active_match_background: elevated_surface,
 
                 editor_background: editor,
                 editor_gutter_background: editor,
@@ -308,6 +308,9 @@ pub struct ThemeColorsContent {
     #[serde(rename = "search.match_background")]
     pub search_match_background: Option<String>,
 
+    #[serde(rename = "search.active_match_background")]
+    pub search_active_match_background: Option<String>,
+
     #[serde(rename = "panel.background")]
     pub panel_background: Option<String>,
 
@@ -666,6 +669,10 @@ impl ThemeColorsContent {
                 .search_match_background
                 .as_ref()
                 .and_then(|color| try_parse_color(color).ok()),
+            search_active_match_background: self
+                .search_active_match_background
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
             panel_background: self
                 .panel_background
                 .as_ref()
@@ -262,17 +262,21 @@ impl<P> PathLikeWithPosition<P> {
 pub struct PathMatcher {
     maybe_path: PathBuf,
     glob: GlobMatcher,
+    negated: bool,
 }
 
 impl std::fmt::Display for PathMatcher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
         self.maybe_path.to_string_lossy().fmt(f)
     }
 }
 
 impl PartialEq for PathMatcher {
     fn eq(&self, other: &Self) -> bool {
-        self.maybe_path.eq(&other.maybe_path)
+        self.maybe_path.eq(&other.maybe_path) && self.negated == other.negated
     }
 }
 
@@ -283,9 +287,27 @@ impl PathMatcher {
         Ok(PathMatcher {
             glob: Glob::new(maybe_glob)?.compile_matcher(),
             maybe_path: PathBuf::from(maybe_glob),
+            negated: false,
         })
     }
 
+    /// Creates a matcher for `maybe_glob`, negated if it starts with `!` like in gitignore files.
+    pub fn negatable(maybe_glob: &str) -> Result<Self, globset::Error> {
+        match maybe_glob.strip_prefix('!') {
+            Some(negated_glob) => Ok(PathMatcher {
+                negated: true,
+                ..PathMatcher::new(negated_glob)?
+            }),
+            None => PathMatcher::new(maybe_glob),
+        }
+    }
+
+    /// Whether the paths this matcher matches are taken out of the ones matched by the globs
+    /// before it, rather than added to them. [`PathMatcher::is_match`] ignores the negation.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
     pub fn is_match<P: AsRef<Path>>(&self, other: P) -> bool {
         let other_path = other.as_ref();
         other_path.starts_with(&self.maybe_path)
@@ -1,5 +1,5 @@
 use gpui::{actions, impl_actions, ViewContext};
-use search::{buffer_search, BufferSearchBar, SearchOptions};
+use search::{buffer_search, BufferSearchBar, ProjectSearchView, SearchOptions};
 use serde_derive::Deserialize;
 use workspace::{searchable::Direction, Workspace};
 
@@ -193,8 +193,17 @@ pub fn move_to_match_internal(
     Vim::update(cx, |vim, cx| {
         let pane = workspace.active_pane().clone();
         let count = vim.take_count(cx).unwrap_or(1);
-        let prior_selections = vim.editor_selections(cx);
 
+        // in the results of a project search, move through its matches instead.
+        if let Some(search_view) = workspace.active_item_as::<ProjectSearchView>(cx) {
+            if search_view.update(cx, |search_view, cx| {
+                search_view.select_match_in_results(direction, count, cx)
+            }) {
+                return;
+            }
+        }
+
+        let prior_selections = vim.editor_selections(cx);
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {